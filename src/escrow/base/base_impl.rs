@@ -1,10 +1,10 @@
-use near_sdk::{ env, AccountId, IntoStorageKey };
+use near_sdk::{ env, AccountId, IntoStorageKey, Promise };
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::collections::{ TreeMap, LookupMap, UnorderedSet };
 use crate::escrow::base::{ EscrowCore, EscrowEnumeration };
 use near_sdk::json_types::U128;
-use crate::escrow::metadata::{ EscrowEnum, JsonEscrow };
-use crate::escrow::{ EscrowOfferId };
+use crate::escrow::metadata::{ EscrowEnum, EscrowAsset, EscrowFill, JsonEscrow };
+use crate::escrow::{ EscrowOfferId, TokenId, escrow_asset_key };
 use crate::utils::assert_tx_money;
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -17,16 +17,44 @@ pub struct EscrowFeature {
 
     pub offer_owner_by_account: LookupMap<EscrowOfferId, AccountId>,
     pub offer_receiver_by_account: LookupMap<EscrowOfferId, AccountId>,
+
+    pub offer_expires_at_by_id: LookupMap<EscrowOfferId, u64>,
+    pub offers_by_expires_at: TreeMap<(u64, EscrowOfferId), ()>,
+
+    // offers without receiver, indexed by requested asset
+    pub open_offers: UnorderedSet<EscrowOfferId>,
+    pub open_offers_by_asset: LookupMap<String, UnorderedSet<EscrowOfferId>>,
+
+    pub offer_fill_by_id: LookupMap<EscrowOfferId, EscrowFill>,
+
+    // basket and fill transfers failed to send, claimed by the account later
+    pub claims_by_account: LookupMap<AccountId, Vec<EscrowAsset>>,
+
+    // counter offers chain by the first offer
+    pub offer_thread_by_id: LookupMap<EscrowOfferId, EscrowOfferId>,
+    pub threads_by_id: LookupMap<EscrowOfferId, Vec<JsonEscrow>>,
+
+    // last offer number of the account, offer ids are never reused
+    pub offer_nonce_by_account: LookupMap<AccountId, u64>,
 }
 
 impl EscrowFeature {
-    pub fn new<O, OT, OF, OO, OR, OA>(
+    pub fn new<O, OT, OF, OO, OR, OA, OE, OEI, OP, OPA, OFL, OTH, OTHS, OCL, ON>(
         offer_prefix: O,
         offer_to_account_prefix: OT,
         offer_from_account_prefix: OF,
         offer_owner_account_prefix: OO,
         offer_receiver_account_prefix: OR,
-        offer_accepted_prefix: OA
+        offer_accepted_prefix: OA,
+        offer_expires_at_prefix: OE,
+        offers_by_expires_at_prefix: OEI,
+        open_offers_prefix: OP,
+        open_offers_by_asset_prefix: OPA,
+        offer_fill_prefix: OFL,
+        offer_thread_prefix: OTH,
+        threads_prefix: OTHS,
+        claims_prefix: OCL,
+        offer_nonce_prefix: ON
     )
         -> Self
         where
@@ -35,7 +63,16 @@ impl EscrowFeature {
             OF: IntoStorageKey,
             OO: IntoStorageKey,
             OR: IntoStorageKey,
-            OA: IntoStorageKey
+            OA: IntoStorageKey,
+            OE: IntoStorageKey,
+            OEI: IntoStorageKey,
+            OP: IntoStorageKey,
+            OPA: IntoStorageKey,
+            OFL: IntoStorageKey,
+            OTH: IntoStorageKey,
+            OTHS: IntoStorageKey,
+            OCL: IntoStorageKey,
+            ON: IntoStorageKey
     {
         let this = Self {
             offer_accepted_by_id: LookupMap::new(offer_accepted_prefix),
@@ -44,6 +81,15 @@ impl EscrowFeature {
             offers_for_account: TreeMap::new(offer_from_account_prefix),
             offer_owner_by_account: LookupMap::new(offer_owner_account_prefix),
             offer_receiver_by_account: LookupMap::new(offer_receiver_account_prefix),
+            offer_expires_at_by_id: LookupMap::new(offer_expires_at_prefix),
+            offers_by_expires_at: TreeMap::new(offers_by_expires_at_prefix),
+            open_offers: UnorderedSet::new(open_offers_prefix),
+            open_offers_by_asset: LookupMap::new(open_offers_by_asset_prefix),
+            offer_fill_by_id: LookupMap::new(offer_fill_prefix),
+            offer_thread_by_id: LookupMap::new(offer_thread_prefix),
+            threads_by_id: LookupMap::new(threads_prefix),
+            claims_by_account: LookupMap::new(claims_prefix),
+            offer_nonce_by_account: LookupMap::new(offer_nonce_prefix),
         };
 
         this
@@ -56,12 +102,65 @@ impl EscrowCore for EscrowFeature {
 
         let signer_id = env::predecessor_account_id();
         let owner_id = self.offer_owner_by_account.get(&offer_id).expect("Not found");
-        let receiver_id = self.offer_receiver_by_account.get(&offer_id).expect("Not found");
+        let receiver_id = self.offer_receiver_by_account.get(&offer_id);
 
-        assert_eq!(owner_id, signer_id, "Unauthorized");
+        // basket deposits of the receiver are refunded on cancel as well
+        let is_basket = matches!(self.offer_by_id.get(&offer_id), Some(EscrowEnum::Basket { .. }));
+
+        assert!(
+            owner_id == signer_id || (is_basket && receiver_id.as_ref() == Some(&signer_id)),
+            "Unauthorized"
+        );
 
         self.internal_withdraw_offer(&offer_id, &owner_id, &receiver_id)
     }
+
+    fn escrow_basket_offer(
+        &mut self,
+        receiver_id: AccountId,
+        assets_in: Vec<EscrowAsset>,
+        assets_out: Vec<EscrowAsset>,
+        expires_at: Option<u64>
+    ) -> JsonEscrow {
+        let sender_id = env::predecessor_account_id();
+
+        self.internal_make_basket_offer(
+            &sender_id,
+            &receiver_id,
+            &assets_in,
+            &assets_out,
+            &expires_at
+        )
+    }
+
+    fn escrow_refund_expired(&mut self, limit: Option<u64>) -> u64 {
+        self.internal_refund_expired(&limit)
+    }
+
+    fn escrow_basket_settle(&mut self, offer_id: EscrowOfferId) {
+        self.internal_settle_basket(&offer_id)
+    }
+
+    fn escrow_claim(&mut self) -> u64 {
+        let account_id = env::predecessor_account_id();
+
+        self.internal_claim(&account_id)
+    }
+
+    fn escrow_basket_deposit(&mut self, offer_id: EscrowOfferId) {
+        let sender_id = env::predecessor_account_id();
+        let deposit = env::attached_deposit();
+
+        let unused = self.internal_basket_deposit(
+            &offer_id,
+            &sender_id,
+            &(EscrowAsset::Near { amount: U128(deposit) })
+        );
+
+        if unused.0 > 0 {
+            Promise::new(sender_id).transfer(unused.0);
+        }
+    }
 }
 
 impl EscrowEnumeration for EscrowFeature {
@@ -94,4 +193,45 @@ impl EscrowEnumeration for EscrowFeature {
     fn escrow_offers_total_for_owner(&self, account_id: AccountId) -> u64 {
         self.internal_total_offers_for_owner(&account_id)
     }
+
+    fn escrow_claims(&self, account_id: AccountId) -> Vec<EscrowAsset> {
+        self.claims_by_account.get(&account_id).unwrap_or_default()
+    }
+
+    fn escrow_offer_thread(&self, offer_id: EscrowOfferId) -> Vec<JsonEscrow> {
+        self.internal_find_offer_thread(&offer_id)
+    }
+
+    fn escrow_open_offers(&self, limit: Option<u64>, offset: Option<U128>) -> Vec<JsonEscrow> {
+        self.internal_find_open_offers(&self.open_offers, &limit, &offset)
+    }
+
+    fn escrow_open_offers_by_asset(
+        &self,
+        contract_id: AccountId,
+        token_id: Option<TokenId>,
+        limit: Option<u64>,
+        offset: Option<U128>
+    ) -> Vec<JsonEscrow> {
+        let asset_key = escrow_asset_key(&contract_id, &token_id);
+
+        if let Some(offers) = self.open_offers_by_asset.get(&asset_key) {
+            self.internal_find_open_offers(&offers, &limit, &offset)
+        } else {
+            vec![]
+        }
+    }
+
+    fn escrow_open_offers_total(&self) -> u64 {
+        self.open_offers.len()
+    }
+
+    fn escrow_offers_expiring(
+        &self,
+        before: u64,
+        limit: Option<u64>,
+        offset: Option<U128>
+    ) -> Vec<JsonEscrow> {
+        self.internal_find_offers_expiring(&before, &limit, &offset)
+    }
 }
@@ -0,0 +1,247 @@
+use crate::escrow::{ EscrowFeature, EscrowEnum, EscrowAsset, EscrowOfferId, JsonEscrow };
+use crate::escrow::base::ext_self;
+use near_sdk::{ AccountId, env, require, Gas, Promise };
+use near_sdk::json_types::U128;
+use crate::ft::base::core_impl::GAS_FOR_FT_TRANSFER;
+use crate::nft::base::GAS_FOR_NFT_TRANSFER;
+use crate::mt::base::{ ext_mt, GAS_FOR_MT_TRANSFER };
+
+// both sides with their resolvers fit into one call
+pub const ESCROW_MAX_BASKET_ASSETS: usize = 4;
+
+const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+
+impl EscrowFeature {
+    pub(crate) fn assert_valid_basket(&self, assets: &Vec<EscrowAsset>) {
+        assert!(
+            !assets.is_empty() && assets.len() <= ESCROW_MAX_BASKET_ASSETS,
+            "Invalid assets count"
+        );
+
+        for (index, asset) in assets.iter().enumerate() {
+            assert!(asset.amount().0 > 0, "Invalid amount");
+            assert!(
+                !assets[..index].iter().any(|other| other.is_same(&asset)),
+                "Duplicated asset"
+            );
+        }
+    }
+
+    pub(crate) fn internal_make_basket_offer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        assets_in: &Vec<EscrowAsset>,
+        assets_out: &Vec<EscrowAsset>,
+        expires_at: &Option<u64>
+    ) -> JsonEscrow {
+        assert_ne!(&sender_id, &receiver_id, "Not self");
+
+        self.assert_valid_expiration(&expires_at);
+
+        self.assert_valid_basket(&assets_in);
+        self.assert_valid_basket(&assets_out);
+
+        self.internal_make_offer(
+            &(EscrowEnum::Basket {
+                assets_in: assets_in.clone(),
+                assets_out: assets_out.clone(),
+                funded_in: vec![U128(0); assets_in.len()],
+                funded_out: vec![U128(0); assets_out.len()],
+            }),
+            &sender_id,
+            &Some(receiver_id.clone()),
+            None,
+            expires_at.clone()
+        )
+    }
+
+    /// adds the deposit to the side of `sender_id`, returns not used amount
+    pub(crate) fn internal_basket_deposit(
+        &mut self,
+        offer_id: &EscrowOfferId,
+        sender_id: &AccountId,
+        asset: &EscrowAsset
+    ) -> U128 {
+        let mut offer = self.offer_by_id.get(&offer_id).expect("Not found offer");
+        let owner_id = self.offer_owner_by_account.get(&offer_id).expect("Not found");
+        let receiver_id = self.offer_receiver_by_account.get(&offer_id).expect("Not found");
+
+        assert!(!self.internal_offer_is_expired(&offer_id), "Offer is expired");
+
+        let accepted = match &mut offer {
+            EscrowEnum::Basket { assets_in, assets_out, funded_in, funded_out } => {
+                let (assets, funded) = if sender_id == &owner_id {
+                    (assets_in, funded_in)
+                } else if sender_id == &receiver_id {
+                    (assets_out, funded_out)
+                } else {
+                    env::panic_str("Unauthorized")
+                };
+
+                let index = assets
+                    .iter()
+                    .position(|item| item.is_same(&asset))
+                    .expect("Asset is not required");
+                let required = assets[index].amount().0 - funded[index].0;
+
+                assert!(required > 0, "Asset is already funded");
+
+                let accepted = std::cmp::min(required, asset.amount().0);
+
+                funded[index] = U128(funded[index].0 + accepted);
+
+                accepted
+            }
+            _ => env::panic_str("Not basket offer"),
+        };
+
+        self.offer_by_id.insert(&offer_id, &offer);
+
+        U128(asset.amount().0 - accepted)
+    }
+
+    pub(crate) fn internal_basket_is_funded(&self, offer: &EscrowEnum) -> bool {
+        match offer {
+            EscrowEnum::Basket { assets_in, assets_out, funded_in, funded_out } => {
+                let is_funded = |assets: &Vec<EscrowAsset>, funded: &Vec<U128>| {
+                    assets
+                        .iter()
+                        .zip(funded.iter())
+                        .all(|(asset, funded)| asset.amount() == *funded)
+                };
+
+                is_funded(assets_in, funded_in) && is_funded(assets_out, funded_out)
+            }
+            _ => false,
+        }
+    }
+
+    /// both sides are kept by the contract, so the swap can not be half done
+    pub(crate) fn internal_settle_basket(&mut self, offer_id: &EscrowOfferId) {
+        let offer = self.offer_by_id.get(&offer_id).expect("Not found offer");
+        let owner_id = self.offer_owner_by_account.get(&offer_id).expect("Not found");
+        let receiver_id = self.offer_receiver_by_account.get(&offer_id).expect("Not found");
+
+        assert!(self.internal_basket_is_funded(&offer), "Basket is not funded");
+
+        if let EscrowEnum::Basket { assets_in, assets_out, .. } = offer {
+            let transfers: Vec<(AccountId, EscrowAsset)> = assets_in
+                .into_iter()
+                .map(|asset| (receiver_id.clone(), asset))
+                .chain(assets_out.into_iter().map(|asset| (owner_id.clone(), asset)))
+                .collect();
+
+            self.internal_remove_offer(&offer_id);
+            self.internal_transfer_assets(&transfers);
+        }
+    }
+
+    /// returns deposited parts of both sides
+    pub(crate) fn internal_refund_basket(
+        &mut self,
+        offer: &EscrowEnum,
+        owner_id: &AccountId,
+        receiver_id: &AccountId
+    ) {
+        if let EscrowEnum::Basket { assets_in, assets_out, funded_in, funded_out } = offer {
+            let funded = |account_id: &AccountId, assets: &Vec<EscrowAsset>, funded: &Vec<U128>| {
+                assets
+                    .iter()
+                    .zip(funded.iter())
+                    .filter(|(_, funded)| funded.0 > 0)
+                    .map(|(asset, funded)| (account_id.clone(), asset.with_amount(*funded)))
+                    .collect::<Vec<(AccountId, EscrowAsset)>>()
+            };
+
+            let mut transfers = funded(&owner_id, &assets_in, &funded_in);
+            transfers.extend(funded(&receiver_id, &assets_out, &funded_out));
+
+            self.internal_transfer_assets(&transfers);
+        }
+    }
+
+    /// sends the assets, failed ones are added to the claims of the receiver
+    pub(crate) fn internal_transfer_assets(&mut self, transfers: &Vec<(AccountId, EscrowAsset)>) {
+        let gas = transfers
+            .iter()
+            .fold(Gas(0), |gas, (_, asset)| {
+                gas + self.internal_asset_transfer_gas(&asset) + GAS_FOR_RESOLVE_TRANSFER
+            });
+
+        require!(env::prepaid_gas() > gas, "More gas is required");
+
+        for (account_id, asset) in transfers {
+            self.internal_transfer_asset(&account_id, &asset, &asset.amount()).then(
+                ext_self
+                    ::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .resolve_transfer(account_id.clone(), asset.clone())
+            );
+        }
+    }
+
+    pub(crate) fn internal_add_claim(&mut self, account_id: &AccountId, asset: &EscrowAsset) {
+        let mut claims = self.claims_by_account.get(&account_id).unwrap_or_default();
+
+        claims.push(asset.clone());
+
+        self.claims_by_account.insert(&account_id, &claims);
+    }
+
+    /// sends up to `ESCROW_MAX_BASKET_ASSETS` failed transfers again
+    pub(crate) fn internal_claim(&mut self, account_id: &AccountId) -> u64 {
+        let mut claims = self.claims_by_account.get(&account_id).expect("Not found claims");
+        let count = std::cmp::min(claims.len(), ESCROW_MAX_BASKET_ASSETS);
+
+        let transfers: Vec<(AccountId, EscrowAsset)> = claims
+            .drain(..count)
+            .map(|asset| (account_id.clone(), asset))
+            .collect();
+
+        if claims.is_empty() {
+            self.claims_by_account.remove(&account_id);
+        } else {
+            self.claims_by_account.insert(&account_id, &claims);
+        }
+
+        self.internal_transfer_assets(&transfers);
+
+        count as u64
+    }
+
+    fn internal_asset_transfer_gas(&self, asset: &EscrowAsset) -> Gas {
+        match asset {
+            EscrowAsset::Near { .. } => Gas(0),
+            EscrowAsset::Ft { .. } => GAS_FOR_FT_TRANSFER,
+            EscrowAsset::Nft { .. } => GAS_FOR_NFT_TRANSFER,
+            EscrowAsset::Mt { .. } => GAS_FOR_MT_TRANSFER,
+        }
+    }
+
+    pub(crate) fn internal_transfer_asset(
+        &mut self,
+        receiver_id: &AccountId,
+        asset: &EscrowAsset,
+        amount: &U128
+    ) -> Promise {
+        match asset {
+            EscrowAsset::Near { .. } => Promise::new(receiver_id.clone()).transfer(amount.0),
+            EscrowAsset::Ft { ft_contract_id, .. } =>
+                self.internal_transfer_ft(&receiver_id, &ft_contract_id, &amount),
+            EscrowAsset::Nft { nft_contract_id, nft_token_id } =>
+                self.internal_transfer_nft(&receiver_id, &nft_contract_id, &nft_token_id),
+            EscrowAsset::Mt { mt_contract_id, mt_token_id, .. } =>
+                ext_mt
+                    ::ext(mt_contract_id.clone())
+                    .with_static_gas(GAS_FOR_MT_TRANSFER)
+                    .with_attached_deposit(1)
+                    .mt_batch_transfer(
+                        receiver_id.clone(),
+                        vec![AccountId::new_unchecked(mt_token_id.clone())],
+                        vec![amount.clone()],
+                        Some("Escrow transfer".to_string())
+                    ),
+        }
+    }
+}
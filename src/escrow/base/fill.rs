@@ -0,0 +1,96 @@
+use crate::escrow::{ EscrowFeature, EscrowEnum, EscrowAsset, EscrowFill, EscrowOfferId, MfEscrowFill };
+use near_sdk::{ AccountId, env };
+use near_sdk::json_types::U128;
+use crate::utils::mul_div;
+
+impl EscrowFeature {
+    pub(crate) fn internal_add_offer_fill(&mut self, offer_id: &EscrowOfferId) {
+        let offer = self.offer_by_id.get(&offer_id).expect("Not found offer");
+
+        match offer {
+            EscrowEnum::FtToFt { amount_in, amount_out, .. } => {
+                self.offer_fill_by_id.insert(&offer_id, &(EscrowFill {
+                    amount_in,
+                    amount_out,
+                    filled_in: U128(0),
+                    filled_out: U128(0),
+                }));
+            }
+            _ => env::panic_str("Only ft to ft offer can be filled by parts"),
+        }
+    }
+
+    /// Fills the part of offer for `amount` of requested ft, returns not used amount.
+    /// The taker gets `amount * amount_in / amount_out` of the original offer rounded down,
+    /// the last fill takes the rest.
+    pub(crate) fn internal_fill_offer(
+        &mut self,
+        owner_id: &AccountId,
+        taker_id: &AccountId,
+        offer_id: &EscrowOfferId,
+        ft_contract_id: &AccountId,
+        amount: &U128
+    ) -> U128 {
+        let mut offer = self.offer_by_id.get(&offer_id).expect("Not found offer");
+        let mut fill = self.offer_fill_by_id.get(&offer_id).expect("Not found fill");
+
+        self.assert_can_accept(&offer_id, &owner_id, &taker_id);
+
+        let (ft_contract_id_in, paid, given, remaining_in, remaining_out) = match &mut offer {
+            EscrowEnum::FtToFt { ft_contract_id_in, amount_in, ft_contract_id_out, amount_out } => {
+                assert_eq!(ft_contract_id_out, ft_contract_id, "Invalid ft token");
+
+                let paid = std::cmp::min(amount.0, amount_out.0);
+                let given = if paid == amount_out.0 {
+                    amount_in.0
+                } else {
+                    mul_div(paid, fill.amount_in.0, fill.amount_out.0)
+                };
+
+                assert!(given > 0, "Fill is too small");
+
+                *amount_in = U128(amount_in.0 - given);
+                *amount_out = U128(amount_out.0 - paid);
+
+                (ft_contract_id_in.clone(), paid, given, *amount_in, *amount_out)
+            }
+            _ => env::panic_str("Not partial offer"),
+        };
+
+        fill.filled_in = U128(fill.filled_in.0 + given);
+        fill.filled_out = U128(fill.filled_out.0 + paid);
+
+        if remaining_out.0 == 0 {
+            self.internal_remove_offer(&offer_id);
+        } else {
+            self.offer_by_id.insert(&offer_id, &offer);
+            self.offer_fill_by_id.insert(&offer_id, &fill);
+        }
+
+        // both parts are kept by the contract, failed parts are left to `escrow_claim`
+        self.internal_transfer_assets(
+            &vec![
+                (taker_id.clone(), EscrowAsset::Ft {
+                    ft_contract_id: ft_contract_id_in,
+                    amount: U128(given),
+                }),
+                (owner_id.clone(), EscrowAsset::Ft {
+                    ft_contract_id: ft_contract_id.clone(),
+                    amount: U128(paid),
+                })
+            ]
+        );
+
+        (MfEscrowFill {
+            offer_id: &offer_id,
+            owner_id: &owner_id,
+            taker_id: &taker_id,
+            amount_in: &U128(given),
+            amount_out: &U128(paid),
+            remaining_in: &remaining_in,
+            remaining_out: &remaining_out,
+        }).emit();
+
+        U128(amount.0 - paid)
+    }
+}
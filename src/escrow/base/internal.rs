@@ -1,4 +1,4 @@
-use crate::escrow::{ EscrowFeature, JsonEscrow, EscrowEnum, ContractId, TokenId, EscrowOfferId };
+use crate::escrow::{ EscrowFeature, JsonEscrow, EscrowEnum, EscrowFill, ContractId, TokenId, EscrowOfferId };
 use near_sdk::{ AccountId, require, env, Gas, Promise, BorshStorageKey };
 use near_sdk::json_types::U128;
 use near_sdk::collections::{ UnorderedSet };
@@ -12,6 +12,8 @@ use crate::escrow::base::{ ext_self };
 pub(crate) const GAS_FOR_RESOLVE_REMOVE: Gas = Gas(25_000_000_000_000);
 pub(crate) const GAS_FOR_RESOLVE_ACCEPT: Gas = Gas(25_000_000_000_000);
 
+pub const ESCROW_MAX_REFUND_EXPIRED: usize = 5;
+
 /// Helper structure to for keys of the persistent collections.
 #[derive(BorshStorageKey, BorshSerialize)]
 pub enum StorageKey {
@@ -21,6 +23,16 @@ pub enum StorageKey {
     EscrowOffersForAccount {
         account_hash: Vec<u8>,
     },
+    EscrowOpenOffersByAsset {
+        asset_hash: Vec<u8>,
+    },
+}
+
+pub fn escrow_asset_key(contract_id: &ContractId, token_id: &Option<TokenId>) -> String {
+    match token_id {
+        Some(token_id) => format!("{}:{}", contract_id, token_id),
+        None => contract_id.to_string(),
+    }
 }
 
 impl EscrowFeature {
@@ -30,31 +42,54 @@ impl EscrowFeature {
         &mut self,
         offer: &EscrowEnum,
         sender_id: &AccountId,
-        receiver_id: &AccountId,
-        offer_id: Option<EscrowOfferId>
+        receiver_id: &Option<AccountId>,
+        offer_id: Option<EscrowOfferId>,
+        expires_at: Option<u64>
     ) -> JsonEscrow {
-        let total = self.internal_total_offers_by_owner(&sender_id);
-        let offer_id = offer_id.unwrap_or_else(|| format!("{}-{}", sender_id.clone(), total + 1));
+        let offer_id = offer_id.unwrap_or_else(|| {
+            let nonce = self.offer_nonce_by_account.get(&sender_id).unwrap_or(0) + 1;
+
+            self.offer_nonce_by_account.insert(&sender_id, &nonce);
+
+            format!("{}-{}", sender_id.clone(), nonce)
+        });
 
         let mut sender_offers = self.offers_by_account.get(&sender_id).unwrap_or_else(|| {
             UnorderedSet::new(StorageKey::EscrowOffersByAccount {
                 account_hash: env::sha256(sender_id.as_bytes()),
             })
         });
-        let mut receiver_offers = self.offers_for_account.get(&receiver_id).unwrap_or_else(|| {
-            UnorderedSet::new(StorageKey::EscrowOffersForAccount {
-                account_hash: env::sha256(receiver_id.as_bytes()),
-            })
-        });
 
         sender_offers.insert(&offer_id);
-        receiver_offers.insert(&offer_id);
 
         self.offer_by_id.insert(&offer_id, &offer);
         self.offers_by_account.insert(&sender_id, &sender_offers);
-        self.offers_for_account.insert(&receiver_id, &receiver_offers);
         self.offer_owner_by_account.insert(&offer_id, &sender_id);
-        self.offer_receiver_by_account.insert(&offer_id, &receiver_id);
+
+        if let Some(receiver_id) = receiver_id {
+            let mut receiver_offers = self.offers_for_account.get(&receiver_id).unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::EscrowOffersForAccount {
+                    account_hash: env::sha256(receiver_id.as_bytes()),
+                })
+            });
+
+            receiver_offers.insert(&offer_id);
+
+            self.offers_for_account.insert(&receiver_id, &receiver_offers);
+            self.offer_receiver_by_account.insert(&offer_id, &receiver_id);
+        } else {
+            self.internal_add_open_offer(&offer_id, &offer);
+        }
+
+        if let Some(expires_at) = expires_at {
+            self.offer_expires_at_by_id.insert(&offer_id, &expires_at);
+
+            // expired offer restored after a failed refund is parked out of the refund queue,
+            // so it does not block the next offers, the owner removes it with `escrow_remove_offer`
+            if expires_at > env::block_timestamp() {
+                self.offers_by_expires_at.insert(&(expires_at, offer_id.clone()), &());
+            }
+        }
 
         JsonEscrow {
             offer_id,
@@ -62,39 +97,121 @@ impl EscrowFeature {
             receiver_id: receiver_id.clone(),
             data: offer.clone(),
             is_accepted: false,
+            expires_at,
+            fill: None,
+        }
+    }
+
+    /// puts back the offer removed before a failed transfer
+    pub(crate) fn internal_restore_offer(
+        &mut self,
+        offer: &EscrowEnum,
+        owner_id: &AccountId,
+        receiver_id: &Option<AccountId>,
+        offer_id: &EscrowOfferId,
+        expires_at: Option<u64>,
+        fill: &Option<EscrowFill>
+    ) {
+        self.internal_make_offer(&offer, &owner_id, &receiver_id, Some(offer_id.clone()), expires_at);
+
+        if let Some(fill) = fill {
+            self.offer_fill_by_id.insert(&offer_id, &fill);
         }
     }
 
     pub(crate) fn internal_remove_offer(&mut self, offer_id: &EscrowOfferId) {
         let owner_id = self.offer_owner_by_account.get(&offer_id).expect("Not found");
-        let receiver_id = self.offer_receiver_by_account.get(&offer_id).expect("Not found");
+        let offer = self.offer_by_id.get(&offer_id).expect("Not found");
 
         let mut owner_offers = self.offers_by_account.get(&owner_id).expect("Not found sender");
-        let mut receiver_offers = self.offers_for_account
-            .get(&receiver_id)
-            .expect("Not found receiver");
 
         owner_offers.remove(&offer_id);
-        receiver_offers.remove(&offer_id);
 
         self.offer_by_id.remove(offer_id);
         self.offer_owner_by_account.remove(&offer_id);
-        self.offer_receiver_by_account.remove(&offer_id);
         self.offers_by_account.insert(&owner_id, &owner_offers);
-        self.offers_for_account.insert(&receiver_id, &receiver_offers);
+
+        if let Some(receiver_id) = self.offer_receiver_by_account.remove(&offer_id) {
+            let mut receiver_offers = self.offers_for_account
+                .get(&receiver_id)
+                .expect("Not found receiver");
+
+            receiver_offers.remove(&offer_id);
+
+            self.offers_for_account.insert(&receiver_id, &receiver_offers);
+        } else {
+            self.internal_remove_open_offer(&offer_id, &offer);
+        }
+
+        if let Some(expires_at) = self.offer_expires_at_by_id.remove(&offer_id) {
+            self.offers_by_expires_at.remove(&(expires_at, offer_id.clone()));
+        }
+
+        self.offer_fill_by_id.remove(&offer_id);
+    }
+
+    /// requested asset of an offer, nft is keyed with the token
+    pub(crate) fn internal_offer_asset_key(&self, offer: &EscrowEnum) -> Option<String> {
+        match offer {
+            EscrowEnum::FtToFt { ft_contract_id_out, .. } => Some(ft_contract_id_out.to_string()),
+            EscrowEnum::NftToFt { ft_contract_id_out, .. } => Some(ft_contract_id_out.to_string()),
+            EscrowEnum::FtToNft { nft_contract_id_out, nft_token_id_out, .. } =>
+                Some(escrow_asset_key(&nft_contract_id_out, &Some(nft_token_id_out.clone()))),
+            EscrowEnum::NftToNft { nft_contract_id_out, nft_token_id_out, .. } =>
+                Some(escrow_asset_key(&nft_contract_id_out, &Some(nft_token_id_out.clone()))),
+            EscrowEnum::Basket { .. } => None,
+        }
+    }
+
+    fn internal_add_open_offer(&mut self, offer_id: &EscrowOfferId, offer: &EscrowEnum) {
+        let asset_key = self.internal_offer_asset_key(&offer).expect("Offer requires receiver");
+
+        let mut asset_offers = self.open_offers_by_asset.get(&asset_key).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::EscrowOpenOffersByAsset {
+                asset_hash: env::sha256(asset_key.as_bytes()),
+            })
+        });
+
+        asset_offers.insert(&offer_id);
+
+        self.open_offers.insert(&offer_id);
+        self.open_offers_by_asset.insert(&asset_key, &asset_offers);
+    }
+
+    fn internal_remove_open_offer(&mut self, offer_id: &EscrowOfferId, offer: &EscrowEnum) {
+        self.open_offers.remove(&offer_id);
+
+        if let Some(asset_key) = self.internal_offer_asset_key(&offer) {
+            if let Some(mut asset_offers) = self.open_offers_by_asset.get(&asset_key) {
+                asset_offers.remove(&offer_id);
+
+                if asset_offers.is_empty() {
+                    self.open_offers_by_asset.remove(&asset_key);
+                } else {
+                    self.open_offers_by_asset.insert(&asset_key, &asset_offers);
+                }
+            }
+        }
     }
 
     pub(crate) fn internal_withdraw_offer(
         &mut self,
         offer_id: &EscrowOfferId,
         owner_id: &AccountId,
-        receiver_id: &AccountId
+        receiver_id: &Option<AccountId>
     ) {
         let offer = self.offer_by_id.get(&offer_id).expect("Not found");
+        let expires_at = self.offer_expires_at_by_id.get(&offer_id);
+        let fill = self.offer_fill_by_id.get(&offer_id);
 
         self.internal_remove_offer(&offer_id);
 
         match &offer {
+            EscrowEnum::Basket { .. } => {
+                let receiver_id = receiver_id.as_ref().expect("Not found receiver");
+
+                self.internal_refund_basket(&offer, &owner_id, &receiver_id);
+            }
             EscrowEnum::FtToFt {
                 ft_contract_id_in,
                 amount_in,
@@ -114,7 +231,9 @@ impl EscrowFeature {
                             owner_id.clone(),
                             receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -137,7 +256,9 @@ impl EscrowFeature {
                             owner_id.clone(),
                             receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -160,7 +281,9 @@ impl EscrowFeature {
                             owner_id.clone(),
                             receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -183,7 +306,9 @@ impl EscrowFeature {
                             owner_id.clone(),
                             receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -192,6 +317,71 @@ impl EscrowFeature {
 
     //
 
+    pub(crate) fn assert_valid_expiration(&self, expires_at: &Option<u64>) {
+        if let Some(expires_at) = expires_at {
+            assert!(expires_at > &env::block_timestamp(), "Invalid expiration");
+        }
+    }
+
+    /// returns receiver of the offer, open offers are accepted by any account
+    pub(crate) fn assert_can_accept(
+        &self,
+        offer_id: &EscrowOfferId,
+        owner_id: &AccountId,
+        account_id: &AccountId
+    ) -> Option<AccountId> {
+        let offer_receiver_id = self.offer_receiver_by_account.get(&offer_id);
+
+        if let Some(offer_receiver_id) = &offer_receiver_id {
+            assert_eq!(offer_receiver_id, account_id, "Not found offer");
+        } else {
+            assert_ne!(owner_id, account_id, "Not self");
+        }
+
+        assert!(!self.internal_offer_is_expired(&offer_id), "Offer is expired");
+        assert!(self.internal_offer_is_latest(&offer_id), "Offer is superseded");
+
+        offer_receiver_id
+    }
+
+    pub(crate) fn internal_offer_is_expired(&self, offer_id: &EscrowOfferId) -> bool {
+        self.offer_expires_at_by_id
+            .get(&offer_id)
+            .map(|expires_at| expires_at <= env::block_timestamp())
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn internal_refund_expired(&mut self, limit: &Option<u64>) -> u64 {
+        let limit = limit
+            .map(|v| v as usize)
+            .unwrap_or(ESCROW_MAX_REFUND_EXPIRED)
+            .min(ESCROW_MAX_REFUND_EXPIRED);
+        require!(limit != 0, "Cannot provide limit of 0.");
+        require!(
+            env::prepaid_gas().0 > (GAS_FOR_RESOLVE_REMOVE.0 + GAS_FOR_NFT_TRANSFER.0) * (limit as u64),
+            "More gas is required"
+        );
+
+        let now = env::block_timestamp();
+        let expired: Vec<EscrowOfferId> = self.offers_by_expires_at
+            .iter()
+            .take_while(|((expires_at, _), _)| expires_at <= &now)
+            .take(limit)
+            .map(|((_, offer_id), _)| offer_id)
+            .collect();
+
+        for offer_id in expired.iter() {
+            let owner_id = self.offer_owner_by_account.get(&offer_id).expect("Not found");
+            let receiver_id = self.offer_receiver_by_account.get(&offer_id);
+
+            self.internal_withdraw_offer(&offer_id, &owner_id, &receiver_id);
+        }
+
+        expired.len() as u64
+    }
+
+    //
+
     pub(crate) fn internal_transfer_ft(
         &mut self,
         receiver_id: &AccountId,
@@ -234,9 +424,10 @@ impl EscrowFeature {
         amount: &U128
     ) {
         let offer = self.offer_by_id.get(&offer_id).expect("Not found offer");
-        let receiver_offers = self.offers_for_account.get(&receiver_id).expect("Not found offers");
+        let offer_receiver_id = self.assert_can_accept(&offer_id, &owner_id, &receiver_id);
 
-        assert!(receiver_offers.contains(&offer_id), "Not found offer");
+        let expires_at = self.offer_expires_at_by_id.get(&offer_id);
+        let fill = self.offer_fill_by_id.get(&offer_id);
 
         match &offer {
             EscrowEnum::FtToFt { ft_contract_id_out, amount_out, ft_contract_id_in, amount_in } => {
@@ -251,9 +442,11 @@ impl EscrowFeature {
                         .with_static_gas(GAS_FOR_RESOLVE_ACCEPT)
                         .resolve_accept_offer(
                             owner_id.clone(),
-                            receiver_id.clone(),
+                            offer_receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -278,9 +471,11 @@ impl EscrowFeature {
                         .with_static_gas(GAS_FOR_RESOLVE_ACCEPT)
                         .resolve_accept_offer(
                             owner_id.clone(),
-                            receiver_id.clone(),
+                            offer_receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -299,9 +494,10 @@ impl EscrowFeature {
         token_id: &TokenId
     ) {
         let offer = self.offer_by_id.get(&offer_id).expect("Not found offer");
-        let receiver_offers = self.offers_for_account.get(&receiver_id).expect("Not found offers");
+        let offer_receiver_id = self.assert_can_accept(&offer_id, &owner_id, &receiver_id);
 
-        assert!(receiver_offers.contains(&offer_id), "Not found offer");
+        let expires_at = self.offer_expires_at_by_id.get(&offer_id);
+        let fill = self.offer_fill_by_id.get(&offer_id);
 
         match &offer {
             EscrowEnum::FtToNft {
@@ -321,9 +517,11 @@ impl EscrowFeature {
                         .with_static_gas(GAS_FOR_RESOLVE_ACCEPT)
                         .resolve_accept_offer(
                             owner_id.clone(),
-                            receiver_id.clone(),
+                            offer_receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -348,9 +546,11 @@ impl EscrowFeature {
                         .with_static_gas(GAS_FOR_RESOLVE_ACCEPT)
                         .resolve_accept_offer(
                             owner_id.clone(),
-                            receiver_id.clone(),
+                            offer_receiver_id.clone(),
                             offer.clone(),
-                            offer_id.clone()
+                            offer_id.clone(),
+                            expires_at,
+                            fill.clone()
                         )
                 );
             }
@@ -387,9 +587,11 @@ impl EscrowFeature {
             return Some(JsonEscrow {
                 offer_id: offer_id.clone(),
                 data: offer,
-                receiver_id: self.offer_receiver_by_account.get(&offer_id).expect("Not receiver"),
+                receiver_id: self.offer_receiver_by_account.get(&offer_id),
                 sender_id: self.offer_owner_by_account.get(&offer_id).expect("Not found owner"),
                 is_accepted: self.offer_accepted_by_id.get(&offer_id).unwrap_or_else(|| false),
+                expires_at: self.offer_expires_at_by_id.get(&offer_id),
+                fill: self.offer_fill_by_id.get(&offer_id),
             });
         }
 
@@ -452,6 +654,49 @@ impl EscrowFeature {
             .collect()
     }
 
+    pub(crate) fn internal_find_open_offers(
+        &self,
+        offers: &UnorderedSet<EscrowOfferId>,
+        limit: &Option<u64>,
+        offset: &Option<U128>
+    ) -> Vec<JsonEscrow> {
+        let start_index: u128 = offset.map(From::from).unwrap_or_default();
+
+        if (offers.len() as u128) <= start_index {
+            return vec![];
+        }
+
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+
+        offers
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|offer_id| { self.enum_get_offer(&offer_id).expect("Not found offer") })
+            .collect()
+    }
+
+    pub(crate) fn internal_find_offers_expiring(
+        &self,
+        before: &u64,
+        limit: &Option<u64>,
+        offset: &Option<U128>
+    ) -> Vec<JsonEscrow> {
+        let start_index: u128 = offset.map(From::from).unwrap_or_default();
+
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+
+        self.offers_by_expires_at
+            .iter()
+            .take_while(|((expires_at, _), _)| expires_at <= before)
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|((_, offer_id), _)| { self.enum_get_offer(&offer_id).expect("Not found offer") })
+            .collect()
+    }
+
     pub(crate) fn internal_total_offers_by_owner(&self, account_id: &AccountId) -> u64 {
         let offers = self.offers_by_account.get(&account_id);
 
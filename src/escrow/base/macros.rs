@@ -13,17 +13,52 @@ macro_rules! impl_escrow_core {
 
             self.$tokens.escrow_remove_offer(offer_id)
           }
+
+          fn escrow_basket_offer(&mut self, receiver_id: AccountId, assets_in: Vec<$crate::escrow::EscrowAsset>, assets_out: Vec<$crate::escrow::EscrowAsset>, expires_at: Option<u64>) -> $crate::escrow::JsonEscrow {
+            self.$assert_use();
+
+            self.$tokens.escrow_basket_offer(receiver_id, assets_in, assets_out, expires_at)
+          }
+
+          #[payable]
+          fn escrow_basket_deposit(&mut self, offer_id: $crate::escrow::EscrowOfferId) {
+            self.$assert_use();
+
+            self.$tokens.escrow_basket_deposit(offer_id)
+          }
+
+          fn escrow_basket_settle(&mut self, offer_id: $crate::escrow::EscrowOfferId) {
+            self.$assert_use();
+
+            self.$tokens.escrow_basket_settle(offer_id)
+          }
+
+          fn escrow_claim(&mut self) -> u64 {
+            self.$assert_use();
+
+            self.$tokens.escrow_claim()
+          }
+
+          fn escrow_refund_expired(&mut self, limit: Option<u64>) -> u64 {
+            self.$assert_use();
+
+            self.$tokens.escrow_refund_expired(limit)
+          }
         }
 
         #[near_bindgen]
         impl EscrowResolver for $contract {
           #[private]
-          fn resolve_accept_offer(&mut self, owner_id: AccountId, receiver_id: AccountId, offer: $crate::escrow::EscrowEnum, offer_id: $crate::escrow::EscrowOfferId) -> bool {
-            self.$tokens.resolve_accept_offer(owner_id, receiver_id, offer, offer_id)
+          fn resolve_accept_offer(&mut self, owner_id: AccountId, receiver_id: Option<AccountId>, offer: $crate::escrow::EscrowEnum, offer_id: $crate::escrow::EscrowOfferId, expires_at: Option<u64>, fill: Option<$crate::escrow::EscrowFill>) -> bool {
+            self.$tokens.resolve_accept_offer(owner_id, receiver_id, offer, offer_id, expires_at, fill)
           }
            #[private]
-          fn resolve_remove_offer(&mut self, owner_id: AccountId, receiver_id: AccountId, offer: $crate::escrow::EscrowEnum, offer_id: $crate::escrow::EscrowOfferId) -> bool {
-            self.$tokens.resolve_remove_offer(owner_id, receiver_id, offer, offer_id)
+          fn resolve_remove_offer(&mut self, owner_id: AccountId, receiver_id: Option<AccountId>, offer: $crate::escrow::EscrowEnum, offer_id: $crate::escrow::EscrowOfferId, expires_at: Option<u64>, fill: Option<$crate::escrow::EscrowFill>) -> bool {
+            self.$tokens.resolve_remove_offer(owner_id, receiver_id, offer, offer_id, expires_at, fill)
+          }
+          #[private]
+          fn resolve_transfer(&mut self, account_id: AccountId, asset: $crate::escrow::EscrowAsset) -> bool {
+            self.$tokens.resolve_transfer(account_id, asset)
           }
         }
     };
@@ -51,6 +86,24 @@ macro_rules! impl_escrow_enumeration {
           fn escrow_offers_total_for_owner(&self, account_id: AccountId) -> u64 {
             self.$tokens.escrow_offers_total_for_owner(account_id)
           }
+          fn escrow_claims(&self, account_id: AccountId) -> Vec<$crate::escrow::EscrowAsset> {
+            self.$tokens.escrow_claims(account_id)
+          }
+          fn escrow_offer_thread(&self, offer_id: $crate::escrow::EscrowOfferId) -> Vec<JsonEscrow> {
+            self.$tokens.escrow_offer_thread(offer_id)
+          }
+          fn escrow_open_offers(&self, limit: Option<u64>, offset: Option<U128>) -> Vec<JsonEscrow> {
+            self.$tokens.escrow_open_offers(limit, offset)
+          }
+          fn escrow_open_offers_by_asset(&self, contract_id: AccountId, token_id: Option<$crate::escrow::TokenId>, limit: Option<u64>, offset: Option<U128>) -> Vec<JsonEscrow> {
+            self.$tokens.escrow_open_offers_by_asset(contract_id, token_id, limit, offset)
+          }
+          fn escrow_open_offers_total(&self) -> u64 {
+            self.$tokens.escrow_open_offers_total()
+          }
+          fn escrow_offers_expiring(&self, before: u64, limit: Option<u64>, offset: Option<U128>) -> Vec<JsonEscrow> {
+            self.$tokens.escrow_offers_expiring(before, limit, offset)
+          }
         }
     };
 }
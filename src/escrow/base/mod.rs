@@ -1,9 +1,15 @@
 pub use base_impl::*;
+pub use basket::ESCROW_MAX_BASKET_ASSETS;
+pub use internal::{ ESCROW_MAX_REFUND_EXPIRED, escrow_asset_key };
+pub use thread::ESCROW_MAX_THREAD_LEN;
 use near_sdk::{ AccountId, ext_contract };
 use near_sdk::json_types::U128;
-use crate::escrow::metadata::{ EscrowOfferId, JsonEscrow, EscrowEnum };
+use crate::escrow::metadata::{ EscrowOfferId, JsonEscrow, EscrowEnum, EscrowAsset, EscrowFill, TokenId };
 
 pub mod base_impl;
+mod basket;
+mod fill;
+mod thread;
 mod macros;
 mod internal;
 mod receivers;
@@ -11,6 +17,25 @@ mod resolvers;
 
 pub trait EscrowCore {
     fn escrow_remove_offer(&mut self, offer_id: EscrowOfferId);
+
+    /// each side is deposited with `offer_id` transfer calls, the swap is settled when both are funded
+    fn escrow_basket_offer(
+        &mut self,
+        receiver_id: AccountId,
+        assets_in: Vec<EscrowAsset>,
+        assets_out: Vec<EscrowAsset>,
+        expires_at: Option<u64>
+    ) -> JsonEscrow;
+    // payable
+    fn escrow_basket_deposit(&mut self, offer_id: EscrowOfferId);
+    /// swaps both sides of the funded basket, failed transfers are kept for `escrow_claim`
+    fn escrow_basket_settle(&mut self, offer_id: EscrowOfferId);
+    /// retries failed basket and fill transfers of the caller, returns count of sent assets
+    fn escrow_claim(&mut self) -> u64;
+
+    /// refunds expired offers to their owners, returns count of refunded offers,
+    /// offers with a failed refund are left to their owners
+    fn escrow_refund_expired(&mut self, limit: Option<u64>) -> u64;
 }
 
 pub trait EscrowEnumeration {
@@ -29,6 +54,28 @@ pub trait EscrowEnumeration {
   ) -> Vec<JsonEscrow>;
   fn escrow_offers_total_by_owner(&self, account_id: AccountId) -> u64;
   fn escrow_offers_total_for_owner(&self, account_id: AccountId) -> u64;
+  /// assets of the account waiting for `escrow_claim`
+  fn escrow_claims(&self, account_id: AccountId) -> Vec<EscrowAsset>;
+  /// counter offers chain of the offer from the first one, the last is the latest
+  fn escrow_offer_thread(&self, offer_id: EscrowOfferId) -> Vec<JsonEscrow>;
+  /// offers without receiver
+  fn escrow_open_offers(&self, limit: Option<u64>, offset: Option<U128>) -> Vec<JsonEscrow>;
+  /// open offers requesting the ft contract or the nft token
+  fn escrow_open_offers_by_asset(
+    &self,
+    contract_id: AccountId,
+    token_id: Option<TokenId>,
+    limit: Option<u64>,
+    offset: Option<U128>
+  ) -> Vec<JsonEscrow>;
+  fn escrow_open_offers_total(&self) -> u64;
+  /// offers with expiration up to `before`, soonest first
+  fn escrow_offers_expiring(
+    &self,
+    before: u64,
+    limit: Option<u64>,
+    offset: Option<U128>
+  ) -> Vec<JsonEscrow>;
 }
 
 #[ext_contract(ext_self)]
@@ -36,15 +83,20 @@ pub trait EscrowResolver {
     fn resolve_accept_offer(
         &mut self,
         owner_id: AccountId,
-        receiver_id: AccountId,
+        receiver_id: Option<AccountId>,
         offer: EscrowEnum,
-        offer_id: EscrowOfferId
+        offer_id: EscrowOfferId,
+        expires_at: Option<u64>,
+        fill: Option<EscrowFill>
     ) -> bool;
     fn resolve_remove_offer(
         &mut self,
         owner_id: AccountId,
-        receiver_id: AccountId,
+        receiver_id: Option<AccountId>,
         offer: EscrowEnum,
-        offer_id: EscrowOfferId
+        offer_id: EscrowOfferId,
+        expires_at: Option<u64>,
+        fill: Option<EscrowFill>
     ) -> bool;
+    fn resolve_transfer(&mut self, account_id: AccountId, asset: EscrowAsset) -> bool;
 }
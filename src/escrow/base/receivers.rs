@@ -2,8 +2,11 @@ use crate::escrow::{
     EscrowFeature,
     EscrowOnFtTransferArgs,
     EscrowOnNftTransferArgs,
+    EscrowOnMtTransferArgs,
     TokenId,
     EscrowEnum,
+    EscrowAsset,
+    EscrowOfferId,
 };
 use crate::escrow::base::internal::{GAS_FOR_RESOLVE_ACCEPT};
 use near_sdk::{ PromiseOrValue, AccountId, env, require };
@@ -24,16 +27,27 @@ impl EscrowFeature {
             nft_token_id_out,
             receiver_id,
             offer_id,
+            expires_at,
+            partial,
+            counter_offer_id,
         } = args;
 
-        if let Some(receiver_id) = receiver_id {
-            assert_ne!(&sender_id, &receiver_id, "Not self");
+        // counter offer is sent to the owner of the received offer
+        let receiver_id = &self.internal_counter_receiver(&counter_offer_id, &sender_id, &receiver_id);
+
+        // offers without receiver are open for any account
+        if receiver_id.is_some() || offer_id.is_none() {
+            if let Some(receiver_id) = receiver_id {
+                assert_ne!(&sender_id, &receiver_id, "Not self");
+            }
+
+            self.assert_valid_expiration(&expires_at);
 
             if let Some(ft_contract_id_out) = ft_contract_id_out {
                 if let Some(ft_amount_out) = ft_amount_out {
                     assert_ne!(&ft_contract_id_out, &ft_contract_id, "Ft contracts does not equal");
 
-                    self.internal_make_offer(
+                    let offer = self.internal_make_offer(
                         &(EscrowEnum::FtToFt {
                             ft_contract_id_in: ft_contract_id.clone(),
                             ft_contract_id_out: ft_contract_id_out.clone(),
@@ -41,16 +55,23 @@ impl EscrowFeature {
                             amount_out: ft_amount_out.clone(),
                         }),
                         &sender_id,
-                        &receiver_id,
-                        None
+                        receiver_id,
+                        None,
+                        expires_at.clone()
                     );
 
+                    self.internal_link_counter_offer(&counter_offer_id, &offer, &sender_id);
+
+                    if partial.unwrap_or(false) {
+                        self.internal_add_offer_fill(&offer.offer_id);
+                    }
+
                     return PromiseOrValue::Value(U128(0));
                 }
             }
             if let Some(nft_contract_id_out) = nft_contract_id_out {
                 if let Some(nft_token_id_out) = nft_token_id_out {
-                    self.internal_make_offer(
+                    let offer = self.internal_make_offer(
                         &(EscrowEnum::FtToNft {
                             ft_contract_id_in: ft_contract_id.clone(),
                             nft_contract_id_out: nft_contract_id_out.clone(),
@@ -58,22 +79,45 @@ impl EscrowFeature {
                             nft_token_id_out: nft_token_id_out.clone(),
                         }),
                         &sender_id,
-                        &receiver_id,
-                        None
+                        receiver_id,
+                        None,
+                        expires_at.clone()
                     );
 
+                    self.internal_link_counter_offer(&counter_offer_id, &offer, &sender_id);
+
                     return PromiseOrValue::Value(U128(0));
                 }
             }
 
             env::panic_str(&"Invalid params");
         } else if let Some(offer_id) = offer_id {
+            if self.internal_is_basket_offer(&offer_id) {
+                return PromiseOrValue::Value(
+                    self.internal_basket_deposit(
+                        &offer_id,
+                        &sender_id,
+                        &(EscrowAsset::Ft {
+                            ft_contract_id: ft_contract_id.clone(),
+                            amount: amount.clone(),
+                        })
+                    )
+                );
+            }
+
           require!(
               env::prepaid_gas() > GAS_FOR_RESOLVE_ACCEPT,
               "More gas is required"
             );
 
             let owner_id = self.offer_owner_by_account.get(&offer_id).unwrap();
+
+            if self.offer_fill_by_id.get(&offer_id).is_some() {
+                return PromiseOrValue::Value(
+                    self.internal_fill_offer(&owner_id, &sender_id, &offer_id, &ft_contract_id, &amount)
+                );
+            }
+
             self.internal_accept_offer_unknown_to_ft(
                 &owner_id,
                 &sender_id,
@@ -100,14 +144,24 @@ impl EscrowFeature {
             nft_token_id_out,
             receiver_id,
             offer_id,
+            expires_at,
+            counter_offer_id,
         } = args;
 
-        if let Some(receiver_id) = receiver_id {
-            assert_ne!(&sender_id, &receiver_id, "Not self");
+        // counter offer is sent to the owner of the received offer
+        let receiver_id = &self.internal_counter_receiver(&counter_offer_id, &sender_id, &receiver_id);
+
+        // offers without receiver are open for any account
+        if receiver_id.is_some() || offer_id.is_none() {
+            if let Some(receiver_id) = receiver_id {
+                assert_ne!(&sender_id, &receiver_id, "Not self");
+            }
+
+            self.assert_valid_expiration(&expires_at);
 
             if let Some(ft_contract_id_out) = ft_contract_id_out {
                 if let Some(ft_amount_out) = ft_amount_out {
-                    self.internal_make_offer(
+                    let offer = self.internal_make_offer(
                         &(EscrowEnum::NftToFt {
                             nft_contract_id_in: contract_id.clone(),
                             ft_contract_id_out: ft_contract_id_out.clone(),
@@ -115,16 +169,19 @@ impl EscrowFeature {
                             amount_out: ft_amount_out.clone(),
                         }),
                         &sender_id,
-                        &receiver_id,
-                        None
+                        receiver_id,
+                        None,
+                        expires_at.clone()
                     );
 
+                    self.internal_link_counter_offer(&counter_offer_id, &offer, &sender_id);
+
                     return PromiseOrValue::Value(false);
                 }
             }
             if let Some(nft_contract_id_out) = nft_contract_id_out {
                 if let Some(nft_token_id_out) = nft_token_id_out {
-                    self.internal_make_offer(
+                    let offer = self.internal_make_offer(
                         &(EscrowEnum::NftToNft {
                             nft_contract_id_in: contract_id.clone(),
                             nft_contract_id_out: nft_contract_id_out.clone(),
@@ -132,16 +189,32 @@ impl EscrowFeature {
                             nft_token_id_out: nft_token_id_out.clone(),
                         }),
                         &sender_id,
-                        &receiver_id,
-                        None
+                        receiver_id,
+                        None,
+                        expires_at.clone()
                     );
 
+                    self.internal_link_counter_offer(&counter_offer_id, &offer, &sender_id);
+
                     return PromiseOrValue::Value(false);
                 }
             }
 
             env::panic_str(&"Invalid params");
         } else if let Some(offer_id) = offer_id {
+            if self.internal_is_basket_offer(&offer_id) {
+                self.internal_basket_deposit(
+                    &offer_id,
+                    &sender_id,
+                    &(EscrowAsset::Nft {
+                        nft_contract_id: contract_id.clone(),
+                        nft_token_id: token_id.clone(),
+                    })
+                );
+
+                return PromiseOrValue::Value(false);
+            }
+
           require!(
               env::prepaid_gas() > GAS_FOR_RESOLVE_ACCEPT,
               "More gas is required"
@@ -159,4 +232,38 @@ impl EscrowFeature {
 
         PromiseOrValue::Value(false)
     }
+
+    pub fn internal_on_mt_transfer(
+        &mut self,
+        args: &EscrowOnMtTransferArgs,
+        contract_id: &AccountId,
+        token_ids: &Vec<AccountId>,
+        amounts: &Vec<U128>,
+        sender_id: &AccountId
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_eq!(token_ids.len(), amounts.len(), "Invalid params");
+        assert!(self.internal_is_basket_offer(&args.offer_id), "Not basket offer");
+
+        let unused = token_ids
+            .iter()
+            .zip(amounts.iter())
+            .map(|(token_id, amount)| {
+                self.internal_basket_deposit(
+                    &args.offer_id,
+                    &sender_id,
+                    &(EscrowAsset::Mt {
+                        mt_contract_id: contract_id.clone(),
+                        mt_token_id: token_id.to_string(),
+                        amount: amount.clone(),
+                    })
+                )
+            })
+            .collect();
+
+        PromiseOrValue::Value(unused)
+    }
+
+    fn internal_is_basket_offer(&self, offer_id: &EscrowOfferId) -> bool {
+        matches!(self.offer_by_id.get(&offer_id), Some(EscrowEnum::Basket { .. }))
+    }
 }
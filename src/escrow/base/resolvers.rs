@@ -1,13 +1,15 @@
-use crate::escrow::{ EscrowResolver, EscrowFeature, EscrowEnum, EscrowOfferId };
+use crate::escrow::{ EscrowResolver, EscrowFeature, EscrowEnum, EscrowOfferId, EscrowAsset, EscrowFill };
 use near_sdk::{ AccountId, env, PromiseResult };
 
 impl EscrowResolver for EscrowFeature {
     fn resolve_accept_offer(
         &mut self,
         owner_id: AccountId,
-        receiver_id: AccountId,
+        receiver_id: Option<AccountId>,
         offer: EscrowEnum,
-        offer_id: EscrowOfferId
+        offer_id: EscrowOfferId,
+        expires_at: Option<u64>,
+        fill: Option<EscrowFill>
     ) -> bool {
         match env::promise_result(0) {
             PromiseResult::NotReady => env::abort(),
@@ -61,12 +63,14 @@ impl EscrowResolver for EscrowFeature {
                     &nft_token_id_out
                   );
                 }
+                // settled without callback
+                EscrowEnum::Basket { .. } => {}
               }
 
               true
             }
             PromiseResult::Failed => {
-              self.internal_make_offer(&offer, &owner_id, &receiver_id, Some(offer_id));
+              self.internal_restore_offer(&offer, &owner_id, &receiver_id, &offer_id, expires_at, &fill);
 
               false
             }
@@ -76,15 +80,29 @@ impl EscrowResolver for EscrowFeature {
     fn resolve_remove_offer(
         &mut self,
         owner_id: AccountId,
-        receiver_id: AccountId,
+        receiver_id: Option<AccountId>,
         offer: EscrowEnum,
-        offer_id: EscrowOfferId
+        offer_id: EscrowOfferId,
+        expires_at: Option<u64>,
+        fill: Option<EscrowFill>
     ) -> bool {
         match env::promise_result(0) {
             PromiseResult::NotReady => env::abort(),
             PromiseResult::Successful(_value) => { true }
             PromiseResult::Failed => {
-                self.internal_make_offer(&offer, &owner_id, &receiver_id, Some(offer_id));
+                self.internal_restore_offer(&offer, &owner_id, &receiver_id, &offer_id, expires_at, &fill);
+
+                false
+            }
+        }
+    }
+
+    fn resolve_transfer(&mut self, account_id: AccountId, asset: EscrowAsset) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::NotReady => env::abort(),
+            PromiseResult::Successful(_value) => { true }
+            PromiseResult::Failed => {
+                self.internal_add_claim(&account_id, &asset);
 
                 false
             }
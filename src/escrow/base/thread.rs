@@ -0,0 +1,119 @@
+use crate::escrow::{ EscrowFeature, EscrowEnum, EscrowOfferId, JsonEscrow };
+use crate::escrow::events::MfEscrowCounter;
+use near_sdk::AccountId;
+
+pub const ESCROW_MAX_THREAD_LEN: usize = 10;
+
+impl EscrowFeature {
+    /// receiver of the counter offer, only the receiver of the offer can counter it
+    pub(crate) fn internal_counter_receiver(
+        &self,
+        counter_offer_id: &Option<EscrowOfferId>,
+        sender_id: &AccountId,
+        receiver_id: &Option<AccountId>
+    ) -> Option<AccountId> {
+        if let Some(counter_offer_id) = counter_offer_id {
+            let offer = self.offer_by_id.get(&counter_offer_id).expect("Not found offer");
+            let owner_id = self.offer_owner_by_account.get(&counter_offer_id).expect("Not found");
+            let offer_receiver_id = self.offer_receiver_by_account
+                .get(&counter_offer_id)
+                .expect("Open offer can not be countered");
+
+            assert_eq!(&offer_receiver_id, sender_id, "Not found offer");
+            assert!(
+                receiver_id.is_none() || receiver_id.as_ref() == Some(&owner_id),
+                "Invalid receiver"
+            );
+            assert!(
+                !matches!(offer, EscrowEnum::Basket { .. }),
+                "Basket offer can not be countered"
+            );
+            assert!(!self.internal_offer_is_expired(&counter_offer_id), "Offer is expired");
+            assert!(self.internal_offer_is_latest(&counter_offer_id), "Offer is superseded");
+
+            let thread_len = self.offer_thread_by_id
+                .get(&counter_offer_id)
+                .and_then(|root_id| self.threads_by_id.get(&root_id))
+                .map(|thread| thread.len())
+                .unwrap_or(1);
+
+            assert!(thread_len < ESCROW_MAX_THREAD_LEN, "Too many counter offers");
+
+            return Some(owner_id);
+        }
+
+        receiver_id.clone()
+    }
+
+    /// adds the counter offer to the thread, the countered offer is refunded to its owner
+    pub(crate) fn internal_link_counter_offer(
+        &mut self,
+        counter_offer_id: &Option<EscrowOfferId>,
+        offer: &JsonEscrow,
+        sender_id: &AccountId
+    ) {
+        if let Some(counter_offer_id) = counter_offer_id {
+            let countered = self.enum_get_offer(&counter_offer_id).expect("Not found offer");
+            let (root_id, mut thread) = match self.offer_thread_by_id.get(&counter_offer_id) {
+                Some(root_id) => {
+                    let thread = self.threads_by_id.get(&root_id).expect("Not found thread");
+
+                    (root_id, thread)
+                }
+                None => {
+                    self.offer_thread_by_id.insert(&counter_offer_id, &counter_offer_id);
+
+                    (counter_offer_id.clone(), vec![])
+                }
+            };
+
+            // the countered offer is the latest, its snapshot is updated for the thread view
+            thread.pop();
+            thread.push(countered.clone());
+            thread.push(offer.clone());
+
+            self.offer_thread_by_id.insert(&offer.offer_id, &root_id);
+            self.threads_by_id.insert(&root_id, &thread);
+
+            self.internal_withdraw_offer(
+                &counter_offer_id,
+                &countered.sender_id,
+                &countered.receiver_id
+            );
+
+            (MfEscrowCounter {
+                offer_id: &counter_offer_id,
+                counter_offer_id: &offer.offer_id,
+                account_id: &sender_id,
+            }).emit();
+        }
+    }
+
+    /// offers replaced by a counter offer can not be accepted
+    pub(crate) fn internal_offer_is_latest(&self, offer_id: &EscrowOfferId) -> bool {
+        self.offer_thread_by_id
+            .get(&offer_id)
+            .and_then(|root_id| self.threads_by_id.get(&root_id))
+            .and_then(|thread| thread.last().map(|last| &last.offer_id == offer_id))
+            .unwrap_or(true)
+    }
+
+    pub(crate) fn internal_find_offer_thread(&self, offer_id: &EscrowOfferId) -> Vec<JsonEscrow> {
+        if let Some(root_id) = self.offer_thread_by_id.get(&offer_id) {
+            let thread = self.threads_by_id.get(&root_id).expect("Not found thread");
+
+            return thread
+                .into_iter()
+                .map(|item| {
+                    if self.offer_thread_by_id.get(&item.offer_id).as_ref() == Some(&root_id) {
+                        self.enum_get_offer(&item.offer_id).unwrap_or(item)
+                    } else {
+                        item
+                    }
+                })
+                .collect();
+        }
+
+        self.enum_get_offer(&offer_id).into_iter().collect()
+    }
+}
@@ -1,4 +1,5 @@
 use near_sdk::AccountId;
+use near_sdk::json_types::U128;
 use serde::Serialize;
 use crate::event::{ NearEvent };
 
@@ -18,6 +19,48 @@ impl MfEscrowCreate<'_> {
     }
 }
 
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MfEscrowFill<'a> {
+    pub offer_id: &'a String,
+    pub owner_id: &'a AccountId,
+    pub taker_id: &'a AccountId,
+    // sent to the taker
+    pub amount_in: &'a U128,
+    // paid by the taker
+    pub amount_out: &'a U128,
+    pub remaining_in: &'a U128,
+    pub remaining_out: &'a U128,
+}
+
+impl MfEscrowFill<'_> {
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    pub fn emit_many<'a>(data: &'a [MfEscrowFill<'a>]) {
+        new_mf_escrow_v1(MfEscrowEventKind::MfEscrowFill(data)).emit()
+    }
+}
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MfEscrowCounter<'a> {
+    pub offer_id: &'a String,
+    pub counter_offer_id: &'a String,
+    pub account_id: &'a AccountId,
+}
+
+impl MfEscrowCounter<'_> {
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    pub fn emit_many<'a>(data: &'a [MfEscrowCounter<'a>]) {
+        new_mf_escrow_v1(MfEscrowEventKind::MfEscrowCounter(data)).emit()
+    }
+}
+
 //
 
 #[derive(Serialize, Debug)]
@@ -33,6 +76,8 @@ pub(crate) struct MfEscrowEvent<'a> {
 #[allow(clippy::enum_variant_names)]
 enum MfEscrowEventKind<'a> {
     MfEscrowCreate(&'a [MfEscrowCreate<'a>]),
+    MfEscrowFill(&'a [MfEscrowFill<'a>]),
+    MfEscrowCounter(&'a [MfEscrowCounter<'a>]),
 }
 
 fn new_mf_escrow<'a>(version: &'static str, event_kind: MfEscrowEventKind<'a>) -> NearEvent<'a> {
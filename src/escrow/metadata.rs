@@ -8,6 +8,85 @@ pub type TokenId = String;
 pub type EscrowOfferId = String;
 pub type ContractId = AccountId;
 
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+    JsonSchema
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EscrowAsset {
+    Near {
+        amount: U128,
+    },
+    Ft {
+        ft_contract_id: ContractId,
+        amount: U128,
+    },
+    Nft {
+        nft_contract_id: ContractId,
+        nft_token_id: TokenId,
+    },
+    Mt {
+        mt_contract_id: ContractId,
+        mt_token_id: TokenId,
+        amount: U128,
+    },
+}
+
+impl EscrowAsset {
+    pub fn amount(&self) -> U128 {
+        match self {
+            EscrowAsset::Near { amount } => *amount,
+            EscrowAsset::Ft { amount, .. } => *amount,
+            EscrowAsset::Nft { .. } => U128(1),
+            EscrowAsset::Mt { amount, .. } => *amount,
+        }
+    }
+
+    /// same token with other amount, nft is not changed
+    pub fn with_amount(&self, amount: U128) -> EscrowAsset {
+        let mut asset = self.clone();
+
+        match &mut asset {
+            EscrowAsset::Near { amount: value } => *value = amount,
+            EscrowAsset::Ft { amount: value, .. } => *value = amount,
+            EscrowAsset::Nft { .. } => {}
+            EscrowAsset::Mt { amount: value, .. } => *value = amount,
+        }
+
+        asset
+    }
+
+    /// same token regardless of amount
+    pub fn is_same(&self, other: &EscrowAsset) -> bool {
+        match (self, other) {
+            (EscrowAsset::Near { .. }, EscrowAsset::Near { .. }) => true,
+            (
+                EscrowAsset::Ft { ft_contract_id, .. },
+                EscrowAsset::Ft { ft_contract_id: other_contract_id, .. },
+            ) => ft_contract_id == other_contract_id,
+            (
+                EscrowAsset::Nft { nft_contract_id, nft_token_id },
+                EscrowAsset::Nft { nft_contract_id: other_contract_id, nft_token_id: other_token_id },
+            ) => nft_contract_id == other_contract_id && nft_token_id == other_token_id,
+            (
+                EscrowAsset::Mt { mt_contract_id, mt_token_id, .. },
+                EscrowAsset::Mt {
+                    mt_contract_id: other_contract_id,
+                    mt_token_id: other_token_id,
+                    ..
+                },
+            ) => mt_contract_id == other_contract_id && mt_token_id == other_token_id,
+            _ => false,
+        }
+    }
+}
+
 #[derive(
     BorshDeserialize,
     BorshSerialize,
@@ -48,6 +127,14 @@ pub enum EscrowEnum {
         nft_contract_id_out: ContractId,
         nft_token_id_out: TokenId,
     },
+    // both sides are deposited to the contract, `funded_*` follow the assets order
+    Basket {
+        assets_in: Vec<EscrowAsset>,
+        assets_out: Vec<EscrowAsset>,
+
+        funded_in: Vec<U128>,
+        funded_out: Vec<U128>,
+    },
 }
 
 #[derive(
@@ -65,8 +152,32 @@ pub struct JsonEscrow {
     pub offer_id: EscrowOfferId,
     pub data: EscrowEnum,
     pub sender_id: AccountId,
-    pub receiver_id: AccountId,
+    // `None` for open offers
+    pub receiver_id: Option<AccountId>,
     pub is_accepted: bool,
+    pub expires_at: Option<u64>,
+    // partially fillable `FtToFt` offer, `data` keeps the remaining amounts
+    pub fill: Option<EscrowFill>,
+}
+
+/// Original amounts of a partially fillable offer and its filled part
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+    JsonSchema
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowFill {
+    pub amount_in: U128,
+    pub amount_out: U128,
+
+    pub filled_in: U128,
+    pub filled_out: U128,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -80,6 +191,12 @@ pub struct EscrowOnFtTransferArgs {
 
     pub receiver_id: Option<AccountId>,
     pub offer_id: Option<EscrowOfferId>,
+    // acceptance is rejected after the time
+    pub expires_at: Option<u64>,
+    // replaces the received offer, the offer deposit is refunded
+    pub counter_offer_id: Option<EscrowOfferId>,
+    // `FtToFt` offer can be filled by parts
+    pub partial: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -93,4 +210,14 @@ pub struct EscrowOnNftTransferArgs {
 
     pub receiver_id: Option<AccountId>,
     pub offer_id: Option<EscrowOfferId>,
+    // acceptance is rejected after the time
+    pub expires_at: Option<u64>,
+    // replaces the received offer, the offer deposit is refunded
+    pub counter_offer_id: Option<EscrowOfferId>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowOnMtTransferArgs {
+    pub offer_id: EscrowOfferId,
 }
@@ -1,5 +1,14 @@
 pub mod base;
-pub use self::base::{ EscrowCore, EscrowResolver, EscrowEnumeration, EscrowFeature };
+pub use self::base::{
+    EscrowCore,
+    EscrowResolver,
+    EscrowEnumeration,
+    EscrowFeature,
+    ESCROW_MAX_BASKET_ASSETS,
+    ESCROW_MAX_REFUND_EXPIRED,
+    escrow_asset_key,
+    ESCROW_MAX_THREAD_LEN,
+};
 
 pub(crate) mod events;
 
@@ -7,3 +16,5 @@ pub mod metadata;
 
 pub use self::metadata::*;
 pub use self::events::*;
+
+mod test;
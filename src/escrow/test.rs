@@ -0,0 +1,703 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::escrow::{ EscrowFeature, EscrowCore, EscrowEnumeration, EscrowResolver };
+    use crate::escrow::{ EscrowAsset, EscrowEnum, EscrowFill, EscrowOnFtTransferArgs, EscrowOnNftTransferArgs };
+    use near_sdk::test_utils::{ accounts, VMContextBuilder };
+    use near_sdk::{ AccountId, BorshStorageKey, Balance, Gas, testing_env, PromiseOrValue, PromiseResult, RuntimeFeesConfig, VMConfig };
+    use near_sdk::json_types::U128;
+    use near_sdk::borsh::{ self, BorshSerialize };
+
+    const TX_PRICE: Balance = 10_000_000_000_000_000_000_000;
+    const NEAR_AMOUNT: Balance = 1_000_000_000_000_000_000_000_000;
+    const HOUR: u64 = 3_600_000_000_000;
+
+    /// Helper structure for keys of the persistent collections.
+    #[derive(BorshSerialize, BorshStorageKey)]
+    pub enum StorageKey {
+        Offers,
+        OffersByAccount,
+        OffersForAccount,
+        OfferOwner,
+        OfferReceiver,
+        OfferAccepted,
+        OfferExpiresAt,
+        OffersByExpiresAt,
+        OpenOffers,
+        OpenOffersByAsset,
+        OfferFill,
+        OfferThread,
+        Threads,
+        Claims,
+        OfferNonce,
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
+
+    fn get_instance() -> EscrowFeature {
+        EscrowFeature::new(
+            StorageKey::Offers,
+            StorageKey::OffersByAccount,
+            StorageKey::OffersForAccount,
+            StorageKey::OfferOwner,
+            StorageKey::OfferReceiver,
+            StorageKey::OfferAccepted,
+            StorageKey::OfferExpiresAt,
+            StorageKey::OffersByExpiresAt,
+            StorageKey::OpenOffers,
+            StorageKey::OpenOffersByAsset,
+            StorageKey::OfferFill,
+            StorageKey::OfferThread,
+            StorageKey::Threads,
+            StorageKey::Claims,
+            StorageKey::OfferNonce,
+        )
+    }
+
+    fn ft_a() -> AccountId {
+        AccountId::new_unchecked("ft_a".to_string())
+    }
+
+    fn ft_b() -> AccountId {
+        AccountId::new_unchecked("ft_b".to_string())
+    }
+
+    fn nft() -> AccountId {
+        AccountId::new_unchecked("nft".to_string())
+    }
+
+    fn resolve_with(result: PromiseResult) {
+        let mut context = get_context(accounts(0));
+        context.block_timestamp(2 * HOUR);
+        testing_env!(
+            context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result]
+        );
+    }
+
+    fn ft_deposit_args(offer_id: &String) -> EscrowOnFtTransferArgs {
+        EscrowOnFtTransferArgs {
+            ft_contract_id_out: None,
+            ft_amount_out: None,
+            nft_contract_id_out: None,
+            nft_token_id_out: None,
+            receiver_id: None,
+            offer_id: Some(offer_id.clone()),
+            expires_at: None,
+            counter_offer_id: None,
+            partial: None,
+        }
+    }
+
+    fn open_offer_args() -> EscrowOnFtTransferArgs {
+        EscrowOnFtTransferArgs {
+            ft_contract_id_out: Some(ft_b()),
+            ft_amount_out: Some(U128(50)),
+            nft_contract_id_out: None,
+            nft_token_id_out: None,
+            receiver_id: None,
+            offer_id: None,
+            expires_at: None,
+            counter_offer_id: None,
+            partial: None,
+        }
+    }
+
+    fn add_ft_offer(instance: &mut EscrowFeature, expires_at: Option<u64>) -> String {
+        let mut context = get_context(ft_a());
+        testing_env!(context.build());
+
+        let args = EscrowOnFtTransferArgs {
+            ft_contract_id_out: Some(ft_b()),
+            ft_amount_out: Some(U128(50)),
+            nft_contract_id_out: None,
+            nft_token_id_out: None,
+            receiver_id: Some(accounts(2)),
+            offer_id: None,
+            expires_at,
+            counter_offer_id: None,
+            partial: None,
+        };
+
+        instance.internal_on_ft_transfer(&args, &ft_a(), &U128(100), &accounts(1));
+
+        let offers = instance.escrow_offers_by_owner(accounts(1), None, None);
+
+        offers
+            .into_iter()
+            .map(|offer| offer.offer_id)
+            .last()
+            .unwrap()
+    }
+
+    fn add_basket(instance: &mut EscrowFeature) -> String {
+        let owner_id = accounts(1);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        instance
+            .escrow_basket_offer(
+                accounts(2),
+                vec![
+                    EscrowAsset::Ft { ft_contract_id: ft_a(), amount: U128(100) },
+                    EscrowAsset::Near { amount: U128(NEAR_AMOUNT) }
+                ],
+                vec![
+                    EscrowAsset::Ft { ft_contract_id: ft_b(), amount: U128(50) },
+                    EscrowAsset::Nft { nft_contract_id: nft(), nft_token_id: "1".to_string() }
+                ],
+                None
+            ).offer_id
+    }
+
+    #[test]
+    fn test_basket_funded_by_both_sides() {
+        let owner_id = accounts(1);
+        let receiver_id = accounts(2);
+
+        let mut instance = get_instance();
+        let offer_id = add_basket(&mut instance);
+
+        let mut context = get_context(ft_a());
+        testing_env!(context.build());
+
+        let result = instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_a(), &U128(60), &owner_id);
+        assert!(matches!(result, PromiseOrValue::Value(U128(0))));
+
+        // the rest over the required amount is returned
+        let result = instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_a(), &U128(70), &owner_id);
+        assert!(matches!(result, PromiseOrValue::Value(U128(30))));
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.attached_deposit(NEAR_AMOUNT).build());
+
+        instance.escrow_basket_deposit(offer_id.clone());
+
+        let mut context = get_context(ft_b());
+        testing_env!(context.build());
+
+        instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_b(), &U128(50), &receiver_id);
+
+        let mut context = get_context(nft());
+        testing_env!(context.build());
+
+        let args = EscrowOnNftTransferArgs {
+            ft_contract_id_out: None,
+            ft_amount_out: None,
+            nft_contract_id_out: None,
+            nft_token_id_out: None,
+            receiver_id: None,
+            offer_id: Some(offer_id.clone()),
+            expires_at: None,
+            counter_offer_id: None,
+        };
+
+        instance.internal_on_nft_transfer(&args, &nft(), &"1".to_string(), &receiver_id);
+
+        // deposits do not settle, so the last depositor does not pay for the swap
+        let offer = instance.escrow_offer(offer_id.clone()).unwrap();
+
+        match offer.data {
+            EscrowEnum::Basket { funded_in, funded_out, .. } => {
+                assert_eq!(funded_in, vec![U128(100), U128(NEAR_AMOUNT)]);
+                assert_eq!(funded_out, vec![U128(50), U128(1)]);
+            }
+            _ => panic!("Not basket offer"),
+        }
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        instance.escrow_basket_settle(offer_id.clone());
+
+        assert!(instance.escrow_offer(offer_id).is_none());
+        assert_eq!(instance.escrow_offers_total_by_owner(owner_id), 0);
+        assert_eq!(instance.escrow_offers_total_for_owner(receiver_id), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Basket is not funded")]
+    fn test_basket_settle_not_funded() {
+        let mut instance = get_instance();
+        let offer_id = add_basket(&mut instance);
+
+        let mut context = get_context(ft_a());
+        testing_env!(context.build());
+
+        instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_a(), &U128(100), &accounts(1));
+
+        instance.escrow_basket_settle(offer_id);
+    }
+
+    #[test]
+    fn test_basket_refund_on_cancel() {
+        let receiver_id = accounts(2);
+
+        let mut instance = get_instance();
+        let offer_id = add_basket(&mut instance);
+
+        let mut context = get_context(ft_b());
+        testing_env!(context.build());
+
+        instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_b(), &U128(20), &receiver_id);
+
+        let mut context = get_context(receiver_id.clone());
+        testing_env!(context.attached_deposit(TX_PRICE).build());
+
+        instance.escrow_remove_offer(offer_id.clone());
+
+        assert!(instance.escrow_offer(offer_id).is_none());
+        assert_eq!(instance.escrow_offers_total_for_owner(receiver_id), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized")]
+    fn test_basket_cancel_unauthorized() {
+        let mut instance = get_instance();
+        let offer_id = add_basket(&mut instance);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.attached_deposit(TX_PRICE).build());
+
+        instance.escrow_remove_offer(offer_id);
+    }
+
+    #[test]
+    fn test_basket_failed_transfer_claim() {
+        let receiver_id = accounts(2);
+        let asset = EscrowAsset::Ft { ft_contract_id: ft_a(), amount: U128(100) };
+
+        let mut instance = get_instance();
+
+        resolve_with(PromiseResult::Failed);
+
+        assert!(!instance.resolve_transfer(receiver_id.clone(), asset.clone()));
+        assert_eq!(instance.escrow_claims(receiver_id.clone()), vec![asset.clone()]);
+
+        resolve_with(PromiseResult::Successful(vec![]));
+
+        assert!(instance.resolve_transfer(receiver_id.clone(), asset.clone()));
+        assert_eq!(instance.escrow_claims(receiver_id.clone()).len(), 1);
+
+        let mut context = get_context(receiver_id.clone());
+        testing_env!(context.build());
+
+        assert_eq!(instance.escrow_claim(), 1);
+        assert!(instance.escrow_claims(receiver_id).is_empty());
+    }
+
+    #[test]
+    fn test_refund_expired() {
+        let owner_id = accounts(1);
+
+        let mut instance = get_instance();
+
+        let expired_1 = add_ft_offer(&mut instance, Some(HOUR));
+        let expired_2 = add_ft_offer(&mut instance, Some(HOUR));
+        let active = add_ft_offer(&mut instance, Some(3 * HOUR));
+
+        assert_eq!(instance.escrow_offers_expiring(HOUR, None, None).len(), 2);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(2 * HOUR).prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        assert_eq!(instance.escrow_refund_expired(None), 2);
+
+        assert!(instance.escrow_offer(expired_1).is_none());
+        assert!(instance.escrow_offer(expired_2).is_none());
+        assert!(instance.escrow_offer(active.clone()).is_some());
+        assert_eq!(instance.escrow_offers_total_by_owner(owner_id), 1);
+
+        assert_eq!(instance.escrow_refund_expired(None), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Offer is expired")]
+    fn test_accept_expired() {
+        let mut instance = get_instance();
+
+        let offer_id = add_ft_offer(&mut instance, Some(HOUR));
+
+        let mut context = get_context(ft_b());
+        testing_env!(context.block_timestamp(2 * HOUR).build());
+
+        instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_b(), &U128(50), &accounts(2));
+    }
+
+    #[test]
+    fn test_refund_expired_failed_is_parked() {
+        let owner_id = accounts(1);
+
+        let mut instance = get_instance();
+
+        let mut failed = vec![];
+
+        for _ in 0..5 {
+            failed.push(add_ft_offer(&mut instance, Some(HOUR)));
+        }
+
+        let next = add_ft_offer(&mut instance, Some(HOUR));
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(2 * HOUR).prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        assert_eq!(instance.escrow_refund_expired(None), 5);
+
+        // refunds of the first batch fail, the offers are restored
+        for offer_id in failed.iter() {
+            assert!(instance.escrow_offer(offer_id.clone()).is_none());
+
+            resolve_with(PromiseResult::Failed);
+
+            assert!(
+                !instance.resolve_remove_offer(
+                    owner_id.clone(),
+                    Some(accounts(2)),
+                    EscrowEnum::FtToFt {
+                        ft_contract_id_in: ft_a(),
+                        ft_contract_id_out: ft_b(),
+                        amount_in: U128(100),
+                        amount_out: U128(50),
+                    },
+                    offer_id.clone(),
+                    Some(HOUR),
+                    None
+                )
+            );
+            assert!(instance.escrow_offer(offer_id.clone()).is_some());
+        }
+
+        // restored offers do not block the rest of the queue
+        assert_eq!(instance.escrow_offers_expiring(2 * HOUR, None, None).len(), 1);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(2 * HOUR).prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        assert_eq!(instance.escrow_refund_expired(None), 1);
+        assert!(instance.escrow_offer(next).is_none());
+
+        // the owner withdraws the parked offer
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.block_timestamp(2 * HOUR).attached_deposit(TX_PRICE).build());
+
+        instance.escrow_remove_offer(failed[0].clone());
+
+        assert!(instance.escrow_offer(failed[0].clone()).is_none());
+    }
+
+    #[test]
+    fn test_open_offer_accepted_by_third_account() {
+        let owner_id = accounts(1);
+
+        let mut instance = get_instance();
+
+        let mut context = get_context(ft_a());
+        testing_env!(context.build());
+
+        instance.internal_on_ft_transfer(&open_offer_args(), &ft_a(), &U128(100), &owner_id);
+
+        assert_eq!(instance.escrow_open_offers_total(), 1);
+        assert!(instance.escrow_open_offers_by_asset(ft_a(), None, None, None).is_empty());
+
+        let offers = instance.escrow_open_offers_by_asset(ft_b(), None, None, None);
+
+        assert_eq!(offers.len(), 1);
+
+        let offer_id = offers[0].offer_id.clone();
+
+        let mut context = get_context(ft_b());
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_b(), &U128(50), &accounts(3));
+
+        assert!(instance.escrow_offer(offer_id).is_none());
+        assert_eq!(instance.escrow_open_offers_total(), 0);
+        assert!(instance.escrow_open_offers_by_asset(ft_b(), None, None, None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Not self")]
+    fn test_open_offer_accepted_by_owner() {
+        let owner_id = accounts(1);
+
+        let mut instance = get_instance();
+
+        let mut context = get_context(ft_a());
+        testing_env!(context.build());
+
+        instance.internal_on_ft_transfer(&open_offer_args(), &ft_a(), &U128(100), &owner_id);
+
+        let offer_id = instance.escrow_open_offers(None, None)[0].offer_id.clone();
+
+        let mut context = get_context(ft_b());
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_b(), &U128(50), &owner_id);
+    }
+
+    fn add_partial_offer(instance: &mut EscrowFeature, amount_in: u128, amount_out: u128) -> String {
+        let mut context = get_context(ft_a());
+        testing_env!(context.build());
+
+        let mut args = open_offer_args();
+
+        args.ft_amount_out = Some(U128(amount_out));
+        args.receiver_id = Some(accounts(2));
+        args.partial = Some(true);
+
+        instance.internal_on_ft_transfer(&args, &ft_a(), &U128(amount_in), &accounts(1));
+
+        instance.escrow_offers_by_owner(accounts(1), None, None)[0].offer_id.clone()
+    }
+
+    fn fill(instance: &mut EscrowFeature, offer_id: &String, amount: u128) -> U128 {
+        let mut context = get_context(ft_b());
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        match instance.internal_on_ft_transfer(&ft_deposit_args(&offer_id), &ft_b(), &U128(amount), &accounts(2)) {
+            PromiseOrValue::Value(unused) => unused,
+            _ => panic!("Unexpected promise"),
+        }
+    }
+
+    #[test]
+    fn test_fill_rounding() {
+        let mut instance = get_instance();
+        let offer_id = add_partial_offer(&mut instance, 100, 30);
+
+        // 10 * 100 / 30 is rounded down
+        assert_eq!(fill(&mut instance, &offer_id, 10), U128(0));
+
+        let offer = instance.escrow_offer(offer_id.clone()).unwrap();
+
+        assert_eq!(offer.data, EscrowEnum::FtToFt {
+            ft_contract_id_in: ft_a(),
+            ft_contract_id_out: ft_b(),
+            amount_in: U128(67),
+            amount_out: U128(20),
+        });
+        assert_eq!(offer.fill, Some(EscrowFill {
+            amount_in: U128(100),
+            amount_out: U128(30),
+            filled_in: U128(33),
+            filled_out: U128(10),
+        }));
+
+        // the rate is taken from the original amounts
+        assert_eq!(fill(&mut instance, &offer_id, 10), U128(0));
+
+        let offer = instance.escrow_offer(offer_id.clone()).unwrap();
+
+        assert_eq!(offer.fill.unwrap().filled_in, U128(66));
+    }
+
+    #[test]
+    fn test_fill_last_takes_remainder() {
+        let mut instance = get_instance();
+        let offer_id = add_partial_offer(&mut instance, 100, 30);
+
+        fill(&mut instance, &offer_id, 10);
+        fill(&mut instance, &offer_id, 10);
+
+        // the last fill gets 34 instead of the rounded 33, the rest of the payment is returned
+        assert_eq!(fill(&mut instance, &offer_id, 15), U128(5));
+
+        assert!(instance.escrow_offer(offer_id).is_none());
+        assert_eq!(instance.escrow_offers_total_by_owner(accounts(1)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fill is too small")]
+    fn test_fill_too_small() {
+        let mut instance = get_instance();
+        let offer_id = add_partial_offer(&mut instance, 1, 10);
+
+        fill(&mut instance, &offer_id, 1);
+    }
+
+    #[test]
+    fn test_fill_failed_part_is_claimable() {
+        let owner_id = accounts(1);
+        let asset = EscrowAsset::Ft { ft_contract_id: ft_b(), amount: U128(10) };
+
+        let mut instance = get_instance();
+
+        resolve_with(PromiseResult::Failed);
+
+        assert!(!instance.resolve_transfer(owner_id.clone(), asset.clone()));
+        assert_eq!(instance.escrow_claims(owner_id), vec![asset]);
+    }
+
+    #[test]
+    fn test_remove_partial_offer_failed_keeps_fill() {
+        let owner_id = accounts(1);
+
+        let mut instance = get_instance();
+        let offer_id = add_partial_offer(&mut instance, 100, 30);
+
+        fill(&mut instance, &offer_id, 10);
+
+        let offer = instance.escrow_offer(offer_id.clone()).unwrap();
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.attached_deposit(TX_PRICE).build());
+
+        instance.escrow_remove_offer(offer_id.clone());
+
+        assert!(instance.escrow_offer(offer_id.clone()).is_none());
+
+        resolve_with(PromiseResult::Failed);
+
+        instance.resolve_remove_offer(
+            owner_id,
+            offer.receiver_id.clone(),
+            offer.data.clone(),
+            offer_id.clone(),
+            None,
+            offer.fill.clone()
+        );
+
+        assert_eq!(instance.escrow_offer(offer_id.clone()), Some(offer));
+
+        // the restored offer is still filled by parts
+        assert_eq!(fill(&mut instance, &offer_id, 20), U128(0));
+        assert!(instance.escrow_offer(offer_id).is_none());
+    }
+
+    fn add_counter_offer(instance: &mut EscrowFeature, counter_offer_id: &String) -> String {
+        let mut context = get_context(ft_b());
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        let args = EscrowOnFtTransferArgs {
+            ft_contract_id_out: Some(ft_a()),
+            ft_amount_out: Some(U128(100)),
+            nft_contract_id_out: None,
+            nft_token_id_out: None,
+            receiver_id: None,
+            offer_id: None,
+            expires_at: None,
+            counter_offer_id: Some(counter_offer_id.clone()),
+            partial: None,
+        };
+
+        instance.internal_on_ft_transfer(&args, &ft_b(), &U128(40), &accounts(2));
+
+        instance.escrow_offers_by_owner(accounts(2), None, None)[0].offer_id.clone()
+    }
+
+    #[test]
+    fn test_counter_offer_accept() {
+        let owner_id = accounts(1);
+        let receiver_id = accounts(2);
+
+        let mut instance = get_instance();
+
+        let offer_id = add_ft_offer(&mut instance, None);
+        let counter_offer_id = add_counter_offer(&mut instance, &offer_id);
+
+        assert_eq!(counter_offer_id, format!("{}-1", receiver_id));
+        assert!(instance.escrow_offer(offer_id.clone()).is_none());
+
+        let counter_offer = instance.escrow_offer(counter_offer_id.clone()).unwrap();
+
+        assert_eq!(counter_offer.receiver_id, Some(owner_id.clone()));
+
+        let mut context = get_context(ft_a());
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+
+        instance.internal_on_ft_transfer(&ft_deposit_args(&counter_offer_id), &ft_a(), &U128(100), &owner_id);
+
+        assert!(instance.escrow_offer(counter_offer_id.clone()).is_none());
+
+        let thread = instance.escrow_offer_thread(counter_offer_id.clone());
+
+        assert_eq!(
+            thread
+                .iter()
+                .map(|offer| offer.offer_id.clone())
+                .collect::<Vec<String>>(),
+            vec![offer_id.clone(), counter_offer_id]
+        );
+
+        // ids of finished threads are not reused
+        let next_offer_id = add_ft_offer(&mut instance, None);
+
+        assert_ne!(next_offer_id, offer_id);
+        assert_eq!(instance.escrow_offer_thread(next_offer_id.clone()).len(), 1);
+        assert!(instance.internal_offer_is_latest(&next_offer_id));
+    }
+
+    #[test]
+    fn test_counter_offer_superseded_refund() {
+        let owner_id = accounts(1);
+
+        let mut instance = get_instance();
+
+        let offer_id = add_ft_offer(&mut instance, None);
+        let offer = instance.escrow_offer(offer_id.clone()).unwrap();
+
+        add_counter_offer(&mut instance, &offer_id);
+
+        // the countered offer is refunded to its owner
+        assert!(instance.escrow_offer(offer_id.clone()).is_none());
+        assert_eq!(instance.escrow_offers_total_by_owner(owner_id.clone()), 0);
+
+        resolve_with(PromiseResult::Failed);
+
+        instance.resolve_remove_offer(
+            owner_id.clone(),
+            offer.receiver_id.clone(),
+            offer.data.clone(),
+            offer_id.clone(),
+            None,
+            None
+        );
+
+        // failed refund restores the offer, it can be withdrawn but not accepted
+        assert!(instance.escrow_offer(offer_id.clone()).is_some());
+        assert!(!instance.internal_offer_is_latest(&offer_id));
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.attached_deposit(TX_PRICE).build());
+
+        instance.escrow_remove_offer(offer_id.clone());
+
+        assert!(instance.escrow_offer(offer_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Offer is superseded")]
+    fn test_counter_offer_superseded_accept() {
+        let owner_id = accounts(1);
+
+        let mut instance = get_instance();
+
+        let offer_id = add_ft_offer(&mut instance, None);
+        let offer = instance.escrow_offer(offer_id.clone()).unwrap();
+
+        add_counter_offer(&mut instance, &offer_id);
+
+        resolve_with(PromiseResult::Failed);
+
+        instance.resolve_remove_offer(
+            owner_id,
+            offer.receiver_id,
+            offer.data,
+            offer_id.clone(),
+            None,
+            None
+        );
+
+        fill(&mut instance, &offer_id, 50);
+    }
+}
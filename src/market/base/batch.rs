@@ -0,0 +1,280 @@
+use near_sdk::{ AccountId, Gas, Promise, PromiseResult, env, ext_contract };
+use std::collections::HashMap;
+use crate::market::base::{ MarketFeature, MarketBatch };
+use crate::market::{
+    Sale,
+    SaleItem,
+    SaleListing,
+    SaleUpdate,
+    SaleBatchFailure,
+    MarketCreateSale,
+    MarketRemoveSale,
+    MarketUpdateSale,
+};
+use crate::nft::Token;
+use crate::nft::base::external::ext_nft;
+use crate::utils::contract_token_id;
+
+/// max sales removed or updated in a single call
+pub const MAX_SALES_BATCH: usize = 50;
+/// max sales listed in a single call, each one needs a call to the nft contract
+pub const MAX_LISTINGS_BATCH: usize = 10;
+
+const GAS_FOR_NFT_TOKEN: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_LIST_BATCH: Gas = Gas(100_000_000_000_000);
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn market_resolve_list_batch(
+        &mut self,
+        owner_id: AccountId,
+        listings: Vec<SaleListing>
+    ) -> Vec<SaleBatchFailure>;
+}
+
+impl MarketFeature {
+    fn internal_check_listing(&self, listing: &SaleListing) -> Result<(), String> {
+        if listing.sale_conditions.is_empty() {
+            return Err("Sale conditions are empty".to_string());
+        }
+        for ft_token_id in listing.sale_conditions.keys() {
+            if !self.ft_token_ids.contains(&ft_token_id) {
+                return Err(format!("Token {} not supported by this market", ft_token_id));
+            }
+        }
+        if self.internal_is_listed(&contract_token_id(&listing.nft_contract_id, &listing.token_id)) {
+            return Err("Token already listed".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn internal_check_listing_token(
+        &self,
+        listing: &SaleListing,
+        owner_id: &AccountId,
+        token: Option<Token>
+    ) -> Result<Token, String> {
+        let token = token.ok_or_else(|| "No token".to_string())?;
+
+        if &token.owner_id != owner_id {
+            return Err("Must be token owner".to_string());
+        }
+
+        let approval_id = token.approved_account_ids
+            .as_ref()
+            .and_then(|approvals| approvals.get(&env::current_account_id()).cloned());
+        if approval_id != Some(listing.approval_id) {
+            return Err("Token is not approved for market".to_string());
+        }
+
+        Ok(token)
+    }
+}
+
+impl MarketBatch for MarketFeature {
+    fn market_list_batch(&mut self, listings: Vec<SaleListing>) -> Promise {
+        assert!(!listings.is_empty(), "Listings are empty");
+        assert!(
+            listings.len() <= MAX_LISTINGS_BATCH,
+            "Can't list more than {} tokens at once",
+            MAX_LISTINGS_BATCH
+        );
+
+        // ownership and approvals are checked on the nft contracts
+        let checks = listings
+            .iter()
+            .map(|listing| {
+                ext_nft
+                    ::ext(listing.nft_contract_id.clone())
+                    .with_static_gas(GAS_FOR_NFT_TOKEN)
+                    .nft_token(listing.token_id.clone())
+            })
+            .reduce(|checks, check| checks.and(check))
+            .unwrap();
+
+        checks.then(
+            ext_self
+                ::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_LIST_BATCH)
+                .market_resolve_list_batch(env::predecessor_account_id(), listings)
+        )
+    }
+
+    fn market_remove_batch(&mut self, items: Vec<SaleItem>) -> Vec<SaleBatchFailure> {
+        assert!(
+            items.len() <= MAX_SALES_BATCH,
+            "Can't remove more than {} sales at once",
+            MAX_SALES_BATCH
+        );
+
+        let account_id = env::predecessor_account_id();
+        let mut failures = vec![];
+        let mut removed: Vec<Sale> = vec![];
+
+        for item in items {
+            let sale = self.sales.get(&contract_token_id(&item.nft_contract_id, &item.token_id));
+            let result = sale
+                .ok_or_else(|| "Not found sale".to_string())
+                .and_then(|sale| self.internal_check_sale_remove(&sale, &account_id));
+
+            if let Err(error) = result {
+                failures.push(SaleBatchFailure {
+                    nft_contract_id: item.nft_contract_id,
+                    token_id: item.token_id,
+                    error,
+                });
+                continue;
+            }
+
+            let sale = self.internal_take_sale(&item.nft_contract_id, &item.token_id);
+            self.refund_all_bids(&sale.bids);
+            removed.push(sale);
+        }
+
+        if !removed.is_empty() {
+            MarketRemoveSale::emit_many(
+                &removed
+                    .iter()
+                    .map(|sale| MarketRemoveSale {
+                        owner_id: &sale.owner_id,
+                        nft_contract_id: &sale.nft_contract_id,
+                        token_id: &sale.token_id,
+                    })
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        failures
+    }
+
+    fn market_update_batch(&mut self, updates: Vec<SaleUpdate>) -> Vec<SaleBatchFailure> {
+        assert!(
+            updates.len() <= MAX_SALES_BATCH,
+            "Can't update more than {} sales at once",
+            MAX_SALES_BATCH
+        );
+
+        let account_id = env::predecessor_account_id();
+        let mut failures = vec![];
+        let mut updated: Vec<SaleUpdate> = vec![];
+
+        for update in updates {
+            let sale = self.sales.get(&contract_token_id(&update.nft_contract_id, &update.token_id));
+            let result = sale.ok_or_else(|| "No sale".to_string()).and_then(|sale| {
+                if update.sale_conditions.is_empty() {
+                    return Err("Sale conditions are empty".to_string());
+                }
+                for ft_token_id in update.sale_conditions.keys() {
+                    self.internal_check_sale_update(&sale, &account_id, &ft_token_id)?;
+                }
+                Ok(sale)
+            });
+
+            match result {
+                Ok(mut sale) => {
+                    for (ft_token_id, price) in update.sale_conditions.iter() {
+                        self.internal_set_sale_price(&mut sale, &ft_token_id, &price);
+                    }
+                    updated.push(update);
+                }
+                Err(error) => {
+                    failures.push(SaleBatchFailure {
+                        nft_contract_id: update.nft_contract_id,
+                        token_id: update.token_id,
+                        error,
+                    });
+                }
+            }
+        }
+
+        let events: Vec<MarketUpdateSale> = updated
+            .iter()
+            .flat_map(|update| {
+                let account_id = &account_id;
+                update.sale_conditions.iter().map(move |(ft_token_id, price)| MarketUpdateSale {
+                    owner_id: account_id,
+                    nft_contract_id: &update.nft_contract_id,
+                    token_id: &update.token_id,
+                    ft_token_id,
+                    price,
+                })
+            })
+            .collect();
+        if !events.is_empty() {
+            MarketUpdateSale::emit_many(&events);
+        }
+
+        failures
+    }
+
+    // #[private]
+    fn market_resolve_list_batch(
+        &mut self,
+        owner_id: AccountId,
+        listings: Vec<SaleListing>
+    ) -> Vec<SaleBatchFailure> {
+        let mut failures = vec![];
+        let mut created: Vec<Sale> = vec![];
+
+        for (index, listing) in listings.into_iter().enumerate() {
+            let token = match env::promise_result(index as u64) {
+                PromiseResult::Successful(value) =>
+                    near_sdk::serde_json::from_slice::<Option<Token>>(&value).ok().flatten(),
+                _ => None,
+            };
+
+            let result = self
+                .internal_check_listing(&listing)
+                .and_then(|_| self.internal_check_listing_token(&listing, &owner_id, token));
+
+            let token = match result {
+                Ok(token) => token,
+                Err(error) => {
+                    failures.push(SaleBatchFailure {
+                        nft_contract_id: listing.nft_contract_id,
+                        token_id: listing.token_id,
+                        error,
+                    });
+                    continue;
+                }
+            };
+
+            let sale = Sale {
+                owner_id: owner_id.clone(),
+                approval_id: listing.approval_id,
+                nft_contract_id: listing.nft_contract_id,
+                token_id: listing.token_id,
+                sale_conditions: listing.sale_conditions,
+                bids: HashMap::new(),
+                created_at: env::block_timestamp(),
+                is_auction: false,
+                auction: None,
+                dutch_auction: None,
+                starts_at: None,
+                expires_at: None,
+                types: token.types,
+                rarity: token.rarity,
+                buyers: None,
+            };
+            self.internal_insert_sale(&sale);
+            created.push(sale);
+        }
+
+        if !created.is_empty() {
+            MarketCreateSale::emit_many(
+                &created
+                    .iter()
+                    .map(|sale| MarketCreateSale {
+                        owner_id: &sale.owner_id,
+                        nft_contract_id: &sale.nft_contract_id,
+                        token_id: &sale.token_id,
+                        sale: &sale,
+                    })
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        failures
+    }
+}
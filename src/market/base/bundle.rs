@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use near_sdk::{ AccountId, Gas, Promise, PromiseResult, env, ext_contract };
+use near_sdk::collections::UnorderedSet;
+use near_sdk::json_types::U128;
+use near_sdk::borsh::BorshSerialize;
+use crate::market::base::{ MarketFeature, MarketBundles, GAS_FOR_NFT_TRANSFER, GAS_FOR_FT_TRANSFER };
+use crate::market::base::internal::StorageKey;
+use crate::market::{
+    Bundle,
+    BundleId,
+    BundleItem,
+    TokenId,
+    SaleConditions,
+    MarketBundleCreate,
+    MarketBundleRemove,
+    MarketBundleOffer,
+};
+use crate::metadata::FungibleTokenId;
+use crate::nft::Token;
+use crate::nft::base::external::ext_nft;
+use crate::reputation::{ SALE_INCREMENT, BUY_INCREMENT };
+use crate::utils::{ contract_token_id, hash_account_id, near_ft };
+
+/// every token is moved twice on purchase, so bundles are kept small to fit into 300 Tgas
+/// with a margin for the purchase call itself (4 * 18 Tgas + 152 Tgas for the resolver)
+pub const MAX_BUNDLE_SIZE: usize = 4;
+
+const GAS_FOR_NFT_TOKEN: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_BUNDLE_CREATE: Gas = Gas(20_000_000_000_000);
+const GAS_FOR_RESOLVE_BUNDLE_TRANSFER: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_BUNDLE_PURCHASE: Gas = Gas(
+    GAS_FOR_NFT_TRANSFER.0 * (MAX_BUNDLE_SIZE as u64) +
+        GAS_FOR_RESOLVE_BUNDLE_TRANSFER.0 +
+        GAS_FOR_FT_TRANSFER.0 * 10 +
+        20_000_000_000_000
+);
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn market_resolve_bundle_create(
+        &mut self,
+        owner_id: AccountId,
+        items: Vec<BundleItem>,
+        sale_conditions: SaleConditions
+    ) -> Bundle;
+    fn market_resolve_bundle_purchase(
+        &mut self,
+        ft_token_id: FungibleTokenId,
+        buyer_id: AccountId,
+        bundle: Bundle,
+        price: U128
+    ) -> U128;
+    fn market_resolve_bundle_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        tokens: Vec<(AccountId, TokenId)>
+    );
+}
+
+impl MarketFeature {
+    pub(crate) fn internal_add_bundle(&mut self, bundle: &Bundle) {
+        self.bundles.insert(&bundle.bundle_id, &bundle);
+
+        let mut by_owner_id = self.bundles_by_owner_id.get(&bundle.owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                (StorageKey::BundlesByOwnerIdInner {
+                    account_id_hash: hash_account_id(&bundle.owner_id),
+                })
+                    .try_to_vec()
+                    .unwrap()
+            )
+        });
+        by_owner_id.insert(&bundle.bundle_id);
+        self.bundles_by_owner_id.insert(&bundle.owner_id, &by_owner_id);
+
+        for item in bundle.items.iter() {
+            self.bundle_by_token_id.insert(
+                &contract_token_id(&item.nft_contract_id, &item.token_id),
+                &bundle.bundle_id
+            );
+        }
+    }
+
+    pub(crate) fn internal_remove_bundle(&mut self, bundle_id: &BundleId) -> Bundle {
+        let bundle = self.bundles.remove(&bundle_id).expect("No bundle");
+
+        let mut by_owner_id = self.bundles_by_owner_id
+            .get(&bundle.owner_id)
+            .expect("No bundle by owner_id");
+        by_owner_id.remove(&bundle_id);
+        if by_owner_id.is_empty() {
+            self.bundles_by_owner_id.remove(&bundle.owner_id);
+        } else {
+            self.bundles_by_owner_id.insert(&bundle.owner_id, &by_owner_id);
+        }
+
+        for item in bundle.items.iter() {
+            self.bundle_by_token_id.remove(&contract_token_id(&item.nft_contract_id, &item.token_id));
+        }
+
+        (MarketBundleRemove {
+            owner_id: &bundle.owner_id,
+            bundle_id: &bundle_id,
+        }).emit();
+
+        bundle
+    }
+
+    /// bundle price split between items, the last one takes the rounding remainder
+    pub(crate) fn internal_bundle_item_prices(&self, items_len: usize, price: u128) -> Vec<u128> {
+        let item_price = price / (items_len as u128);
+
+        (0..items_len)
+            .map(|index| {
+                if index == items_len - 1 {
+                    price - item_price * ((items_len as u128) - 1)
+                } else {
+                    item_price
+                }
+            })
+            .collect()
+    }
+
+    /// tokens held by market are sent to the receiver, failed ones are left to `market_bundle_claim`
+    pub(crate) fn internal_bundle_transfer(
+        &self,
+        receiver_id: &AccountId,
+        tokens: Vec<(AccountId, TokenId)>,
+        memo: &str
+    ) -> Promise {
+        let transfers = tokens
+            .iter()
+            .map(|(nft_contract_id, token_id)| {
+                ext_nft
+                    ::ext(nft_contract_id.clone())
+                    .with_static_gas(GAS_FOR_NFT_TRANSFER)
+                    .with_attached_deposit(1)
+                    .nft_transfer(receiver_id.clone(), token_id.clone(), None, Some(memo.to_string()))
+            })
+            .reduce(|transfers, transfer| transfers.and(transfer))
+            .unwrap();
+
+        transfers.then(
+            ext_self
+                ::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_BUNDLE_TRANSFER)
+                .market_resolve_bundle_transfer(receiver_id.clone(), tokens)
+        )
+    }
+
+    pub(crate) fn internal_bundle_purchase(
+        &mut self,
+        bundle_id: &BundleId,
+        ft_token_id: &FungibleTokenId,
+        amount: &U128,
+        buyer_id: &AccountId
+    ) -> Promise {
+        let bundle = self.bundles.get(&bundle_id).expect("No bundle");
+
+        assert_ne!(&bundle.owner_id, buyer_id, "Cannot buy your own bundle");
+
+        let price = bundle.sale_conditions
+            .get(&ft_token_id)
+            .expect("Not for sale in that token type");
+
+        assert_eq!(price, amount, "Amount must be equal to bundle price");
+
+        let bundle = self.internal_remove_bundle(&bundle_id);
+
+        let fee = self.internal_market_fee(&price.0, &buyer_id, None, &ft_token_id);
+
+        let price = U128(price.0 - fee);
+
+        // tokens are held by market until every transfer succeeded
+        let item_prices = self.internal_bundle_item_prices(bundle.items.len(), price.0);
+        let transfers = bundle.items
+            .iter()
+            .zip(item_prices)
+            .map(|(item, item_price)| {
+                ext_nft
+                    ::ext(item.nft_contract_id.clone())
+                    .with_static_gas(GAS_FOR_NFT_TRANSFER)
+                    .with_attached_deposit(1)
+                    .nft_transfer_payout(
+                        env::current_account_id(),
+                        item.token_id.clone(),
+                        item.approval_id,
+                        U128(item_price),
+                        10,
+                        Some("payout from market".to_string())
+                    )
+            })
+            .reduce(|transfers, transfer| transfers.and(transfer))
+            .unwrap();
+
+        transfers.then(
+            ext_self
+                ::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_BUNDLE_PURCHASE)
+                .market_resolve_bundle_purchase(
+                    ft_token_id.clone(),
+                    buyer_id.clone(),
+                    bundle,
+                    price
+                )
+        )
+    }
+}
+
+impl MarketBundles for MarketFeature {
+    fn market_bundle_create(
+        &mut self,
+        items: Vec<BundleItem>,
+        sale_conditions: SaleConditions
+    ) -> Promise {
+        assert!(items.len() > 1, "Bundle must have at least 2 tokens");
+        assert!(
+            items.len() <= MAX_BUNDLE_SIZE,
+            "Bundle can't have more than {} tokens",
+            MAX_BUNDLE_SIZE
+        );
+        assert!(!sale_conditions.is_empty(), "Sale conditions are empty");
+
+        for (ft_token_id, price) in sale_conditions.iter() {
+            if !self.ft_token_ids.contains(&ft_token_id) {
+                env::panic_str(
+                    &format!("Token {} not supported by this market", ft_token_id).to_string()
+                );
+            }
+            assert!(
+                price.0 >= (items.len() as u128),
+                "Price is too low to be split between tokens"
+            );
+        }
+
+        for (index, item) in items.iter().enumerate() {
+            assert!(
+                !items[..index]
+                    .iter()
+                    .any(
+                        |other| other.nft_contract_id == item.nft_contract_id && other.token_id == item.token_id
+                    ),
+                "Token is duplicated"
+            );
+
+            assert!(
+                !self.internal_is_listed(&contract_token_id(&item.nft_contract_id, &item.token_id)),
+                "Token already listed"
+            );
+        }
+
+        // ownership and approvals are checked on the nft contracts
+        let checks = items
+            .iter()
+            .map(|item| {
+                ext_nft
+                    ::ext(item.nft_contract_id.clone())
+                    .with_static_gas(GAS_FOR_NFT_TOKEN)
+                    .nft_token(item.token_id.clone())
+            })
+            .reduce(|checks, check| checks.and(check))
+            .unwrap();
+
+        checks.then(
+            ext_self
+                ::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_BUNDLE_CREATE)
+                .market_resolve_bundle_create(
+                    env::predecessor_account_id(),
+                    items,
+                    sale_conditions
+                )
+        )
+    }
+
+    fn market_bundle_remove(&mut self, bundle_id: BundleId) {
+        let bundle = self.bundles.get(&bundle_id).expect("No bundle");
+
+        assert_eq!(env::predecessor_account_id(), bundle.owner_id, "Must be bundle owner");
+
+        self.internal_remove_bundle(&bundle_id);
+    }
+
+    // #[payable]
+    fn market_bundle_offer(&mut self, bundle_id: BundleId) -> Promise {
+        self.internal_bundle_purchase(
+            &bundle_id,
+            &near_ft(),
+            &U128(env::attached_deposit()),
+            &env::predecessor_account_id()
+        )
+    }
+
+    fn market_bundle_claim(&mut self, nft_contract_id: AccountId, token_id: TokenId) -> Promise {
+        let receiver_id = self.bundle_claims
+            .remove(&contract_token_id(&nft_contract_id, &token_id))
+            .expect("No claim");
+
+        assert_eq!(env::predecessor_account_id(), receiver_id, "Must be claim receiver");
+
+        self.internal_bundle_transfer(
+            &receiver_id,
+            vec![(nft_contract_id, token_id)],
+            "bundle claim"
+        )
+    }
+
+    // #[private]
+    fn market_resolve_bundle_create(
+        &mut self,
+        owner_id: AccountId,
+        items: Vec<BundleItem>,
+        sale_conditions: SaleConditions
+    ) -> Bundle {
+        let market_id = env::current_account_id();
+
+        for (index, item) in items.iter().enumerate() {
+            let token = match env::promise_result(index as u64) {
+                PromiseResult::Successful(value) =>
+                    near_sdk::serde_json::from_slice::<Option<Token>>(&value).ok().flatten(),
+                _ => None,
+            };
+            let token = token.expect("No token");
+
+            // could be listed while the tokens were checked
+            assert!(
+                !self.internal_is_listed(&contract_token_id(&item.nft_contract_id, &item.token_id)),
+                "Token already listed"
+            );
+
+            assert_eq!(token.owner_id, owner_id, "Must be token owner");
+            assert_eq!(
+                token.approved_account_ids.and_then(|approvals| approvals.get(&market_id).cloned()),
+                Some(item.approval_id),
+                "Token is not approved for market"
+            );
+        }
+
+        self.bundles_nonce += 1;
+
+        let bundle = Bundle {
+            bundle_id: self.bundles_nonce.to_string(),
+            owner_id,
+            items,
+            sale_conditions,
+            created_at: env::block_timestamp(),
+        };
+
+        self.internal_add_bundle(&bundle);
+
+        (MarketBundleCreate {
+            owner_id: &bundle.owner_id,
+            bundle: &bundle,
+        }).emit();
+
+        bundle
+    }
+
+    // #[private]
+    fn market_resolve_bundle_purchase(
+        &mut self,
+        ft_token_id: FungibleTokenId,
+        buyer_id: AccountId,
+        bundle: Bundle,
+        price: U128
+    ) -> U128 {
+        let item_prices = self.internal_bundle_item_prices(bundle.items.len(), price.0);
+        // price paid by buyer, market fee is the difference
+        let paid = bundle.sale_conditions.get(&ft_token_id).unwrap().0;
+
+        // None for tokens that were not transferred
+        let payouts: Vec<Option<HashMap<AccountId, U128>>> = item_prices
+            .iter()
+            .enumerate()
+            .map(|(index, item_price)| {
+                match env::promise_result(index as u64) {
+                    PromiseResult::Successful(value) =>
+                        Some(
+                            self
+                                .internal_parse_payout(&value, &U128(*item_price), 0)
+                                .map(|payout| payout.payout)
+                                // token is transferred, bad payout from bad NFT contract goes to the seller
+                                .unwrap_or_else(|| {
+                                    vec![(bundle.owner_id.clone(), U128(*item_price))]
+                                        .into_iter()
+                                        .collect()
+                                })
+                        ),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        if payouts.iter().any(|payout| payout.is_none()) {
+            // return the tokens that did move and refund the buyer
+            let moved: Vec<(AccountId, TokenId)> = bundle.items
+                .iter()
+                .zip(payouts.iter())
+                .filter(|(_, payout)| payout.is_some())
+                .map(|(item, _)| (item.nft_contract_id.clone(), item.token_id.clone()))
+                .collect();
+
+            if !moved.is_empty() {
+                self.internal_bundle_transfer(&bundle.owner_id, moved, "bundle purchase failed");
+            }
+
+            if ft_token_id == near_ft() {
+                Promise::new(buyer_id).transfer(paid);
+
+                return U128(0);
+            }
+            // leave function and return all FTs in ft_resolve_transfer
+            return U128(paid);
+        }
+
+        self.internal_bundle_transfer(
+            &buyer_id,
+            bundle.items
+                .iter()
+                .map(|item| (item.nft_contract_id.clone(), item.token_id.clone()))
+                .collect(),
+            "bundle purchase"
+        );
+
+        // royalties of every contract are merged into a single payout
+        let mut payout: HashMap<AccountId, U128> = HashMap::new();
+        for (receiver_id, amount) in payouts.into_iter().flatten().flatten() {
+            let total = payout.get(&receiver_id).map(|total| total.0).unwrap_or(0);
+            payout.insert(receiver_id, U128(total + amount.0));
+        }
+
+        if payout.len() > 10 {
+            env::log_str("Cannot have more than 10 royalties");
+            payout = vec![(bundle.owner_id.clone(), price)].into_iter().collect();
+        }
+
+        if self.reputation.is_some() {
+            self.reputation.as_mut().unwrap().internal_add_reputation(&bundle.owner_id, &SALE_INCREMENT);
+            self.reputation.as_mut().unwrap().internal_add_reputation(&buyer_id, &BUY_INCREMENT);
+        }
+
+        // fee is attributed to nft contracts the same way price is split between items
+        let item_fees = self.internal_bundle_item_prices(bundle.items.len(), paid - price.0);
+        for (item, item_fee) in bundle.items.iter().zip(item_fees) {
+            self.internal_collect_fee(&ft_token_id, &item.nft_contract_id, item_fee);
+        }
+
+        for (receiver_id, amount) in payout.iter() {
+            self.internal_transfer(&ft_token_id, &receiver_id, amount.0);
+        }
+
+        (MarketBundleOffer {
+            owner_id: &bundle.owner_id,
+            receiver_id: &buyer_id,
+            bundle_id: &bundle.bundle_id,
+            items: &bundle.items,
+            payout: &payout,
+            ft_token_id: &ft_token_id,
+            price: &price,
+        }).emit();
+
+        // keep all FTs (already transferred for payouts)
+        U128(0)
+    }
+
+    // #[private]
+    fn market_resolve_bundle_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        tokens: Vec<(AccountId, TokenId)>
+    ) {
+        for (index, (nft_contract_id, token_id)) in tokens.iter().enumerate() {
+            if let PromiseResult::Successful(_) = env::promise_result(index as u64) {
+                continue;
+            }
+
+            env::log_str(&format!("Token {} of {} is left for claim", token_id, nft_contract_id));
+            self.bundle_claims.insert(&contract_token_id(&nft_contract_id, &token_id), &receiver_id);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use near_sdk::{ AccountId, Gas, Promise, PromiseOrValue, env, ext_contract, is_promise_success };
+use near_sdk::collections::UnorderedSet;
+use near_sdk::json_types::U128;
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::promise_result_as_success;
+use crate::market::base::{ MarketFeature, MarketCollectionOffers, GAS_FOR_NFT_TRANSFER, GAS_FOR_ROYALTIES };
+use crate::market::base::internal::StorageKey;
+use crate::market::{
+    CollectionOffer,
+    CollectionOfferId,
+    TokenId,
+    MarketCollectionOfferCreate,
+    MarketCollectionOfferRemove,
+    MarketCollectionOfferAccept,
+};
+use crate::metadata::FungibleTokenId;
+use crate::nft::{ Token, TokenTypes, TokenRarity };
+use crate::nft::base::external::ext_nft;
+use crate::reputation::{ SALE_INCREMENT, BUY_INCREMENT };
+use crate::utils::{ hash_account_id, near_ft };
+
+const GAS_FOR_NFT_TOKEN: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_OFFER_TOKEN: Gas = Gas(150_000_000_000_000);
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn market_resolve_collection_offer_token(
+        &mut self,
+        offer: CollectionOffer,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: u64
+    ) -> PromiseOrValue<bool>;
+    fn market_resolve_collection_offer(
+        &mut self,
+        offer: CollectionOffer,
+        token_id: TokenId,
+        owner_id: AccountId,
+        price: U128
+    ) -> bool;
+}
+
+impl MarketFeature {
+    pub(crate) fn internal_create_collection_offer(
+        &mut self,
+        owner_id: &AccountId,
+        nft_contract_id: &AccountId,
+        ft_token_id: &FungibleTokenId,
+        price: &U128,
+        types: &Option<TokenTypes>,
+        rarity: &Option<TokenRarity>,
+        expires_at: &Option<u64>
+    ) -> CollectionOffer {
+        let now = env::block_timestamp();
+
+        assert!(price.0 > 0, "Amount must be greater than 0");
+        assert!(expires_at.map(|expires_at| expires_at > now).unwrap_or(true), "Offer is expired");
+        if !self.ft_token_ids.contains(&ft_token_id) {
+            env::panic_str(
+                &format!("Token {} not supported by this market", ft_token_id).to_string()
+            );
+        }
+
+        self.collection_offers_nonce += 1;
+
+        let offer = CollectionOffer {
+            offer_id: self.collection_offers_nonce.to_string(),
+            owner_id: owner_id.clone(),
+            nft_contract_id: nft_contract_id.clone(),
+            ft_token_id: ft_token_id.clone(),
+            price: price.clone(),
+            types: types.clone(),
+            rarity: rarity.clone(),
+            created_at: now,
+            expires_at: expires_at.clone(),
+        };
+
+        self.internal_add_collection_offer(&offer);
+
+        (MarketCollectionOfferCreate {
+            owner_id: &offer.owner_id,
+            nft_contract_id: &offer.nft_contract_id,
+            offer: &offer,
+        }).emit();
+
+        offer
+    }
+
+    pub(crate) fn internal_add_collection_offer(&mut self, offer: &CollectionOffer) {
+        self.collection_offers.insert(&offer.offer_id, &offer);
+
+        let mut by_owner_id = self.collection_offers_by_owner_id
+            .get(&offer.owner_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    (StorageKey::CollectionOffersByOwnerIdInner {
+                        account_id_hash: hash_account_id(&offer.owner_id),
+                    })
+                        .try_to_vec()
+                        .unwrap()
+                )
+            });
+        by_owner_id.insert(&offer.offer_id);
+        self.collection_offers_by_owner_id.insert(&offer.owner_id, &by_owner_id);
+
+        let mut by_nft_contract_id = self.collection_offers_by_nft_contract_id
+            .get(&offer.nft_contract_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    (StorageKey::CollectionOffersByNFTContractIdInner {
+                        account_id_hash: hash_account_id(&offer.nft_contract_id),
+                    })
+                        .try_to_vec()
+                        .unwrap()
+                )
+            });
+        by_nft_contract_id.insert(&offer.offer_id);
+        self.collection_offers_by_nft_contract_id.insert(
+            &offer.nft_contract_id,
+            &by_nft_contract_id
+        );
+    }
+
+    pub(crate) fn internal_remove_collection_offer(
+        &mut self,
+        offer_id: &CollectionOfferId
+    ) -> CollectionOffer {
+        let offer = self.collection_offers.remove(&offer_id).expect("No offer");
+
+        let mut by_owner_id = self.collection_offers_by_owner_id
+            .get(&offer.owner_id)
+            .expect("No offer by owner_id");
+        by_owner_id.remove(&offer_id);
+        if by_owner_id.is_empty() {
+            self.collection_offers_by_owner_id.remove(&offer.owner_id);
+        } else {
+            self.collection_offers_by_owner_id.insert(&offer.owner_id, &by_owner_id);
+        }
+
+        let mut by_nft_contract_id = self.collection_offers_by_nft_contract_id
+            .get(&offer.nft_contract_id)
+            .expect("No offer by nft_contract_id");
+        by_nft_contract_id.remove(&offer_id);
+        if by_nft_contract_id.is_empty() {
+            self.collection_offers_by_nft_contract_id.remove(&offer.nft_contract_id);
+        } else {
+            self.collection_offers_by_nft_contract_id.insert(
+                &offer.nft_contract_id,
+                &by_nft_contract_id
+            );
+        }
+
+        offer
+    }
+
+    pub(crate) fn internal_accept_collection_offer(
+        &mut self,
+        offer_id: &CollectionOfferId,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        approval_id: &u64
+    ) -> Promise {
+        let offer = self.collection_offers.get(&offer_id).expect("No offer");
+
+        assert_eq!(&offer.nft_contract_id, nft_contract_id, "Offer is for another nft contract");
+        assert_ne!(&offer.owner_id, owner_id, "Cannot accept your own offer");
+        assert!(!offer.is_expired(env::block_timestamp()), "Offer is expired");
+
+        // taken out before cross contract calls, resolvers put it back on failure
+        self.internal_remove_collection_offer(&offer_id);
+
+        if offer.types.is_none() && offer.rarity.is_none() {
+            return self.internal_collection_offer_transfer(
+                offer,
+                token_id.clone(),
+                owner_id.clone(),
+                approval_id.clone()
+            );
+        }
+
+        ext_nft
+            ::ext(nft_contract_id.clone())
+            .with_static_gas(GAS_FOR_NFT_TOKEN)
+            .nft_token(token_id.clone())
+            .then(
+                ext_self
+                    ::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_OFFER_TOKEN)
+                    .market_resolve_collection_offer_token(
+                        offer,
+                        token_id.clone(),
+                        owner_id.clone(),
+                        approval_id.clone()
+                    )
+            )
+    }
+
+    pub(crate) fn internal_collection_offer_transfer(
+        &mut self,
+        offer: CollectionOffer,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: u64
+    ) -> Promise {
+        let fee = self.internal_market_fee(
+            &offer.price.0,
+            &offer.owner_id,
+            Some(&offer.nft_contract_id),
+            &offer.ft_token_id
+        );
+
+        let price = U128(offer.price.0 - fee);
+
+        ext_nft
+            ::ext(offer.nft_contract_id.clone())
+            .with_static_gas(GAS_FOR_NFT_TRANSFER)
+            .with_attached_deposit(1)
+            .nft_transfer_payout(
+                offer.owner_id.clone(),
+                token_id.clone(),
+                approval_id,
+                price,
+                10,
+                Some("payout from market".to_string())
+            )
+            .then(
+                ext_self
+                    ::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ROYALTIES)
+                    .market_resolve_collection_offer(offer, token_id, owner_id, price)
+            )
+    }
+}
+
+impl MarketCollectionOffers for MarketFeature {
+    // #[payable]
+    fn market_collection_offer_create(
+        &mut self,
+        nft_contract_id: AccountId,
+        types: Option<TokenTypes>,
+        rarity: Option<TokenRarity>,
+        expires_at: Option<u64>
+    ) -> CollectionOffer {
+        self.internal_create_collection_offer(
+            &env::predecessor_account_id(),
+            &nft_contract_id,
+            &near_ft(),
+            &U128(env::attached_deposit()),
+            &types,
+            &rarity,
+            &expires_at
+        )
+    }
+
+    fn market_collection_offer_remove(&mut self, offer_id: CollectionOfferId) {
+        let offer = self.collection_offers.get(&offer_id).expect("No offer");
+
+        assert!(
+            env::predecessor_account_id() == offer.owner_id ||
+                offer.is_expired(env::block_timestamp()),
+            "Must be offer owner"
+        );
+
+        self.internal_remove_collection_offer(&offer_id);
+        self.internal_transfer(&offer.ft_token_id, &offer.owner_id, offer.price.0);
+
+        (MarketCollectionOfferRemove {
+            owner_id: &offer.owner_id,
+            nft_contract_id: &offer.nft_contract_id,
+            offer_id: &offer_id,
+        }).emit();
+    }
+
+    // #[private]
+    fn market_resolve_collection_offer_token(
+        &mut self,
+        offer: CollectionOffer,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: u64
+    ) -> PromiseOrValue<bool> {
+        let token = promise_result_as_success().and_then(|value| {
+            near_sdk::serde_json::from_slice::<Option<Token>>(&value).ok().flatten()
+        });
+        let is_matched = token
+            .map(|token| token.owner_id == owner_id && offer.matches(&token))
+            .unwrap_or(false);
+
+        if !is_matched {
+            env::log_str("Token does not match the offer");
+            self.internal_add_collection_offer(&offer);
+
+            return PromiseOrValue::Value(false);
+        }
+
+        PromiseOrValue::Promise(
+            self.internal_collection_offer_transfer(offer, token_id, owner_id, approval_id)
+        )
+    }
+
+    // #[private]
+    fn market_resolve_collection_offer(
+        &mut self,
+        offer: CollectionOffer,
+        token_id: TokenId,
+        owner_id: AccountId,
+        price: U128
+    ) -> bool {
+        if !is_promise_success() {
+            // token was not transferred, offer is available again
+            self.internal_add_collection_offer(&offer);
+
+            return false;
+        }
+
+        // token is transferred, bad payout from bad NFT contract goes to the seller
+        let payout = self
+            .internal_payout_result(&price, 0)
+            .map(|payout| payout.payout)
+            .unwrap_or_else(|| vec![(owner_id.clone(), price)].into_iter().collect());
+
+        if self.reputation.is_some() {
+            self.reputation.as_mut().unwrap().internal_add_reputation(&owner_id, &SALE_INCREMENT);
+            self.reputation.as_mut().unwrap().internal_add_reputation(&offer.owner_id, &BUY_INCREMENT);
+        }
+
+        self.internal_collect_fee(
+            &offer.ft_token_id,
+            &offer.nft_contract_id,
+            offer.price.0 - price.0
+        );
+
+        for (receiver_id, amount) in payout.iter() {
+            self.internal_transfer(&offer.ft_token_id, &receiver_id, amount.0);
+        }
+
+        (MarketCollectionOfferAccept {
+            offer_id: &offer.offer_id,
+            owner_id: &owner_id,
+            receiver_id: &offer.owner_id,
+            nft_contract_id: &offer.nft_contract_id,
+            token_id: &token_id,
+            payout: &payout,
+            ft_token_id: &offer.ft_token_id,
+            price: &price,
+        }).emit();
+
+        true
+    }
+}
@@ -1,12 +1,31 @@
+use std::collections::HashMap;
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::collections::{ LookupMap, UnorderedMap, UnorderedSet, TreeMap };
 use near_sdk::json_types::{ U128 };
 use near_sdk::serde::{ Serialize };
-use near_sdk::{AccountId, Balance, Gas, CryptoHash, BorshStorageKey, Promise, promise_result_as_success, env, ext_contract, IntoStorageKey, assert_self};
-use crate::market::metadata::{ ContractAndTokenId, TokenId };
-use crate::market::{ Sale, MarketCore, Bid, MarketUpdateSale, MarketOffer };
+use near_sdk::{AccountId, Balance, Gas, CryptoHash, BorshStorageKey, Promise, env, ext_contract, IntoStorageKey, assert_self, promise_result_as_success};
+use crate::market::metadata::{
+    ContractAndTokenId,
+    TokenId,
+    CollectionOfferId,
+    CollectionOffer,
+    BundleId,
+    Bundle,
+    FeeTier,
+};
+use crate::market::{
+    Sale,
+    MarketCore,
+    Bid,
+    MarketUpdateSale,
+    MarketOffer,
+    MarketAuctionExtend,
+    MarketAuctionSettle,
+};
 use crate::utils::{ contract_token_id, near_ft };
+use crate::market::base::default_fee_tiers;
 use crate::nft::base::external::{ ext_nft };
+use crate::nft::Token;
 use crate::ft::base::external::{ ext_ft };
 use crate::nft::royalty::Payout;
 use crate::metadata::FungibleTokenId;
@@ -41,6 +60,28 @@ pub struct MarketFeature {
     pub storage_deposits: LookupMap<AccountId, Balance>,
     pub bid_history_length: u8,
     pub reputation: Option<ReputationFeature>,
+    pub collection_offers: UnorderedMap<CollectionOfferId, CollectionOffer>,
+    pub collection_offers_by_owner_id: LookupMap<AccountId, UnorderedSet<CollectionOfferId>>,
+    pub collection_offers_by_nft_contract_id: LookupMap<AccountId, UnorderedSet<CollectionOfferId>>,
+    pub collection_offers_nonce: u64,
+    pub bundles: UnorderedMap<BundleId, Bundle>,
+    pub bundles_by_owner_id: LookupMap<AccountId, UnorderedSet<BundleId>>,
+    pub bundles_nonce: u64,
+    pub bundle_by_token_id: LookupMap<ContractAndTokenId, BundleId>,
+    /// bundle tokens held by market after a failed transfer, by receiver
+    pub bundle_claims: LookupMap<ContractAndTokenId, AccountId>,
+    pub fee_tiers: Vec<FeeTier>,
+    pub fee_by_nft_contract_id: LookupMap<AccountId, u16>,
+    pub fee_by_ft_token_id: LookupMap<FungibleTokenId, u16>,
+    pub treasury: LookupMap<FungibleTokenId, Balance>,
+    pub fees_collected: LookupMap<FungibleTokenId, Balance>,
+    pub fees_collected_by_nft_contract_id: LookupMap<AccountId, HashMap<FungibleTokenId, U128>>,
+    pub sales_by_expires_at: TreeMap<(u64, ContractAndTokenId), ()>,
+    pub sales_by_price: TreeMap<(FungibleTokenId, u128, ContractAndTokenId), ()>,
+    pub sales_by_created_at: TreeMap<(u64, ContractAndTokenId), ()>,
+    pub private_sales_by_buyer_id: LookupMap<AccountId, UnorderedSet<ContractAndTokenId>>,
+    /// private sales are kept out of the owner and contract indexes and of supply views
+    pub private_sales_supply: u64,
 }
 
 /// Helper structure to for keys of the persistent collections.
@@ -60,7 +101,7 @@ pub enum StorageKey {
 }
 
 impl MarketFeature {
-    pub fn new<M1, M2, M3, M4, M5, R1>(
+    pub fn new<M1, M2, M3, M4, M5, O1, O2, O3, B1, B2, B3, B4, F1, F2, F3, F4, F5, E1, I1, I2, P1, R1>(
         owner_id: AccountId,
         ft_token_ids: Option<Vec<FungibleTokenId>>,
         bid_history_length: Option<u8>,
@@ -69,6 +110,22 @@ impl MarketFeature {
         by_contract_prefix: M3,
         ft_tokens_prefix: M4,
         storage_prefix: M5,
+        collection_offers_prefix: O1,
+        collection_offers_by_owner_prefix: O2,
+        collection_offers_by_contract_prefix: O3,
+        bundles_prefix: B1,
+        bundles_by_owner_prefix: B2,
+        bundle_by_token_prefix: B3,
+        bundle_claims_prefix: B4,
+        fee_by_nft_contract_prefix: F1,
+        fee_by_ft_token_prefix: F2,
+        treasury_prefix: F3,
+        fees_collected_prefix: F4,
+        fees_collected_by_contract_prefix: F5,
+        sales_by_expires_at_prefix: E1,
+        sales_by_price_prefix: I1,
+        sales_by_created_at_prefix: I2,
+        private_sales_by_buyer_prefix: P1,
         reputation_prefix: Option<R1>,
     )
         -> Self
@@ -78,6 +135,22 @@ impl MarketFeature {
             M3: IntoStorageKey,
             M4: IntoStorageKey,
             M5: IntoStorageKey,
+            O1: IntoStorageKey,
+            O2: IntoStorageKey,
+            O3: IntoStorageKey,
+            B1: IntoStorageKey,
+            B2: IntoStorageKey,
+            B3: IntoStorageKey,
+            B4: IntoStorageKey,
+            F1: IntoStorageKey,
+            F2: IntoStorageKey,
+            F3: IntoStorageKey,
+            F4: IntoStorageKey,
+            F5: IntoStorageKey,
+            E1: IntoStorageKey,
+            I1: IntoStorageKey,
+            I2: IntoStorageKey,
+            P1: IntoStorageKey,
             R1: IntoStorageKey,
     {
         let reputation = reputation_prefix.map(|prefix| {
@@ -92,6 +165,28 @@ impl MarketFeature {
             storage_deposits: LookupMap::new(storage_prefix),
             bid_history_length: bid_history_length.unwrap_or(BID_HISTORY_LENGTH_DEFAULT),
             reputation,
+            collection_offers: UnorderedMap::new(collection_offers_prefix),
+            collection_offers_by_owner_id: LookupMap::new(collection_offers_by_owner_prefix),
+            collection_offers_by_nft_contract_id: LookupMap::new(
+                collection_offers_by_contract_prefix
+            ),
+            collection_offers_nonce: 0,
+            bundles: UnorderedMap::new(bundles_prefix),
+            bundles_by_owner_id: LookupMap::new(bundles_by_owner_prefix),
+            bundles_nonce: 0,
+            bundle_by_token_id: LookupMap::new(bundle_by_token_prefix),
+            bundle_claims: LookupMap::new(bundle_claims_prefix),
+            fee_tiers: default_fee_tiers(),
+            fee_by_nft_contract_id: LookupMap::new(fee_by_nft_contract_prefix),
+            fee_by_ft_token_id: LookupMap::new(fee_by_ft_token_prefix),
+            treasury: LookupMap::new(treasury_prefix),
+            fees_collected: LookupMap::new(fees_collected_prefix),
+            fees_collected_by_nft_contract_id: LookupMap::new(fees_collected_by_contract_prefix),
+            sales_by_expires_at: TreeMap::new(sales_by_expires_at_prefix),
+            sales_by_price: TreeMap::new(sales_by_price_prefix),
+            sales_by_created_at: TreeMap::new(sales_by_created_at_prefix),
+            private_sales_by_buyer_id: LookupMap::new(private_sales_by_buyer_prefix),
+            private_sales_supply: 0,
         };
         // support NEAR by default
         this.ft_token_ids.insert(&near_ft());
@@ -137,8 +232,9 @@ impl MarketCore for MarketFeature {
         }
 
         if let Some(sale) = sale {
-            let owner_id = env::predecessor_account_id();
-            assert_eq!(owner_id, sale.owner_id, "Must be sale owner");
+            if let Err(error) = self.internal_check_sale_remove(&sale, &env::predecessor_account_id()) {
+                env::panic_str(&error);
+            }
 
             self.internal_remove_sale(&nft_contract_id, &token_id);
 
@@ -157,15 +253,18 @@ impl MarketCore for MarketFeature {
         // assert_at_least_one_yocto();
         let contract_and_token_id = contract_token_id(&nft_contract_id, &token_id);
         let mut sale = self.sales.get(&contract_and_token_id).expect("No sale");
-        assert_eq!(env::predecessor_account_id(), sale.owner_id, "Must be sale owner");
 
-        if !self.ft_token_ids.contains(&ft_token_id) {
-            env::panic_str(
-                &format!("Token {} not supported by this market", ft_token_id).to_string()
-            );
+        if
+            let Err(error) = self.internal_check_sale_update(
+                &sale,
+                &env::predecessor_account_id(),
+                &ft_token_id
+            )
+        {
+            env::panic_str(&error);
         }
-        sale.sale_conditions.insert(ft_token_id.clone(), price);
-        self.sales.insert(&contract_and_token_id, &sale);
+
+        self.internal_set_sale_price(&mut sale, &ft_token_id, &price);
 
         (MarketUpdateSale {
             owner_id: &sale.owner_id,
@@ -185,13 +284,31 @@ impl MarketCore for MarketFeature {
             .expect(&format!("No sale {}", contract_and_token_id.to_string()));
         let buyer_id = env::predecessor_account_id();
         assert_ne!(sale.owner_id, buyer_id, "Cannot bid on your own sale.");
+        self.assert_sale_active(&sale);
+        self.assert_sale_buyer(&sale, &buyer_id);
         let ft_token_id = near_ft();
-        let price = sale.sale_conditions.get(&ft_token_id).expect("Not for sale in NEAR").0;
+        assert!(sale.sale_conditions.contains_key(&ft_token_id), "Not for sale in NEAR");
+        let price = self.internal_sale_price(&sale, &ft_token_id);
 
         let deposit = env::attached_deposit();
         assert!(deposit > 0, "Attached deposit must be greater than 0");
 
-        if !sale.is_auction && deposit == price {
+        if let Some(dutch_auction) = &sale.dutch_auction {
+            assert!(env::block_timestamp() >= dutch_auction.start_at, "Auction is not started");
+            assert!(deposit >= price, "Attached deposit must be greater than current price");
+
+            if deposit > price {
+                self.internal_transfer(&ft_token_id, &buyer_id, deposit - price);
+            }
+
+            self.market_process_purchase(
+                nft_contract_id.clone(),
+                token_id,
+                ft_token_id,
+                U128(price),
+                buyer_id
+            );
+        } else if !sale.is_auction && deposit == price {
             self.market_process_purchase(
                 nft_contract_id.clone(),
                 token_id,
@@ -216,6 +333,16 @@ impl MarketCore for MarketFeature {
         buyer_id: AccountId,
         sale: &mut Sale
     ) {
+        let now = env::block_timestamp();
+        let mut min_bid_increment = 0;
+
+        if let Some(auction) = &sale.auction {
+            assert!(now >= auction.start_at, "Auction is not started");
+            assert!(now < auction.end_at, "Auction is ended");
+
+            min_bid_increment = auction.min_bid_increment.0;
+        }
+
         // store a bid and refund any current bid lower
         let new_bid = Bid {
             owner_id: buyer_id,
@@ -231,6 +358,11 @@ impl MarketCore for MarketFeature {
                 "Can't pay less than or equal to current bid price: {}",
                 current_bid.price.0
             );
+            assert!(
+                amount >= current_bid.price.0 + min_bid_increment,
+                "Bid must be at least {} higher than current bid",
+                min_bid_increment
+            );
             if ft_token_id == near_ft() {
                 Promise::new(current_bid.owner_id.clone()).transfer(u128::from(current_bid.price));
             } else {
@@ -247,6 +379,19 @@ impl MarketCore for MarketFeature {
             bids_for_token_id.remove(0);
         }
 
+        if let Some(auction) = sale.auction.as_mut() {
+            // anti-sniping: late bid extends the auction
+            if auction.end_at - now < auction.extension_time {
+                auction.end_at = now + auction.extension_time;
+
+                (MarketAuctionExtend {
+                    nft_contract_id: &sale.nft_contract_id,
+                    token_id: &sale.token_id,
+                    end_at: &auction.end_at,
+                }).emit();
+            }
+        }
+
         self.sales.insert(&contract_and_token_id, &sale);
     }
 
@@ -259,6 +404,8 @@ impl MarketCore for MarketFeature {
         let contract_and_token_id = contract_token_id(&nft_contract_id, &token_id);
         // remove bid before proceeding to process purchase
         let mut sale = self.sales.get(&contract_and_token_id).expect("No sale");
+        assert!(sale.auction.is_none(), "Timed auction must be settled");
+        self.assert_sale_active(&sale);
         let bids_for_token_id = sale.bids.remove(&ft_token_id).expect("No bids");
         let bid = &bids_for_token_id[bids_for_token_id.len() - 1];
         self.sales.insert(&contract_and_token_id, &sale);
@@ -273,6 +420,125 @@ impl MarketCore for MarketFeature {
         );
     }
 
+    // #[private]
+    fn market_resolve_sale_token(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: TokenId,
+        approval_id: u64
+    ) {
+        let contract_and_token_id = contract_token_id(&nft_contract_id, &token_id);
+
+        let token = promise_result_as_success().and_then(|value| {
+            near_sdk::serde_json::from_slice::<Option<Token>>(&value).ok().flatten()
+        });
+
+        // sale could be removed or relisted while token was requested
+        if let (Some(token), Some(mut sale)) = (token, self.sales.get(&contract_and_token_id)) {
+            if sale.approval_id == approval_id {
+                sale.types = token.types;
+                sale.rarity = token.rarity;
+                self.sales.insert(&contract_and_token_id, &sale);
+            }
+        }
+    }
+
+    fn market_remove_expired_sales(&mut self, limit: u64) -> u64 {
+        let now = env::block_timestamp();
+
+        let expired: Vec<(u64, ContractAndTokenId)> = self.sales_by_expires_at
+            .iter()
+            .map(|(key, _)| key)
+            .take_while(|(expires_at, _)| *expires_at <= now)
+            .take(limit as usize)
+            .collect();
+
+        for (_, contract_and_token_id) in expired.iter() {
+            let sale = self.sales.get(&contract_and_token_id).expect("No sale");
+            let sale = self.internal_remove_sale(&sale.nft_contract_id, &sale.token_id);
+
+            self.refund_all_bids(&sale.bids);
+        }
+
+        expired.len() as u64
+    }
+
+    fn market_auction_settle(&mut self, nft_contract_id: AccountId, token_id: String) {
+        let contract_and_token_id = contract_token_id(&nft_contract_id, &token_id);
+        let mut sale = self.sales.get(&contract_and_token_id).expect("No sale");
+        let auction = sale.auction.clone().expect("Sale is not a timed auction");
+
+        assert!(env::block_timestamp() >= auction.end_at, "Auction is not ended");
+
+        let ft_token_id = sale.sale_conditions.keys().next().expect("No sale conditions").clone();
+        let winner = sale.bids
+            .get(&ft_token_id)
+            .and_then(|bids| bids.last().cloned())
+            .filter(|bid| bid.price.0 >= auction.reserve_price.0);
+
+        if let Some(bid) = winner {
+            // winning bid is paid out or returned by the resolver, the rest of the bids are refunded there
+            self.internal_remove_sale(&nft_contract_id, &token_id);
+            sale.bids.remove(&ft_token_id);
+
+            (MarketAuctionSettle {
+                owner_id: &sale.owner_id,
+                nft_contract_id: &nft_contract_id,
+                token_id: &token_id,
+                ft_token_id: &ft_token_id,
+                winner_id: Some(&bid.owner_id),
+                price: Some(&bid.price),
+            }).emit();
+
+            let fee = self.internal_market_fee(
+                &bid.price.0,
+                &bid.owner_id,
+                Some(&nft_contract_id),
+                &ft_token_id
+            );
+
+            let price = U128(bid.price.0 - fee);
+
+            ext_nft
+                ::ext(nft_contract_id.clone())
+                .with_static_gas(GAS_FOR_NFT_TRANSFER)
+                .with_attached_deposit(1)
+                .nft_transfer_payout(
+                    bid.owner_id.clone(),
+                    token_id,
+                    sale.approval_id,
+                    price,
+                    10,
+                    Some("payout from market".to_string())
+                )
+                .then(
+                    ext_self
+                        ::ext(env::current_account_id())
+                        .with_static_gas(GAS_FOR_ROYALTIES)
+                        .market_resolve_auction_settle(
+                            ft_token_id,
+                            bid.owner_id,
+                            sale,
+                            price,
+                            U128(fee)
+                        )
+                );
+        } else {
+            // reserve not met, return token to the owner and refund bids
+            self.internal_remove_sale(&nft_contract_id, &token_id);
+            self.refund_all_bids(&sale.bids);
+
+            (MarketAuctionSettle {
+                owner_id: &sale.owner_id,
+                nft_contract_id: &nft_contract_id,
+                token_id: &token_id,
+                ft_token_id: &ft_token_id,
+                winner_id: None,
+                price: None,
+            }).emit();
+        }
+    }
+
     // #[private]
     fn market_process_purchase(
         &mut self,
@@ -284,7 +550,12 @@ impl MarketCore for MarketFeature {
     ) -> Promise {
         let sale = self.internal_remove_sale(&nft_contract_id, &token_id);
 
-        let fee = self.internal_market_fee(&price.0, &buyer_id);
+        let fee = self.internal_market_fee(
+            &price.0,
+            &buyer_id,
+            Some(&nft_contract_id),
+            &ft_token_id
+        );
 
         let price = U128(price.0 - fee);
 
@@ -304,7 +575,7 @@ impl MarketCore for MarketFeature {
                 ext_self
                     ::ext(env::current_account_id())
                     .with_static_gas(GAS_FOR_ROYALTIES)
-                    .market_resolve_purchase(ft_token_id, buyer_id, sale, price)
+                    .market_resolve_purchase(ft_token_id, buyer_id, sale, price, U128(fee))
             )
     }
 
@@ -317,52 +588,26 @@ impl MarketCore for MarketFeature {
         ft_token_id: FungibleTokenId,
         buyer_id: AccountId,
         sale: Sale,
-        price: U128
+        price: U128,
+        fee: U128
     ) -> U128 {
         // checking for payout information
-        let payout_option = promise_result_as_success().and_then(|value| {
-            // None means a bad payout from bad NFT contract
-            near_sdk::serde_json
-                ::from_slice::<Payout>(&value)
-                .ok()
-                .and_then(|payout| {
-                    // gas to do 10 FT transfers (and definitely 10 NEAR transfers)
-                    if payout.payout.len() + sale.bids.len() > 10 || payout.payout.is_empty() {
-                        env::log_str(
-                            &format!(
-                                "Cannot have more than 10 royalties and sale.bids refunds"
-                            ).to_string()
-                        );
-                        None
-                    } else {
-                        // TODO off by 1 e.g. payouts are fractions of 3333 + 3333 + 3333
-                        let mut remainder = price.0;
-                        for &value in payout.payout.values() {
-                            remainder = remainder.checked_sub(value.0)?;
-                        }
-
-                        if remainder == 0 || remainder == 1 {
-                            Some(payout)
-                        } else {
-                            None
-                        }
-                    }
-                })
-        });
+        let payout_option = self.internal_payout_result(&price, sale.bids.len());
         // is payout option valid?
         let payout: Payout = if let Some(payout_option) = payout_option {
             payout_option
         } else {
-            let fee = self.internal_market_fee(&price.0, &buyer_id);
             if ft_token_id == AccountId::new_unchecked("near".to_string()) {
-                Promise::new(buyer_id).transfer(u128::from(price) + fee);
+                Promise::new(buyer_id).transfer(u128::from(price) + fee.0);
             }
             // leave function and return all FTs in ft_resolve_transfer
-            return U128(price.0 + fee);
+            return U128(price.0 + fee.0);
         };
         // Going to payout everyone, first return all outstanding bids (accepted offer bid was already removed)
         self.refund_all_bids(&sale.bids);
 
+        self.internal_collect_fee(&ft_token_id, &sale.nft_contract_id, fee.0);
+
         if self.reputation.is_some() {
             self.reputation.as_mut().unwrap().internal_add_reputation(&sale.owner_id, &SALE_INCREMENT);
             self.reputation.as_mut().unwrap().internal_add_reputation(&buyer_id, &BUY_INCREMENT);
@@ -409,6 +654,38 @@ impl MarketCore for MarketFeature {
             U128(0)
         }
     }
+
+    // #[private]
+
+    fn market_resolve_auction_settle(
+        &mut self,
+        ft_token_id: FungibleTokenId,
+        buyer_id: AccountId,
+        sale: Sale,
+        price: U128,
+        fee: U128
+    ) -> U128 {
+        if self.internal_payout_result(&price, sale.bids.len()).is_some() {
+            return self.market_resolve_purchase(ft_token_id, buyer_id, sale, price, fee);
+        }
+
+        // token is not transferred and settle is not in ft_transfer_call, return the winning bid here
+        let amount = U128(price.0 + fee.0);
+
+        if ft_token_id == near_ft() {
+            Promise::new(buyer_id).transfer(amount.0);
+        } else {
+            ext_ft
+                ::ext(ft_token_id)
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .with_attached_deposit(1)
+                .ft_transfer(buyer_id, amount, None);
+        }
+
+        self.refund_all_bids(&sale.bids);
+
+        U128(0)
+    }
 }
 
 /// self call
@@ -420,6 +697,16 @@ trait ExtSelf {
         ft_token_id: AccountId,
         buyer_id: AccountId,
         sale: Sale,
-        price: U128
+        price: U128,
+        fee: U128
     ) -> Promise;
+
+    fn market_resolve_auction_settle(
+        &mut self,
+        ft_token_id: AccountId,
+        buyer_id: AccountId,
+        sale: Sale,
+        price: U128,
+        fee: U128
+    ) -> U128;
 }
@@ -1,28 +1,267 @@
-use near_sdk::AccountId;
-use crate::market::base::{ MarketFeature, MARKET_BASE_FEE, MARKET_REDUCED_FEE, MARKET_MIN_FEE };
+use std::collections::HashMap;
+use near_sdk::{ AccountId, Balance, Gas, Promise, env, ext_contract, is_promise_success };
+use near_sdk::json_types::U128;
+use crate::market::base::{ MarketFeature, MarketFees, MARKET_BASE_FEE, MARKET_REDUCED_FEE, MARKET_MIN_FEE };
+use crate::market::{
+    FeeTier,
+    MarketFeeTiersUpdate,
+    MarketNftContractFeeUpdate,
+    MarketFtTokenFeeUpdate,
+    MarketTreasuryWithdraw,
+    MarketTreasuryWithdrawFail,
+};
+use crate::metadata::FungibleTokenId;
 use crate::reputation::MAX_REPUTATION;
+use crate::utils::near_ft;
+
+/// fees are in basis points, market never takes more than 10% of a price
+pub const MARKET_MAX_FEE: u16 = 1_000;
+
+const GAS_FOR_RESOLVE_TREASURY_WITHDRAW: Gas = Gas(10_000_000_000_000);
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn market_resolve_treasury_withdraw(
+        &mut self,
+        ft_token_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128
+    ) -> bool;
+}
+
+/// 3% for everyone, 2% above half of max reputation and 1% at max reputation
+pub fn default_fee_tiers() -> Vec<FeeTier> {
+    vec![
+        FeeTier { reputation: 0, fee: MARKET_BASE_FEE },
+        FeeTier { reputation: MAX_REPUTATION / 2 + 1, fee: MARKET_REDUCED_FEE },
+        FeeTier { reputation: MAX_REPUTATION, fee: MARKET_MIN_FEE }
+    ]
+}
 
 impl MarketFeature {
+    /// fee in basis points, nft contract override goes first, then ft token override,
+    /// then reputation tiers
+    pub fn internal_market_fee_percent(
+        &self,
+        account_id: &AccountId,
+        nft_contract_id: Option<&AccountId>,
+        ft_token_id: &FungibleTokenId
+    ) -> u16 {
+        if let Some(fee) = nft_contract_id.and_then(|id| self.fee_by_nft_contract_id.get(&id)) {
+            return fee;
+        }
+
+        if let Some(fee) = self.fee_by_ft_token_id.get(&ft_token_id) {
+            return fee;
+        }
+
+        let reputation = self.reputation
+            .as_ref()
+            .map(|reputation| reputation.internal_reputation(&account_id))
+            .unwrap_or(0);
+
+        self.fee_tiers
+            .iter()
+            .rev()
+            .find(|tier| reputation >= tier.reputation)
+            .map(|tier| tier.fee)
+            .unwrap_or(MARKET_BASE_FEE)
+    }
+
     pub fn internal_market_fee(
         &mut self,
         price: &u128,
-        account_id: &AccountId,        
+        account_id: &AccountId,
+        nft_contract_id: Option<&AccountId>,
+        ft_token_id: &FungibleTokenId
     ) -> u128 {
-        let mut fee_percent = MARKET_BASE_FEE;  
-        
-        if self.reputation.is_some() {
-            let reputation = self.reputation.as_ref().unwrap().internal_reputation(&account_id);
-            
-            if reputation > MAX_REPUTATION/2 && reputation != MAX_REPUTATION {
-                fee_percent = MARKET_REDUCED_FEE;
-            } 
-            
-            if reputation == MAX_REPUTATION {
-                fee_percent = MARKET_MIN_FEE;
-            }
-        }
+        let fee_percent = self.internal_market_fee_percent(account_id, nft_contract_id, ft_token_id);
+
         let fee: u128 = price * fee_percent as u128 / 10_000u128;
-        
+
         fee
     }
-} 
+
+    pub(crate) fn internal_add_treasury(&mut self, ft_token_id: &FungibleTokenId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+
+        let balance = self.treasury.get(&ft_token_id).unwrap_or(0);
+        self.treasury.insert(&ft_token_id, &(balance + amount));
+    }
+
+    /// fee taken from a purchase, `nft_contract_id` is the contract of the sold token
+    pub(crate) fn internal_collect_fee(
+        &mut self,
+        ft_token_id: &FungibleTokenId,
+        nft_contract_id: &AccountId,
+        amount: Balance
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        self.internal_add_treasury(&ft_token_id, amount);
+
+        let collected = self.fees_collected.get(&ft_token_id).unwrap_or(0);
+        self.fees_collected.insert(&ft_token_id, &(collected + amount));
+
+        let mut by_nft_contract_id = self.fees_collected_by_nft_contract_id
+            .get(&nft_contract_id)
+            .unwrap_or_default();
+        let collected = by_nft_contract_id.get(ft_token_id).map(|collected| collected.0).unwrap_or(0);
+        by_nft_contract_id.insert(ft_token_id.clone(), U128(collected + amount));
+        self.fees_collected_by_nft_contract_id.insert(&nft_contract_id, &by_nft_contract_id);
+    }
+
+    pub(crate) fn internal_sub_treasury(&mut self, ft_token_id: &FungibleTokenId, amount: Balance) {
+        let balance = self.treasury.get(&ft_token_id).unwrap_or(0);
+
+        assert!(balance >= amount, "Not enough funds in treasury");
+
+        self.treasury.insert(&ft_token_id, &(balance - amount));
+    }
+}
+
+impl MarketFees for MarketFeature {
+    fn market_set_fee_tiers(&mut self, fee_tiers: Vec<FeeTier>) {
+        assert_eq!(
+            fee_tiers.first().map(|tier| tier.reputation),
+            Some(0),
+            "First tier must start from 0 reputation"
+        );
+        for (index, tier) in fee_tiers.iter().enumerate() {
+            assert!(tier.fee <= MARKET_MAX_FEE, "Fee can't be more than {}", MARKET_MAX_FEE);
+            if index > 0 {
+                assert!(
+                    tier.reputation > fee_tiers[index - 1].reputation,
+                    "Tiers must be sorted by reputation"
+                );
+            }
+        }
+
+        self.fee_tiers = fee_tiers;
+
+        (MarketFeeTiersUpdate {
+            fee_tiers: &self.fee_tiers,
+        }).emit();
+    }
+
+    fn market_set_nft_contract_fee(&mut self, nft_contract_id: AccountId, fee: Option<u16>) {
+        if let Some(fee) = fee {
+            assert!(fee <= MARKET_MAX_FEE, "Fee can't be more than {}", MARKET_MAX_FEE);
+            self.fee_by_nft_contract_id.insert(&nft_contract_id, &fee);
+        } else {
+            self.fee_by_nft_contract_id.remove(&nft_contract_id);
+        }
+
+        (MarketNftContractFeeUpdate {
+            nft_contract_id: &nft_contract_id,
+            fee: &fee,
+        }).emit();
+    }
+
+    fn market_set_ft_token_fee(&mut self, ft_token_id: AccountId, fee: Option<u16>) {
+        if let Some(fee) = fee {
+            assert!(fee <= MARKET_MAX_FEE, "Fee can't be more than {}", MARKET_MAX_FEE);
+            self.fee_by_ft_token_id.insert(&ft_token_id, &fee);
+        } else {
+            self.fee_by_ft_token_id.remove(&ft_token_id);
+        }
+
+        (MarketFtTokenFeeUpdate {
+            ft_token_id: &ft_token_id,
+            fee: &fee,
+        }).emit();
+    }
+
+    fn market_treasury_withdraw(&mut self, ft_token_id: AccountId, amount: U128) -> Promise {
+        assert!(amount.0 > 0, "Amount must be greater than 0");
+
+        let receiver_id = self.owner_id.clone();
+
+        // balance is taken before transfer, resolver puts it back on failure
+        self.internal_sub_treasury(&ft_token_id, amount.0);
+
+        self.internal_transfer(&ft_token_id, &receiver_id, amount.0).then(
+            ext_self
+                ::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_TREASURY_WITHDRAW)
+                .market_resolve_treasury_withdraw(ft_token_id, receiver_id, amount)
+        )
+    }
+
+    // #[private]
+    fn market_resolve_treasury_withdraw(
+        &mut self,
+        ft_token_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128
+    ) -> bool {
+        if !is_promise_success() {
+            self.internal_add_treasury(&ft_token_id, amount.0);
+
+            (MarketTreasuryWithdrawFail {
+                ft_token_id: &ft_token_id,
+                receiver_id: &receiver_id,
+                amount: &amount,
+            }).emit();
+
+            return false;
+        }
+
+        (MarketTreasuryWithdraw {
+            ft_token_id: &ft_token_id,
+            receiver_id: &receiver_id,
+            amount: &amount,
+        }).emit();
+
+        true
+    }
+
+    /// views
+
+    fn market_fee_tiers(&self) -> Vec<FeeTier> {
+        self.fee_tiers.clone()
+    }
+
+    fn market_nft_contract_fee(&self, nft_contract_id: AccountId) -> Option<u16> {
+        self.fee_by_nft_contract_id.get(&nft_contract_id)
+    }
+
+    fn market_ft_token_fee(&self, ft_token_id: AccountId) -> Option<u16> {
+        self.fee_by_ft_token_id.get(&ft_token_id)
+    }
+
+    fn market_fee(
+        &self,
+        account_id: AccountId,
+        price: U128,
+        nft_contract_id: Option<AccountId>,
+        ft_token_id: Option<AccountId>
+    ) -> U128 {
+        let fee_percent = self.internal_market_fee_percent(
+            &account_id,
+            nft_contract_id.as_ref(),
+            &ft_token_id.unwrap_or_else(near_ft)
+        );
+
+        U128(price.0 * fee_percent as u128 / 10_000u128)
+    }
+
+    fn market_treasury(&self, ft_token_id: AccountId) -> U128 {
+        U128(self.treasury.get(&ft_token_id).unwrap_or(0))
+    }
+
+    fn market_fees_collected(&self, ft_token_id: AccountId) -> U128 {
+        U128(self.fees_collected.get(&ft_token_id).unwrap_or(0))
+    }
+
+    fn market_fees_collected_by_nft_contract_id(
+        &self,
+        nft_contract_id: AccountId
+    ) -> HashMap<FungibleTokenId, U128> {
+        self.fees_collected_by_nft_contract_id.get(&nft_contract_id).unwrap_or_default()
+    }
+}
@@ -1,12 +1,42 @@
 use crate::market::base::{ GAS_FOR_FT_TRANSFER, MarketFeature };
-use crate::market::metadata::{ TokenId, Bids, MarketOnNftApproveArgs };
+use crate::market::metadata::{ ContractAndTokenId, TokenId, Bids, MarketOnNftApproveArgs, PriceDecay };
 use near_sdk::collections::UnorderedSet;
 use std::collections::HashMap;
-use near_sdk::{AccountId, env, CryptoHash, Promise, BorshStorageKey, PromiseOrValue};
-use crate::market::{ Sale, MarketRemoveSale, MarketCreateSale };
+use near_sdk::{
+    AccountId,
+    Gas,
+    env,
+    ext_contract,
+    CryptoHash,
+    Promise,
+    BorshStorageKey,
+    PromiseOrValue,
+    promise_result_as_success,
+};
+use crate::nft::royalty::Payout;
+use near_sdk::json_types::U128;
+use crate::market::{ Sale, MarketRemoveSale, MarketCreateSale, MarketAuctionStart };
 use near_sdk::borsh::{ self, BorshSerialize };
 use crate::ft::base::external::ext_ft;
+use crate::nft::base::external::ext_nft;
 use crate::utils::{ contract_token_id, hash_account_id, near_ft };
+use crate::metadata::FungibleTokenId;
+
+/// private sale allowlist is meant for a few accounts, e.g. members of a guild
+pub const MAX_SALE_BUYERS: usize = 10;
+
+const GAS_FOR_NFT_TOKEN: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_SALE_TOKEN: Gas = Gas(10_000_000_000_000);
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn market_resolve_sale_token(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: TokenId,
+        approval_id: u64
+    );
+}
 
 #[derive(BorshStorageKey, BorshSerialize)]
 pub enum StorageKey {
@@ -16,6 +46,18 @@ pub enum StorageKey {
     ByNFTContractIdInner {
         account_id_hash: CryptoHash,
     },
+    CollectionOffersByOwnerIdInner {
+        account_id_hash: CryptoHash,
+    },
+    CollectionOffersByNFTContractIdInner {
+        account_id_hash: CryptoHash,
+    },
+    BundlesByOwnerIdInner {
+        account_id_hash: CryptoHash,
+    },
+    PrivateSalesByBuyerIdInner {
+        account_id_hash: CryptoHash,
+    },
 }
 
 impl MarketFeature {
@@ -36,41 +78,286 @@ impl MarketFeature {
         }
     }
 
+    pub(crate) fn internal_transfer(
+        &self,
+        ft_token_id: &FungibleTokenId,
+        receiver_id: &AccountId,
+        amount: u128
+    ) -> Promise {
+        if ft_token_id == &near_ft() {
+            Promise::new(receiver_id.clone()).transfer(amount)
+        } else {
+            ext_ft
+                ::ext(ft_token_id.clone())
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .with_attached_deposit(1)
+                .ft_transfer(receiver_id.clone(), U128(amount), None)
+        }
+    }
+
+    pub(crate) fn assert_sale_active(&self, sale: &Sale) {
+        let now = env::block_timestamp();
+
+        assert!(sale.is_started(now), "Sale is not started");
+        assert!(!sale.is_expired(now), "Sale is expired");
+    }
+
+    pub(crate) fn internal_check_sale_remove(
+        &self,
+        sale: &Sale,
+        account_id: &AccountId
+    ) -> Result<(), String> {
+        if account_id != &sale.owner_id {
+            return Err("Must be sale owner".to_string());
+        }
+        if sale.auction.is_some() && !sale.bids.is_empty() {
+            return Err("Cannot remove auction with bids".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn internal_check_sale_update(
+        &self,
+        sale: &Sale,
+        account_id: &AccountId,
+        ft_token_id: &FungibleTokenId
+    ) -> Result<(), String> {
+        if account_id != &sale.owner_id {
+            return Err("Must be sale owner".to_string());
+        }
+        if sale.dutch_auction.is_some() {
+            return Err("Cannot update price of dutch auction".to_string());
+        }
+        if !self.ft_token_ids.contains(&ft_token_id) {
+            return Err(format!("Token {} not supported by this market", ft_token_id));
+        }
+        if sale.auction.is_some() && !sale.sale_conditions.contains_key(&ft_token_id) {
+            return Err("Auction must be in a single token".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn internal_set_sale_price(
+        &mut self,
+        sale: &mut Sale,
+        ft_token_id: &FungibleTokenId,
+        price: &U128
+    ) {
+        let contract_and_token_id = contract_token_id(&sale.nft_contract_id, &sale.token_id);
+
+        if let Some(old_price) = sale.sale_conditions.insert(ft_token_id.clone(), price.clone()) {
+            self.sales_by_price.remove(
+                &(ft_token_id.clone(), old_price.0, contract_and_token_id.clone())
+            );
+        }
+        self.sales_by_price.insert(&(ft_token_id.clone(), price.0, contract_and_token_id.clone()), &());
+        self.sales.insert(&contract_and_token_id, &sale);
+    }
+
+    pub(crate) fn assert_sale_buyer(&self, sale: &Sale, buyer_id: &AccountId) {
+        assert!(sale.is_buyer_allowed(&buyer_id), "Sale is private");
+    }
+
+    /// current price of the sale in given token, dutch auctions price falls over time
+    pub(crate) fn internal_sale_price(&self, sale: &Sale, ft_token_id: &FungibleTokenId) -> u128 {
+        let price = sale.sale_conditions.get(&ft_token_id).expect("Not for sale in that token type");
+
+        if let Some(dutch_auction) = &sale.dutch_auction {
+            return dutch_auction.current_price(env::block_timestamp());
+        }
+
+        price.0
+    }
+
+    /// payout returned by `nft_transfer_payout`, None means a bad payout from bad NFT contract
+    pub(crate) fn internal_payout_result(&self, price: &U128, refunds_len: usize) -> Option<Payout> {
+        promise_result_as_success().and_then(|value| {
+            self.internal_parse_payout(&value, price, refunds_len)
+        })
+    }
+
+    pub(crate) fn internal_parse_payout(
+        &self,
+        value: &[u8],
+        price: &U128,
+        refunds_len: usize
+    ) -> Option<Payout> {
+        near_sdk::serde_json
+            ::from_slice::<Payout>(&value)
+            .ok()
+            .and_then(|payout| {
+                // gas to do 10 FT transfers (and definitely 10 NEAR transfers)
+                if payout.payout.len() + refunds_len > 10 || payout.payout.is_empty() {
+                    env::log_str(
+                        &format!("Cannot have more than 10 royalties and sale.bids refunds").to_string()
+                    );
+                    None
+                } else {
+                    // TODO off by 1 e.g. payouts are fractions of 3333 + 3333 + 3333
+                    let mut remainder = price.0;
+                    for &value in payout.payout.values() {
+                        remainder = remainder.checked_sub(value.0)?;
+                    }
+
+                    if remainder == 0 || remainder == 1 {
+                        Some(payout)
+                    } else {
+                        None
+                    }
+                }
+            })
+    }
+
     pub(crate) fn internal_remove_sale(
         &mut self,
         nft_contract_id: &AccountId,
         token_id: &TokenId
+    ) -> Sale {
+        let sale = self.internal_take_sale(&nft_contract_id, &token_id);
+
+        (MarketRemoveSale {
+            owner_id: &sale.owner_id,
+            nft_contract_id: &nft_contract_id,
+            token_id: &token_id,
+        }).emit();
+
+        sale
+    }
+
+    /// removes sale with its indexes, events are up to the caller
+    pub(crate) fn internal_take_sale(
+        &mut self,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId
     ) -> Sale {
         let contract_and_token_id = contract_token_id(&nft_contract_id, &token_id);
         let sale = self.sales.remove(&contract_and_token_id).expect("No sale");
 
-        let mut by_owner_id = self.by_owner_id.get(&sale.owner_id).expect("No sale by_owner_id");
-        by_owner_id.remove(&contract_and_token_id);
-        if by_owner_id.is_empty() {
-            self.by_owner_id.remove(&sale.owner_id);
-        } else {
-            self.by_owner_id.insert(&sale.owner_id, &by_owner_id);
+        if let Some(expires_at) = sale.expires_at {
+            self.sales_by_expires_at.remove(&(expires_at, contract_and_token_id.clone()));
+        }
+        if sale.dutch_auction.is_none() {
+            for (ft_token_id, price) in sale.sale_conditions.iter() {
+                self.sales_by_price.remove(
+                    &(ft_token_id.clone(), price.0, contract_and_token_id.clone())
+                );
+            }
         }
+        self.sales_by_created_at.remove(&(sale.created_at, contract_and_token_id.clone()));
 
-        let mut by_nft_contract_id = self.by_nft_contract_id
-            .get(&nft_contract_id)
-            .expect("No sale by nft_contract_id");
-        by_nft_contract_id.remove(&token_id);
-        if by_nft_contract_id.is_empty() {
-            self.by_nft_contract_id.remove(&nft_contract_id);
-        } else {
-            self.by_nft_contract_id.insert(&nft_contract_id, &by_nft_contract_id);
+        for buyer_id in sale.buyers.iter().flatten() {
+            if let Some(mut by_buyer_id) = self.private_sales_by_buyer_id.get(&buyer_id) {
+                by_buyer_id.remove(&contract_and_token_id);
+                if by_buyer_id.is_empty() {
+                    self.private_sales_by_buyer_id.remove(&buyer_id);
+                } else {
+                    self.private_sales_by_buyer_id.insert(&buyer_id, &by_buyer_id);
+                }
+            }
         }
 
-        (MarketRemoveSale {
-            owner_id: &sale.owner_id,
-            nft_contract_id: &nft_contract_id,
-            token_id: &token_id,
-        }).emit();
+        // private sales are not in the owner and contract indexes
+        if sale.is_private() {
+            self.private_sales_supply = self.private_sales_supply.saturating_sub(1);
+        } else {
+            let mut by_owner_id = self.by_owner_id.get(&sale.owner_id).expect("No sale by_owner_id");
+            by_owner_id.remove(&contract_and_token_id);
+            if by_owner_id.is_empty() {
+                self.by_owner_id.remove(&sale.owner_id);
+            } else {
+                self.by_owner_id.insert(&sale.owner_id, &by_owner_id);
+            }
+
+            let mut by_nft_contract_id = self.by_nft_contract_id
+                .get(&nft_contract_id)
+                .expect("No sale by nft_contract_id");
+            by_nft_contract_id.remove(&token_id);
+            if by_nft_contract_id.is_empty() {
+                self.by_nft_contract_id.remove(&nft_contract_id);
+            } else {
+                self.by_nft_contract_id.insert(&nft_contract_id, &by_nft_contract_id);
+            }
+        }
 
         sale
     }
 
+    /// token is on sale or in a bundle
+    pub(crate) fn internal_is_listed(&self, contract_and_token_id: &ContractAndTokenId) -> bool {
+        self.sales.get(&contract_and_token_id).is_some() ||
+            self.bundle_by_token_id.contains_key(&contract_and_token_id)
+    }
+
+    /// stores sale with its indexes, events are up to the caller
+    pub(crate) fn internal_insert_sale(&mut self, sale: &Sale) {
+        let contract_and_token_id = contract_token_id(&sale.nft_contract_id, &sale.token_id);
+
+        self.sales.insert(&contract_and_token_id, &sale);
+
+        if let Some(expires_at) = sale.expires_at {
+            self.sales_by_expires_at.insert(&(expires_at, contract_and_token_id.clone()), &());
+        }
+        // dutch auction price goes down over time, so it can't be kept in the price index
+        if sale.dutch_auction.is_none() {
+            for (ft_token_id, price) in sale.sale_conditions.iter() {
+                self.sales_by_price.insert(
+                    &(ft_token_id.clone(), price.0, contract_and_token_id.clone()),
+                    &()
+                );
+            }
+        }
+        self.sales_by_created_at.insert(&(sale.created_at, contract_and_token_id.clone()), &());
+
+        for buyer_id in sale.buyers.iter().flatten() {
+            let mut by_buyer_id = self.private_sales_by_buyer_id.get(&buyer_id).unwrap_or_else(|| {
+                UnorderedSet::new(
+                    (StorageKey::PrivateSalesByBuyerIdInner {
+                        account_id_hash: hash_account_id(&buyer_id),
+                    })
+                        .try_to_vec()
+                        .unwrap()
+                )
+            });
+            by_buyer_id.insert(&contract_and_token_id);
+            self.private_sales_by_buyer_id.insert(&buyer_id, &by_buyer_id);
+        }
+
+        // extra for views, private sales are listed only for their buyers
+
+        if sale.is_private() {
+            self.private_sales_supply += 1;
+        } else {
+            let mut by_owner_id = self.by_owner_id.get(&sale.owner_id).unwrap_or_else(|| {
+                UnorderedSet::new(
+                    (StorageKey::ByOwnerIdInner {
+                        account_id_hash: hash_account_id(&sale.owner_id),
+                    })
+                        .try_to_vec()
+                        .unwrap()
+                )
+            });
+
+            by_owner_id.insert(&contract_and_token_id);
+            self.by_owner_id.insert(&sale.owner_id, &by_owner_id);
+
+            let mut by_nft_contract_id = self.by_nft_contract_id
+                .get(&sale.nft_contract_id)
+                .unwrap_or_else(|| {
+                    UnorderedSet::new(
+                        (StorageKey::ByNFTContractIdInner {
+                            account_id_hash: hash_account_id(&sale.nft_contract_id),
+                        })
+                            .try_to_vec()
+                            .unwrap()
+                    )
+                });
+            by_nft_contract_id.insert(&sale.token_id);
+            self.by_nft_contract_id.insert(&sale.nft_contract_id, &by_nft_contract_id);
+        }
+    }
+
     pub fn internal_on_nft_approve(
         &mut self,
         args: &MarketOnNftApproveArgs,
@@ -79,7 +366,15 @@ impl MarketFeature {
         owner_id: &AccountId,
         approval_id: &u64
     ) -> PromiseOrValue<String> {
-        let MarketOnNftApproveArgs { is_auction, sale_conditions } = args;
+        let MarketOnNftApproveArgs {
+            is_auction,
+            sale_conditions,
+            auction,
+            dutch_auction,
+            starts_at,
+            expires_at,
+            buyers,
+        } = args;
 
         for (ft_token_id, _price) in sale_conditions.clone() {
             if !self.ft_token_ids.contains(&ft_token_id) {
@@ -89,16 +384,66 @@ impl MarketFeature {
             }
         }
 
-        let bids = HashMap::new();
+        if let Some(auction) = auction {
+            assert_eq!(sale_conditions.len(), 1, "Auction must be in a single token");
+            assert!(auction.end_at > auction.start_at, "Auction end must be after start");
+            assert!(auction.end_at > env::block_timestamp(), "Auction end must be in the future");
+            assert!(
+                auction.extension_time > 0 &&
+                    auction.extension_time <= auction.end_at - auction.start_at,
+                "Auction extension time must be positive and not longer than the auction"
+            );
+        }
 
-        let contract_and_token_id = contract_token_id(nft_contract_id, token_id);
+        if let Some(dutch_auction) = dutch_auction {
+            assert!(
+                auction.is_none() && !is_auction.unwrap_or(false),
+                "Dutch auction can't be combined with bids"
+            );
+            assert_eq!(sale_conditions.len(), 1, "Auction must be in a single token");
+            assert_eq!(
+                sale_conditions.values().next().unwrap(),
+                &dutch_auction.start_price,
+                "Sale price must be equal to auction start price"
+            );
+            assert!(
+                dutch_auction.start_price.0 >= dutch_auction.floor_price.0,
+                "Start price must be greater than floor price"
+            );
+            assert!(
+                dutch_auction.end_at > dutch_auction.start_at,
+                "Auction end must be after start"
+            );
+            if let PriceDecay::Stepwise { step } = dutch_auction.decay {
+                assert!(step > 0, "Step must be greater than 0");
+            }
+        }
 
-        let exists = self.sales.get(&contract_and_token_id);
+        if let Some(expires_at) = expires_at {
+            assert!(auction.is_none(), "Timed auction can't expire, use auction end");
+            assert!(*expires_at > env::block_timestamp(), "Sale expiration must be in the future");
+            assert!(
+                *expires_at > starts_at.unwrap_or(0),
+                "Sale expiration must be after start"
+            );
+        }
 
-        if exists.is_some() {
-            env::panic_str("Token already listed");
+        if let Some(buyers) = buyers {
+            assert!(!buyers.is_empty(), "Private sale must have buyers");
+            assert!(
+                buyers.len() <= MAX_SALE_BUYERS,
+                "Private sale can't have more than {} buyers",
+                MAX_SALE_BUYERS
+            );
+            assert!(!buyers.contains(owner_id), "Cannot sell to yourself");
         }
 
+        let bids = HashMap::new();
+
+        let contract_and_token_id = contract_token_id(nft_contract_id, token_id);
+
+        assert!(!self.internal_is_listed(&contract_and_token_id), "Token already listed");
+
         let sale = Sale {
             owner_id: owner_id.clone().into(),
             approval_id: approval_id.clone(),
@@ -107,38 +452,32 @@ impl MarketFeature {
             sale_conditions: sale_conditions.clone(),
             bids,
             created_at: env::block_timestamp(),
-            is_auction: is_auction.unwrap_or(false),
+            is_auction: auction.is_some() || is_auction.unwrap_or(false),
+            auction: auction.clone(),
+            dutch_auction: dutch_auction.clone(),
+            starts_at: starts_at.clone(),
+            expires_at: expires_at.clone(),
+            types: None,
+            rarity: None,
+            buyers: buyers.clone(),
         };
-        self.sales.insert(&contract_and_token_id, &sale);
-
-        // extra for views
+        self.internal_insert_sale(&sale);
 
-        let mut by_owner_id = self.by_owner_id.get(&owner_id).unwrap_or_else(|| {
-            UnorderedSet::new(
-                (StorageKey::ByOwnerIdInner {
-                    account_id_hash: hash_account_id(&owner_id),
-                })
-                    .try_to_vec()
-                    .unwrap()
-            )
-        });
-
-        by_owner_id.insert(&contract_and_token_id);
-        self.by_owner_id.insert(&owner_id, &by_owner_id);
-
-        let mut by_nft_contract_id = self.by_nft_contract_id
-            .get(&nft_contract_id)
-            .unwrap_or_else(|| {
-                UnorderedSet::new(
-                    (StorageKey::ByNFTContractIdInner {
-                        account_id_hash: hash_account_id(&nft_contract_id),
-                    })
-                        .try_to_vec()
-                        .unwrap()
-                )
-            });
-        by_nft_contract_id.insert(&token_id);
-        self.by_nft_contract_id.insert(&nft_contract_id, &by_nft_contract_id);
+        // token types and rarity are stored on the sale for filtering
+        ext_nft
+            ::ext(nft_contract_id.clone())
+            .with_static_gas(GAS_FOR_NFT_TOKEN)
+            .nft_token(token_id.clone())
+            .then(
+                ext_self
+                    ::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_SALE_TOKEN)
+                    .market_resolve_sale_token(
+                        nft_contract_id.clone(),
+                        token_id.clone(),
+                        approval_id.clone()
+                    )
+            );
 
         (MarketCreateSale {
             owner_id: &owner_id,
@@ -147,6 +486,16 @@ impl MarketFeature {
             sale: &sale,
         }).emit();
 
+        if let Some(auction) = &sale.auction {
+            (MarketAuctionStart {
+                owner_id: &owner_id,
+                nft_contract_id: &nft_contract_id,
+                token_id: &token_id,
+                ft_token_id: sale.sale_conditions.keys().next().unwrap(),
+                auction: &auction,
+            }).emit();
+        }
+
         PromiseOrValue::Value("true".to_string())
     }    
 }
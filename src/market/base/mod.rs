@@ -2,11 +2,36 @@ mod core_impl;
 mod internal;
 mod receivers;
 mod fee;
+mod collection_offer;
+mod bundle;
+mod batch;
 
 pub use self::core_impl::*;
-use near_sdk::{ AccountId, Promise, Balance };
+pub use self::bundle::MAX_BUNDLE_SIZE;
+pub use self::batch::{ MAX_SALES_BATCH, MAX_LISTINGS_BATCH };
+pub use self::internal::MAX_SALE_BUYERS;
+pub use self::fee::{ MARKET_MAX_FEE, default_fee_tiers };
+use std::collections::HashMap;
+use near_sdk::{ AccountId, Promise, Balance, PromiseOrValue };
 use near_sdk::json_types::{ U128 };
-use crate::market::{ Sale, ContractAndTokenId };
+use crate::market::{
+    Sale,
+    ContractAndTokenId,
+    CollectionOffer,
+    CollectionOfferId,
+    TokenId,
+    Bundle,
+    BundleId,
+    BundleItem,
+    SaleConditions,
+    FeeTier,
+    SaleItem,
+    SaleListing,
+    SaleUpdate,
+    SaleBatchFailure,
+};
+use crate::metadata::FungibleTokenId;
+use crate::nft::{ TokenTypes, TokenRarity };
 
 pub trait MarketCore {
     fn market_remove_sale(&mut self, nft_contract_id: AccountId, token_id: String);
@@ -40,6 +65,19 @@ pub trait MarketCore {
         ft_token_id: AccountId
     );
 
+    fn market_auction_settle(&mut self, nft_contract_id: AccountId, token_id: String);
+
+    // #[private]
+    fn market_resolve_sale_token(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: TokenId,
+        approval_id: u64
+    );
+
+    /// anyone can remove up to `limit` expired sales, returns number of removed sales
+    fn market_remove_expired_sales(&mut self, limit: u64) -> u64;
+
     // #[private]
     fn market_process_purchase(
         &mut self,
@@ -56,10 +94,156 @@ pub trait MarketCore {
         ft_token_id: AccountId,
         buyer_id: AccountId,
         sale: Sale,
-        price: U128
+        price: U128,
+        fee: U128
+    ) -> U128;
+
+    // #[private]
+    fn market_resolve_auction_settle(
+        &mut self,
+        ft_token_id: AccountId,
+        buyer_id: AccountId,
+        sale: Sale,
+        price: U128,
+        fee: U128
     ) -> U128;
 
    fn market_add_ft_token(&mut self, nft_contract_id: AccountId) -> bool;
 
    fn supported_ft_token_ids(&self) -> Vec<AccountId>;
 }
+
+pub trait MarketCollectionOffers {
+    // #[payable]
+    fn market_collection_offer_create(
+        &mut self,
+        nft_contract_id: AccountId,
+        types: Option<TokenTypes>,
+        rarity: Option<TokenRarity>,
+        expires_at: Option<u64>
+    ) -> CollectionOffer;
+
+    fn market_collection_offer_remove(&mut self, offer_id: CollectionOfferId);
+
+    // #[private]
+    fn market_resolve_collection_offer_token(
+        &mut self,
+        offer: CollectionOffer,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: u64
+    ) -> PromiseOrValue<bool>;
+
+    // #[private]
+    fn market_resolve_collection_offer(
+        &mut self,
+        offer: CollectionOffer,
+        token_id: TokenId,
+        owner_id: AccountId,
+        price: U128
+    ) -> bool;
+}
+
+pub trait MarketBundles {
+    fn market_bundle_create(
+        &mut self,
+        items: Vec<BundleItem>,
+        sale_conditions: SaleConditions
+    ) -> Promise;
+
+    fn market_bundle_remove(&mut self, bundle_id: BundleId);
+
+    // #[payable]
+    fn market_bundle_offer(&mut self, bundle_id: BundleId) -> Promise;
+
+    /// token of a bundle left on market by a failed transfer, only its receiver
+    fn market_bundle_claim(&mut self, nft_contract_id: AccountId, token_id: TokenId) -> Promise;
+
+    // #[private]
+    fn market_resolve_bundle_create(
+        &mut self,
+        owner_id: AccountId,
+        items: Vec<BundleItem>,
+        sale_conditions: SaleConditions
+    ) -> Bundle;
+
+    // #[private]
+    fn market_resolve_bundle_purchase(
+        &mut self,
+        ft_token_id: FungibleTokenId,
+        buyer_id: AccountId,
+        bundle: Bundle,
+        price: U128
+    ) -> U128;
+
+    // #[private]
+    fn market_resolve_bundle_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        tokens: Vec<(AccountId, TokenId)>
+    );
+}
+
+pub trait MarketFees {
+    /// only owner
+    fn market_set_fee_tiers(&mut self, fee_tiers: Vec<FeeTier>);
+
+    /// only owner, None removes the override
+    fn market_set_nft_contract_fee(&mut self, nft_contract_id: AccountId, fee: Option<u16>);
+
+    /// only owner, None removes the override
+    fn market_set_ft_token_fee(&mut self, ft_token_id: AccountId, fee: Option<u16>);
+
+    /// only owner
+    fn market_treasury_withdraw(&mut self, ft_token_id: AccountId, amount: U128) -> Promise;
+
+    // #[private]
+    fn market_resolve_treasury_withdraw(
+        &mut self,
+        ft_token_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128
+    ) -> bool;
+
+    fn market_fee_tiers(&self) -> Vec<FeeTier>;
+
+    fn market_nft_contract_fee(&self, nft_contract_id: AccountId) -> Option<u16>;
+
+    fn market_ft_token_fee(&self, ft_token_id: AccountId) -> Option<u16>;
+
+    /// fee `account_id` would pay on `price`
+    fn market_fee(
+        &self,
+        account_id: AccountId,
+        price: U128,
+        nft_contract_id: Option<AccountId>,
+        ft_token_id: Option<AccountId>
+    ) -> U128;
+
+    fn market_treasury(&self, ft_token_id: AccountId) -> U128;
+
+    fn market_fees_collected(&self, ft_token_id: AccountId) -> U128;
+
+    fn market_fees_collected_by_nft_contract_id(
+        &self,
+        nft_contract_id: AccountId
+    ) -> HashMap<FungibleTokenId, U128>;
+}
+
+pub trait MarketBatch {
+    /// lists tokens already approved for market at fixed prices
+    fn market_list_batch(&mut self, listings: Vec<SaleListing>) -> Promise;
+
+    /// removes sales of predecessor, returns skipped items
+    fn market_remove_batch(&mut self, items: Vec<SaleItem>) -> Vec<SaleBatchFailure>;
+
+    /// sets prices of predecessor sales, returns skipped items
+    fn market_update_batch(&mut self, updates: Vec<SaleUpdate>) -> Vec<SaleBatchFailure>;
+
+    // #[private]
+    fn market_resolve_list_batch(
+        &mut self,
+        owner_id: AccountId,
+        listings: Vec<SaleListing>
+    ) -> Vec<SaleBatchFailure>;
+}
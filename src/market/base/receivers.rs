@@ -1,8 +1,15 @@
 use crate::market::base::MarketFeature;
-use crate::market::{ MarketOnFtTransferArgs, MarketCore };
+use crate::market::{
+    MarketOnFtTransferArgs,
+    MarketOnFtTransferCollectionOfferArgs,
+    MarketOnNftApproveCollectionOfferArgs,
+    MarketOnFtTransferBundleArgs,
+    MarketCore,
+    TokenId,
+};
 use crate::utils::contract_token_id;
 use crate::metadata::{ FungibleTokenId };
-use near_sdk::{ PromiseOrValue, AccountId };
+use near_sdk::{ PromiseOrValue, AccountId, env };
 use near_sdk::json_types::{ U128 };
 
 impl MarketFeature {
@@ -19,18 +26,30 @@ impl MarketFeature {
         let mut sale = self.sales.get(&contract_and_token_id).expect("No sale in ft_on_transfer");
 
         assert_ne!(&sale.owner_id, sender_id, "Cannot buy your own sale.");
+        self.assert_sale_active(&sale);
+        self.assert_sale_buyer(&sale, &sender_id);
 
-        let price = *sale.sale_conditions
-            .get(&ft_token_id)
-            .expect("Not for sale in that token type");
+        let price = U128(self.internal_sale_price(&sale, &ft_token_id));
 
+        // fee is taken from the price in market_process_purchase, same as for NEAR
         assert!(amount.0 > 0, "Amount must be greater than 0");
-        
-        let fee = self.internal_market_fee(&price.0, &sender_id);
-        
-        let amount = &U128(amount.0 - fee);
 
-        if !sale.is_auction && amount == &price {
+        if let Some(dutch_auction) = &sale.dutch_auction {
+            assert!(env::block_timestamp() >= dutch_auction.start_at, "Auction is not started");
+            assert!(amount.0 >= price.0, "Amount must be greater than current price");
+
+            if amount.0 > price.0 {
+                self.internal_transfer(&ft_token_id, &sender_id, amount.0 - price.0);
+            }
+
+            self.market_process_purchase(
+                nft_contract_id.clone(),
+                token_id.clone(),
+                ft_token_id.clone(),
+                price,
+                sender_id.clone()
+            ).into()
+        } else if !sale.is_auction && amount == &price {
             self.market_process_purchase(
                 nft_contract_id.clone(),
                 token_id.clone(),
@@ -53,4 +72,57 @@ impl MarketFeature {
             PromiseOrValue::Value(U128(0))
         }
     }
+
+    pub fn internal_on_ft_transfer_collection_offer(
+        &mut self,
+        args: &MarketOnFtTransferCollectionOfferArgs,
+        ft_token_id: &FungibleTokenId,
+        amount: &U128,
+        sender_id: &AccountId
+    ) -> PromiseOrValue<U128> {
+        let MarketOnFtTransferCollectionOfferArgs { nft_contract_id, types, rarity, expires_at } = args;
+
+        self.internal_create_collection_offer(
+            &sender_id,
+            &nft_contract_id,
+            &ft_token_id,
+            &amount,
+            &types,
+            &rarity,
+            &expires_at
+        );
+
+        PromiseOrValue::Value(U128(0))
+    }
+
+    pub fn internal_on_nft_approve_collection_offer(
+        &mut self,
+        args: &MarketOnNftApproveCollectionOfferArgs,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        approval_id: &u64
+    ) -> PromiseOrValue<String> {
+        let MarketOnNftApproveCollectionOfferArgs { offer_id } = args;
+
+        self.internal_accept_collection_offer(
+            &offer_id,
+            &nft_contract_id,
+            &token_id,
+            &owner_id,
+            &approval_id
+        ).into()
+    }
+
+    pub fn internal_on_ft_transfer_bundle(
+        &mut self,
+        args: &MarketOnFtTransferBundleArgs,
+        ft_token_id: &FungibleTokenId,
+        amount: &U128,
+        sender_id: &AccountId
+    ) -> PromiseOrValue<U128> {
+        let MarketOnFtTransferBundleArgs { bundle_id } = args;
+
+        self.internal_bundle_purchase(&bundle_id, &ft_token_id, &amount, &sender_id).into()
+    }
 }
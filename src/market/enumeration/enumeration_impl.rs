@@ -1,13 +1,24 @@
 use crate::market::base::{ MarketFeature };
 use near_sdk::{ AccountId };
-use crate::market::{ Sale, TokenId, MarketEnumeration };
-use std::cmp::min;
+use crate::market::{
+    Sale,
+    TokenId,
+    MarketEnumeration,
+    CollectionOffer,
+    CollectionOfferId,
+    Bundle,
+    BundleId,
+    SaleFilter,
+    SaleSort,
+    SaleCursor,
+    SalesPage,
+};
 use near_sdk::json_types::U64;
 use crate::utils::contract_token_id;
 
 impl MarketEnumeration for MarketFeature {
     fn market_supply_sales(&self) -> U64 {
-        U64(self.sales.len())
+        U64(self.sales.len() - self.private_sales_supply)
     }
 
     fn market_supply_by_owner_id(&self, account_id: AccountId) -> U64 {
@@ -23,22 +34,17 @@ impl MarketEnumeration for MarketFeature {
         &self,
         account_id: AccountId,
         from_index: U64,
-        limit: u64
+        limit: u64,
+        hide_expired: Option<bool>
     ) -> Vec<Sale> {
-        let mut tmp = vec![];
         let by_owner_id = self.by_owner_id.get(&account_id);
         let sales = if let Some(by_owner_id) = by_owner_id {
             by_owner_id
         } else {
             return vec![];
         };
-        let keys = sales.as_vector();
-        let start = u64::from(from_index);
-        let end = min(start + limit, sales.len());
-        for i in start..end {
-            tmp.push(self.sales.get(&keys.get(i).unwrap()).unwrap());
-        }
-        tmp
+
+        self.enum_sales(sales.iter(), from_index, limit, hide_expired.unwrap_or(false))
     }
 
     fn market_supply_by_nft_contract_id(&self, nft_contract_id: AccountId) -> U64 {
@@ -54,26 +60,175 @@ impl MarketEnumeration for MarketFeature {
         &self,
         nft_contract_id: AccountId,
         from_index: U64,
-        limit: u64
+        limit: u64,
+        hide_expired: Option<bool>
     ) -> Vec<Sale> {
-        let mut tmp = vec![];
         let by_nft_contract_id = self.by_nft_contract_id.get(&nft_contract_id);
         let sales = if let Some(by_nft_contract_id) = by_nft_contract_id {
             by_nft_contract_id
         } else {
             return vec![];
         };
-        let keys = sales.as_vector();
-        let start = u64::from(from_index);
-        let end = min(start + limit, sales.len());
-        for i in start..end {
-            let id = contract_token_id(&nft_contract_id, &keys.get(i).unwrap());
-            tmp.push(self.sales.get(&id).unwrap());
-        }
-        tmp
+
+        self.enum_sales(
+            sales.iter().map(|token_id| contract_token_id(&nft_contract_id, &token_id)),
+            from_index,
+            limit,
+            hide_expired.unwrap_or(false)
+        )
     }
 
     fn market_sale(&self, contract_id: AccountId, token_id: TokenId) -> Option<Sale> {
-        self.sales.get(&format!("{}{}{}", contract_id, "||".to_string(), token_id))
+        self.enum_sale(&contract_token_id(&contract_id, &token_id))
+    }
+
+    fn market_supply_private_sales_by_buyer_id(&self, account_id: AccountId) -> U64 {
+        let by_buyer_id = self.private_sales_by_buyer_id.get(&account_id);
+        if let Some(by_buyer_id) = by_buyer_id {
+            U64(by_buyer_id.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    fn market_private_sales_by_buyer_id(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: u64,
+        hide_expired: Option<bool>
+    ) -> Vec<Sale> {
+        let by_buyer_id = self.private_sales_by_buyer_id.get(&account_id);
+        let sales = if let Some(by_buyer_id) = by_buyer_id {
+            by_buyer_id
+        } else {
+            return vec![];
+        };
+
+        self.enum_sales(sales.iter(), from_index, limit, hide_expired.unwrap_or(false))
+    }
+
+    fn market_sales(
+        &self,
+        filter: Option<SaleFilter>,
+        sort: Option<SaleSort>,
+        cursor: Option<SaleCursor>,
+        limit: u64
+    ) -> SalesPage {
+        self.enum_sales_page(
+            filter.unwrap_or_default(),
+            sort.unwrap_or(SaleSort::CreatedAtDesc),
+            cursor,
+            limit
+        )
+    }
+
+    fn market_collection_offer(&self, offer_id: CollectionOfferId) -> Option<CollectionOffer> {
+        self.collection_offers.get(&offer_id)
+    }
+
+    fn market_supply_collection_offers_by_owner_id(&self, account_id: AccountId) -> U64 {
+        let by_owner_id = self.collection_offers_by_owner_id.get(&account_id);
+        if let Some(by_owner_id) = by_owner_id {
+            U64(by_owner_id.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    fn market_collection_offers_by_owner_id(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: u64
+    ) -> Vec<CollectionOffer> {
+        let offers = if let Some(offers) = self.collection_offers_by_owner_id.get(&account_id) {
+            offers
+        } else {
+            return vec![];
+        };
+
+        offers
+            .iter()
+            .skip(from_index.0 as usize)
+            .take(limit as usize)
+            .map(|offer_id| self.collection_offers.get(&offer_id).unwrap())
+            .collect()
+    }
+
+    fn market_supply_collection_offers_by_nft_contract_id(&self, nft_contract_id: AccountId) -> U64 {
+        let by_nft_contract_id = self.collection_offers_by_nft_contract_id.get(&nft_contract_id);
+        if let Some(by_nft_contract_id) = by_nft_contract_id {
+            U64(by_nft_contract_id.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    fn market_collection_offers_by_nft_contract_id(
+        &self,
+        nft_contract_id: AccountId,
+        from_index: U64,
+        limit: u64
+    ) -> Vec<CollectionOffer> {
+        let offers = if
+            let Some(offers) = self.collection_offers_by_nft_contract_id.get(&nft_contract_id)
+        {
+            offers
+        } else {
+            return vec![];
+        };
+
+        offers
+            .iter()
+            .skip(from_index.0 as usize)
+            .take(limit as usize)
+            .map(|offer_id| self.collection_offers.get(&offer_id).unwrap())
+            .collect()
+    }
+
+    fn market_bundle(&self, bundle_id: BundleId) -> Option<Bundle> {
+        self.bundles.get(&bundle_id)
+    }
+
+    fn market_supply_bundles(&self) -> U64 {
+        U64(self.bundles.len())
+    }
+
+    fn market_bundles(&self, from_index: U64, limit: u64) -> Vec<Bundle> {
+        self.bundles
+            .values()
+            .skip(from_index.0 as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    fn market_supply_bundles_by_owner_id(&self, account_id: AccountId) -> U64 {
+        let by_owner_id = self.bundles_by_owner_id.get(&account_id);
+        if let Some(by_owner_id) = by_owner_id {
+            U64(by_owner_id.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    fn market_bundles_by_owner_id(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: u64
+    ) -> Vec<Bundle> {
+        let bundles = if let Some(bundles) = self.bundles_by_owner_id.get(&account_id) {
+            bundles
+        } else {
+            return vec![];
+        };
+
+        bundles
+            .iter()
+            .skip(from_index.0 as usize)
+            .take(limit as usize)
+            .map(|bundle_id| self.bundles.get(&bundle_id).unwrap())
+            .collect()
     }
 }
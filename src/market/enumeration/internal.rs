@@ -0,0 +1,145 @@
+use crate::market::base::MarketFeature;
+use crate::market::{ Sale, ContractAndTokenId, SaleFilter, SaleSort, SaleCursor, SalesPage };
+use near_sdk::json_types::{ U128, U64 };
+use near_sdk::env;
+
+/// max index entries read by a single `market_sales` call, a page can be shorter
+/// than `limit` when filters skip many sales, `next_cursor` continues from there
+pub const MAX_SALES_SCAN: u64 = 500;
+
+impl MarketFeature {
+    /// sale for views, dutch auction reports its current price
+    pub(crate) fn enum_sale(&self, contract_and_token_id: &ContractAndTokenId) -> Option<Sale> {
+        let mut sale = self.sales.get(&contract_and_token_id)?;
+
+        if let Some(dutch_auction) = &sale.dutch_auction {
+            let price = U128(dutch_auction.current_price(env::block_timestamp()));
+
+            for value in sale.sale_conditions.values_mut() {
+                *value = price;
+            }
+        }
+
+        Some(sale)
+    }
+
+    /// page of sales, `from_index` and `limit` are applied to the index, so only the page
+    /// is loaded, expired ones are left out of it when `hide_expired` is set and the page
+    /// can be shorter than `limit`
+    pub(crate) fn enum_sales<I: Iterator<Item = ContractAndTokenId>>(
+        &self,
+        ids: I,
+        from_index: U64,
+        limit: u64,
+        hide_expired: bool
+    ) -> Vec<Sale> {
+        let now = env::block_timestamp();
+
+        ids.skip(from_index.0 as usize)
+            .take(limit as usize)
+            .filter_map(|id| self.enum_sale(&id))
+            .filter(|sale| !hide_expired || !sale.is_expired(now))
+            .collect()
+    }
+
+    /// sales in index order as (price or created_at, contract_and_token_id)
+    fn enum_sales_keys(
+        &self,
+        filter: &SaleFilter,
+        sort: &SaleSort,
+        cursor: Option<SaleCursor>
+    ) -> Box<dyn Iterator<Item = (u128, ContractAndTokenId)> + '_> {
+        match sort {
+            SaleSort::PriceAsc | SaleSort::PriceDesc => {
+                let ft_token_id = filter.ft_token_id
+                    .clone()
+                    .expect("Sorting by price requires ft_token_id");
+                let min_price = filter.min_price.map(|price| price.0).unwrap_or(0);
+                let max_price = filter.max_price.map(|price| price.0).unwrap_or(u128::MAX);
+                let cursor = cursor.map(|cursor| {
+                    (ft_token_id.clone(), cursor.key.0, cursor.contract_and_token_id)
+                });
+
+                let keys: Box<dyn Iterator<Item = _>> = if sort == &SaleSort::PriceAsc {
+                    // contract_and_token_id is never empty, so start is effectively inclusive
+                    let start = cursor.unwrap_or((ft_token_id.clone(), min_price, String::new()));
+                    Box::new(self.sales_by_price.iter_from(start))
+                } else {
+                    let start = cursor.unwrap_or((
+                        ft_token_id.clone(),
+                        max_price,
+                        char::MAX.to_string(),
+                    ));
+                    Box::new(self.sales_by_price.iter_rev_from(start))
+                };
+
+                Box::new(
+                    keys
+                        .map(|(key, _)| key)
+                        .take_while(move |(id, price, _)| {
+                            id == &ft_token_id && *price >= min_price && *price <= max_price
+                        })
+                        .map(|(_, price, contract_and_token_id)| (price, contract_and_token_id))
+                )
+            }
+            SaleSort::CreatedAtAsc | SaleSort::CreatedAtDesc => {
+                let cursor = cursor.map(|cursor| (cursor.key.0 as u64, cursor.contract_and_token_id));
+
+                let keys: Box<dyn Iterator<Item = _>> = match (sort, cursor) {
+                    (SaleSort::CreatedAtAsc, Some(cursor)) => {
+                        Box::new(self.sales_by_created_at.iter_from(cursor))
+                    }
+                    (SaleSort::CreatedAtAsc, None) => Box::new(self.sales_by_created_at.iter()),
+                    (_, Some(cursor)) => Box::new(self.sales_by_created_at.iter_rev_from(cursor)),
+                    (_, None) => Box::new(self.sales_by_created_at.iter_rev()),
+                };
+
+                Box::new(
+                    keys.map(|((created_at, contract_and_token_id), _)| {
+                        (created_at as u128, contract_and_token_id)
+                    })
+                )
+            }
+        }
+    }
+
+    pub(crate) fn enum_sales_page(
+        &self,
+        filter: SaleFilter,
+        sort: SaleSort,
+        cursor: Option<SaleCursor>,
+        limit: u64
+    ) -> SalesPage {
+        let now = env::block_timestamp();
+        let mut keys = self.enum_sales_keys(&filter, &sort, cursor).peekable();
+
+        let mut sales = vec![];
+        let mut next_cursor = None;
+        let mut scanned = 0;
+
+        while (sales.len() as u64) < limit && scanned < MAX_SALES_SCAN {
+            let (key, contract_and_token_id) = if let Some(next) = keys.next() {
+                next
+            } else {
+                break;
+            };
+            scanned += 1;
+
+            if let Some(sale) = self.enum_sale(&contract_and_token_id) {
+                if !sale.is_private() && filter.matches(&sale, now) {
+                    sales.push(sale);
+                }
+            }
+
+            next_cursor = Some(SaleCursor {
+                key: U128(key),
+                contract_and_token_id,
+            });
+        }
+
+        SalesPage {
+            sales,
+            next_cursor: if keys.peek().is_some() { next_cursor } else { None },
+        }
+    }
+}
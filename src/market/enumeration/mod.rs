@@ -1,29 +1,107 @@
 mod enumeration_impl;
+mod internal;
 
 use near_sdk::json_types::{ U64 };
 use near_sdk::AccountId;
-use crate::market::{ Sale, TokenId };
+use crate::market::{
+    Sale,
+    TokenId,
+    CollectionOffer,
+    CollectionOfferId,
+    Bundle,
+    BundleId,
+    SaleFilter,
+    SaleSort,
+    SaleCursor,
+    SalesPage,
+};
+
+pub use self::internal::MAX_SALES_SCAN;
 
 pub trait MarketEnumeration {
+    /// private sales are left out of supply views and of owner and contract listings
     fn market_supply_sales(&self) -> U64;
 
     fn market_supply_by_owner_id(&self, account_id: AccountId) -> U64;
 
+    /// `hide_expired` skips sales past `expires_at` that are not cleaned up yet,
+    /// private sales are not listed, skipped sales are counted by `from_index` and `limit`
     fn market_sales_by_owner_id(
         &self,
         account_id: AccountId,
         from_index: U64,
-        limit: u64
+        limit: u64,
+        hide_expired: Option<bool>
     ) -> Vec<Sale>;
 
     fn market_supply_by_nft_contract_id(&self, nft_contract_id: AccountId) -> U64;
 
+    /// `hide_expired` skips sales past `expires_at` that are not cleaned up yet,
+    /// private sales are not listed, skipped sales are counted by `from_index` and `limit`
     fn market_sales_by_nft_contract_id(
         &self,
         nft_contract_id: AccountId,
         from_index: U64,
-        limit: u64
+        limit: u64,
+        hide_expired: Option<bool>
     ) -> Vec<Sale>;
 
     fn market_sale(&self, contract_id: AccountId, token_id: TokenId) -> Option<Sale>;
-}
\ No newline at end of file
+
+    fn market_supply_private_sales_by_buyer_id(&self, account_id: AccountId) -> U64;
+
+    /// private sales `account_id` is allowed to buy
+    fn market_private_sales_by_buyer_id(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: u64,
+        hide_expired: Option<bool>
+    ) -> Vec<Sale>;
+
+    /// public sales matching `filter` sorted by `sort` (newest first by default),
+    /// pass `next_cursor` of the previous page to continue, dutch auctions are left out
+    /// of sorting by price, sorting by creation filters them by their current price
+    fn market_sales(
+        &self,
+        filter: Option<SaleFilter>,
+        sort: Option<SaleSort>,
+        cursor: Option<SaleCursor>,
+        limit: u64
+    ) -> SalesPage;
+
+    fn market_collection_offer(&self, offer_id: CollectionOfferId) -> Option<CollectionOffer>;
+
+    fn market_supply_collection_offers_by_owner_id(&self, account_id: AccountId) -> U64;
+
+    fn market_collection_offers_by_owner_id(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: u64
+    ) -> Vec<CollectionOffer>;
+
+    fn market_supply_collection_offers_by_nft_contract_id(&self, nft_contract_id: AccountId) -> U64;
+
+    fn market_collection_offers_by_nft_contract_id(
+        &self,
+        nft_contract_id: AccountId,
+        from_index: U64,
+        limit: u64
+    ) -> Vec<CollectionOffer>;
+
+    fn market_bundle(&self, bundle_id: BundleId) -> Option<Bundle>;
+
+    fn market_supply_bundles(&self) -> U64;
+
+    fn market_bundles(&self, from_index: U64, limit: u64) -> Vec<Bundle>;
+
+    fn market_supply_bundles_by_owner_id(&self, account_id: AccountId) -> U64;
+
+    fn market_bundles_by_owner_id(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: u64
+    ) -> Vec<Bundle>;
+}
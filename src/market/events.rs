@@ -2,7 +2,17 @@ use crate::event::NearEvent;
 use near_sdk::AccountId;
 use serde::Serialize;
 use near_sdk::json_types::U128;
-use crate::market::{ TokenId, Sale };
+use crate::market::{
+    TokenId,
+    Sale,
+    Auction,
+    CollectionOffer,
+    CollectionOfferId,
+    Bundle,
+    BundleId,
+    BundleItem,
+    FeeTier,
+};
 use crate::nft::royalty::Payout;
 use std::collections::HashMap;
 
@@ -111,6 +121,351 @@ impl MarketOffer<'_> {
 
 //
 
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketAuctionStart<'a> {
+    pub owner_id: &'a AccountId,
+    pub nft_contract_id: &'a AccountId,
+    pub token_id: &'a TokenId,
+    pub ft_token_id: &'a AccountId,
+    pub auction: &'a Auction,
+}
+
+impl MarketAuctionStart<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an auction start event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketAuctionStart`] represents the data of each auction.
+    pub fn emit_many(data: &[MarketAuctionStart<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketAuctionStart(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketAuctionExtend<'a> {
+    pub nft_contract_id: &'a AccountId,
+    pub token_id: &'a TokenId,
+    pub end_at: &'a u64,
+}
+
+impl MarketAuctionExtend<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an auction extend event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketAuctionExtend`] represents the data of each extension.
+    pub fn emit_many(data: &[MarketAuctionExtend<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketAuctionExtend(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketAuctionSettle<'a> {
+    pub owner_id: &'a AccountId,
+    pub nft_contract_id: &'a AccountId,
+    pub token_id: &'a TokenId,
+    pub ft_token_id: &'a AccountId,
+    pub winner_id: Option<&'a AccountId>,
+    pub price: Option<&'a U128>,
+}
+
+impl MarketAuctionSettle<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an auction settle event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketAuctionSettle`] represents the data of each settlement.
+    pub fn emit_many(data: &[MarketAuctionSettle<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketAuctionSettle(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketCollectionOfferCreate<'a> {
+    pub owner_id: &'a AccountId,
+    pub nft_contract_id: &'a AccountId,
+    pub offer: &'a CollectionOffer,
+}
+
+impl MarketCollectionOfferCreate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a collection offer create event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketCollectionOfferCreate`] represents the data of each offer.
+    pub fn emit_many(data: &[MarketCollectionOfferCreate<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketCollectionOfferCreate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketCollectionOfferRemove<'a> {
+    pub owner_id: &'a AccountId,
+    pub nft_contract_id: &'a AccountId,
+    pub offer_id: &'a CollectionOfferId,
+}
+
+impl MarketCollectionOfferRemove<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a collection offer remove event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketCollectionOfferRemove`] represents the data of each offer.
+    pub fn emit_many(data: &[MarketCollectionOfferRemove<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketCollectionOfferRemove(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketCollectionOfferAccept<'a> {
+    pub offer_id: &'a CollectionOfferId,
+    pub owner_id: &'a AccountId,
+
+    pub receiver_id: &'a AccountId,
+    pub nft_contract_id: &'a AccountId,
+    pub token_id: &'a TokenId,
+    pub payout: &'a HashMap<AccountId, U128>,
+    pub ft_token_id: &'a AccountId,
+    pub price: &'a U128,
+}
+
+impl MarketCollectionOfferAccept<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a collection offer accept event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketCollectionOfferAccept`] represents the data of each purchase.
+    pub fn emit_many(data: &[MarketCollectionOfferAccept<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketCollectionOfferAccept(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketBundleCreate<'a> {
+    pub owner_id: &'a AccountId,
+    pub bundle: &'a Bundle,
+}
+
+impl MarketBundleCreate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a bundle create event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketBundleCreate`] represents the data of each create.
+    pub fn emit_many(data: &[MarketBundleCreate<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketBundleCreate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketBundleRemove<'a> {
+    pub owner_id: &'a AccountId,
+    pub bundle_id: &'a BundleId,
+}
+
+impl MarketBundleRemove<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a bundle remove event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketBundleRemove`] represents the data of each remove.
+    pub fn emit_many(data: &[MarketBundleRemove<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketBundleRemove(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketBundleOffer<'a> {
+    pub owner_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub bundle_id: &'a BundleId,
+    pub items: &'a [BundleItem],
+    pub payout: &'a HashMap<AccountId, U128>,
+    pub ft_token_id: &'a AccountId,
+    pub price: &'a U128,
+}
+
+impl MarketBundleOffer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a bundle purchase event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketBundleOffer`] represents the data of each purchase.
+    pub fn emit_many(data: &[MarketBundleOffer<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketBundleOffer(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketFeeTiersUpdate<'a> {
+    pub fee_tiers: &'a [FeeTier],
+}
+
+impl MarketFeeTiersUpdate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a fee tiers update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketFeeTiersUpdate`] represents the data of each update.
+    pub fn emit_many(data: &[MarketFeeTiersUpdate<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketFeeTiersUpdate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketNftContractFeeUpdate<'a> {
+    pub nft_contract_id: &'a AccountId,
+    pub fee: &'a Option<u16>,
+}
+
+impl MarketNftContractFeeUpdate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an nft contract fee update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketNftContractFeeUpdate`] represents the data of each update.
+    pub fn emit_many(data: &[MarketNftContractFeeUpdate<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketNftContractFeeUpdate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketFtTokenFeeUpdate<'a> {
+    pub ft_token_id: &'a AccountId,
+    pub fee: &'a Option<u16>,
+}
+
+impl MarketFtTokenFeeUpdate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an ft token fee update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketFtTokenFeeUpdate`] represents the data of each update.
+    pub fn emit_many(data: &[MarketFtTokenFeeUpdate<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketFtTokenFeeUpdate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketTreasuryWithdraw<'a> {
+    pub ft_token_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl MarketTreasuryWithdraw<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a treasury withdraw event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketTreasuryWithdraw`] represents the data of each withdraw.
+    pub fn emit_many(data: &[MarketTreasuryWithdraw<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketTreasuryWithdraw(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MarketTreasuryWithdrawFail<'a> {
+    pub ft_token_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl MarketTreasuryWithdrawFail<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a failed treasury withdraw event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MarketTreasuryWithdrawFail`] represents the data of each withdraw.
+    pub fn emit_many(data: &[MarketTreasuryWithdrawFail<'_>]) {
+        new_mfight_market_v1(MfMarketEventKind::MarketTreasuryWithdrawFail(data)).emit()
+    }
+}
+
+//
+
 #[derive(Serialize, Debug)]
 pub struct MfMarketEvent<'a> {
     version: &'static str,
@@ -127,6 +482,20 @@ enum MfMarketEventKind<'a> {
     MarketUpdateSale(&'a [MarketUpdateSale<'a>]),
     MarketRemoveSale(&'a [MarketRemoveSale<'a>]),
     MarketOffer(&'a [MarketOffer<'a>]),
+    MarketAuctionStart(&'a [MarketAuctionStart<'a>]),
+    MarketAuctionExtend(&'a [MarketAuctionExtend<'a>]),
+    MarketAuctionSettle(&'a [MarketAuctionSettle<'a>]),
+    MarketCollectionOfferCreate(&'a [MarketCollectionOfferCreate<'a>]),
+    MarketCollectionOfferRemove(&'a [MarketCollectionOfferRemove<'a>]),
+    MarketCollectionOfferAccept(&'a [MarketCollectionOfferAccept<'a>]),
+    MarketBundleCreate(&'a [MarketBundleCreate<'a>]),
+    MarketBundleRemove(&'a [MarketBundleRemove<'a>]),
+    MarketBundleOffer(&'a [MarketBundleOffer<'a>]),
+    MarketFeeTiersUpdate(&'a [MarketFeeTiersUpdate<'a>]),
+    MarketNftContractFeeUpdate(&'a [MarketNftContractFeeUpdate<'a>]),
+    MarketFtTokenFeeUpdate(&'a [MarketFtTokenFeeUpdate<'a>]),
+    MarketTreasuryWithdraw(&'a [MarketTreasuryWithdraw<'a>]),
+    MarketTreasuryWithdrawFail(&'a [MarketTreasuryWithdrawFail<'a>]),
 }
 
 fn new_mfight_market<'a>(
@@ -53,6 +53,21 @@ macro_rules! impl_market_core {
             self.$tokens.market_accept_offer(nft_contract_id, token_id, ft_token_id)
           }
 
+          fn market_auction_settle(&mut self, nft_contract_id: AccountId, token_id: String) {
+            $(self.$assert_action();)?
+            self.$tokens.market_auction_settle(nft_contract_id, token_id)
+          }
+
+          #[private]
+          fn market_resolve_sale_token(&mut self, nft_contract_id: AccountId, token_id: String, approval_id: u64) {
+            self.$tokens.market_resolve_sale_token(nft_contract_id, token_id, approval_id)
+          }
+
+          fn market_remove_expired_sales(&mut self, limit: u64) -> u64 {
+            $(self.$assert_action();)?
+            self.$tokens.market_remove_expired_sales(limit)
+          }
+
           #[private]
           fn market_process_purchase(
             &mut self,
@@ -72,8 +87,21 @@ macro_rules! impl_market_core {
             buyer_id: AccountId,
             sale: Sale,
             price: U128,
+            fee: U128,
           ) -> U128 {            
-            self.$tokens.market_resolve_purchase(ft_token_id, buyer_id, sale, price)
+            self.$tokens.market_resolve_purchase(ft_token_id, buyer_id, sale, price, fee)
+          }
+
+          #[private]
+          fn market_resolve_auction_settle(
+            &mut self,
+            ft_token_id: AccountId,
+            buyer_id: AccountId,
+            sale: Sale,
+            price: U128,
+            fee: U128,
+          ) -> U128 {
+            self.$tokens.market_resolve_auction_settle(ft_token_id, buyer_id, sale, price, fee)
           }
 
           fn market_add_ft_token(&mut self, nft_contract_id: AccountId) -> bool {
@@ -104,21 +132,297 @@ macro_rules! impl_market_enumeration {
            self.$tokens.market_supply_by_owner_id(account_id)
           }
 
-          fn market_sales_by_owner_id(&self, account_id: AccountId, from_index: U64, limit: u64) -> Vec<mfight_sdk::market::Sale> {
-            self.$tokens.market_sales_by_owner_id(account_id, from_index, limit)
+          fn market_sales_by_owner_id(&self, account_id: AccountId, from_index: U64, limit: u64, hide_expired: Option<bool>) -> Vec<mfight_sdk::market::Sale> {
+            self.$tokens.market_sales_by_owner_id(account_id, from_index, limit, hide_expired)
           }
 
           fn market_supply_by_nft_contract_id(&self, nft_contract_id: AccountId) -> U64 {
             self.$tokens.market_supply_by_nft_contract_id(nft_contract_id)
           }
 
-          fn market_sales_by_nft_contract_id(&self, nft_contract_id: AccountId, from_index: U64, limit: u64) -> Vec<mfight_sdk::market::Sale> {
-            self.$tokens.market_sales_by_nft_contract_id(nft_contract_id, from_index, limit)
+          fn market_sales_by_nft_contract_id(&self, nft_contract_id: AccountId, from_index: U64, limit: u64, hide_expired: Option<bool>) -> Vec<mfight_sdk::market::Sale> {
+            self.$tokens.market_sales_by_nft_contract_id(nft_contract_id, from_index, limit, hide_expired)
           }
 
           fn market_sale(&self, contract_id: AccountId, token_id: String) -> Option<mfight_sdk::market::Sale> {
             self.$tokens.market_sale(contract_id, token_id)
           }
+
+          fn market_supply_private_sales_by_buyer_id(&self, account_id: AccountId) -> U64 {
+            self.$tokens.market_supply_private_sales_by_buyer_id(account_id)
+          }
+
+          fn market_private_sales_by_buyer_id(&self, account_id: AccountId, from_index: U64, limit: u64, hide_expired: Option<bool>) -> Vec<mfight_sdk::market::Sale> {
+            self.$tokens.market_private_sales_by_buyer_id(account_id, from_index, limit, hide_expired)
+          }
+
+          fn market_sales(
+            &self,
+            filter: Option<$crate::market::SaleFilter>,
+            sort: Option<$crate::market::SaleSort>,
+            cursor: Option<$crate::market::SaleCursor>,
+            limit: u64,
+          ) -> $crate::market::SalesPage {
+            self.$tokens.market_sales(filter, sort, cursor, limit)
+          }
+
+          fn market_collection_offer(&self, offer_id: String) -> Option<$crate::market::CollectionOffer> {
+            self.$tokens.market_collection_offer(offer_id)
+          }
+
+          fn market_supply_collection_offers_by_owner_id(&self, account_id: AccountId) -> U64 {
+            self.$tokens.market_supply_collection_offers_by_owner_id(account_id)
+          }
+
+          fn market_collection_offers_by_owner_id(&self, account_id: AccountId, from_index: U64, limit: u64) -> Vec<$crate::market::CollectionOffer> {
+            self.$tokens.market_collection_offers_by_owner_id(account_id, from_index, limit)
+          }
+
+          fn market_supply_collection_offers_by_nft_contract_id(&self, nft_contract_id: AccountId) -> U64 {
+            self.$tokens.market_supply_collection_offers_by_nft_contract_id(nft_contract_id)
+          }
+
+          fn market_collection_offers_by_nft_contract_id(&self, nft_contract_id: AccountId, from_index: U64, limit: u64) -> Vec<$crate::market::CollectionOffer> {
+            self.$tokens.market_collection_offers_by_nft_contract_id(nft_contract_id, from_index, limit)
+          }
+
+          fn market_bundle(&self, bundle_id: String) -> Option<$crate::market::Bundle> {
+            self.$tokens.market_bundle(bundle_id)
+          }
+
+          fn market_supply_bundles(&self) -> U64 {
+            self.$tokens.market_supply_bundles()
+          }
+
+          fn market_bundles(&self, from_index: U64, limit: u64) -> Vec<$crate::market::Bundle> {
+            self.$tokens.market_bundles(from_index, limit)
+          }
+
+          fn market_supply_bundles_by_owner_id(&self, account_id: AccountId) -> U64 {
+            self.$tokens.market_supply_bundles_by_owner_id(account_id)
+          }
+
+          fn market_bundles_by_owner_id(&self, account_id: AccountId, from_index: U64, limit: u64) -> Vec<$crate::market::Bundle> {
+            self.$tokens.market_bundles_by_owner_id(account_id, from_index, limit)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_market_collection_offers {
+    ($contract:ident, $tokens:ident $(, $assert_action:ident)?) => {
+        use $crate::market::{MarketCollectionOffers, CollectionOffer};
+        use $crate::nft::{TokenTypes, TokenRarity};
+
+        #[near_bindgen]
+        impl MarketCollectionOffers for $contract {
+          #[payable]
+          fn market_collection_offer_create(
+            &mut self,
+            nft_contract_id: AccountId,
+            types: Option<TokenTypes>,
+            rarity: Option<TokenRarity>,
+            expires_at: Option<u64>,
+          ) -> CollectionOffer {
+            $(self.$assert_action();)?
+            self.$tokens.market_collection_offer_create(nft_contract_id, types, rarity, expires_at)
+          }
+
+          fn market_collection_offer_remove(&mut self, offer_id: String) {
+            $(self.$assert_action();)?
+            self.$tokens.market_collection_offer_remove(offer_id)
+          }
+
+          #[private]
+          fn market_resolve_collection_offer_token(
+            &mut self,
+            offer: CollectionOffer,
+            token_id: String,
+            owner_id: AccountId,
+            approval_id: u64,
+          ) -> PromiseOrValue<bool> {
+            self.$tokens.market_resolve_collection_offer_token(offer, token_id, owner_id, approval_id)
+          }
+
+          #[private]
+          fn market_resolve_collection_offer(
+            &mut self,
+            offer: CollectionOffer,
+            token_id: String,
+            owner_id: AccountId,
+            price: U128,
+          ) -> bool {
+            self.$tokens.market_resolve_collection_offer(offer, token_id, owner_id, price)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_market_bundles {
+    ($contract:ident, $tokens:ident $(, $assert_action:ident)?) => {
+        use $crate::market::{MarketBundles, Bundle, BundleItem, SaleConditions};
+
+        #[near_bindgen]
+        impl MarketBundles for $contract {
+          fn market_bundle_create(&mut self, items: Vec<BundleItem>, sale_conditions: SaleConditions) -> Promise {
+            $(self.$assert_action();)?
+            self.$tokens.market_bundle_create(items, sale_conditions)
+          }
+
+          fn market_bundle_remove(&mut self, bundle_id: String) {
+            $(self.$assert_action();)?
+            self.$tokens.market_bundle_remove(bundle_id)
+          }
+
+          #[payable]
+          fn market_bundle_offer(&mut self, bundle_id: String) -> Promise {
+            $(self.$assert_action();)?
+            self.$tokens.market_bundle_offer(bundle_id)
+          }
+
+          fn market_bundle_claim(&mut self, nft_contract_id: AccountId, token_id: String) -> Promise {
+            $(self.$assert_action();)?
+            self.$tokens.market_bundle_claim(nft_contract_id, token_id)
+          }
+
+          #[private]
+          fn market_resolve_bundle_create(
+            &mut self,
+            owner_id: AccountId,
+            items: Vec<BundleItem>,
+            sale_conditions: SaleConditions,
+          ) -> Bundle {
+            self.$tokens.market_resolve_bundle_create(owner_id, items, sale_conditions)
+          }
+
+          #[private]
+          fn market_resolve_bundle_purchase(
+            &mut self,
+            ft_token_id: AccountId,
+            buyer_id: AccountId,
+            bundle: Bundle,
+            price: U128,
+          ) -> U128 {
+            self.$tokens.market_resolve_bundle_purchase(ft_token_id, buyer_id, bundle, price)
+          }
+
+          #[private]
+          fn market_resolve_bundle_transfer(&mut self, receiver_id: AccountId, tokens: Vec<(AccountId, String)>) {
+            self.$tokens.market_resolve_bundle_transfer(receiver_id, tokens)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_market_fees {
+    ($contract:ident, $tokens:ident, $assert_owner:ident) => {
+        use $crate::market::{MarketFees, FeeTier};
+
+        #[near_bindgen]
+        impl MarketFees for $contract {
+          fn market_set_fee_tiers(&mut self, fee_tiers: Vec<FeeTier>) {
+            self.$assert_owner();
+            self.$tokens.market_set_fee_tiers(fee_tiers)
+          }
+
+          fn market_set_nft_contract_fee(&mut self, nft_contract_id: AccountId, fee: Option<u16>) {
+            self.$assert_owner();
+            self.$tokens.market_set_nft_contract_fee(nft_contract_id, fee)
+          }
+
+          fn market_set_ft_token_fee(&mut self, ft_token_id: AccountId, fee: Option<u16>) {
+            self.$assert_owner();
+            self.$tokens.market_set_ft_token_fee(ft_token_id, fee)
+          }
+
+          fn market_treasury_withdraw(&mut self, ft_token_id: AccountId, amount: U128) -> Promise {
+            self.$assert_owner();
+            self.$tokens.market_treasury_withdraw(ft_token_id, amount)
+          }
+
+          #[private]
+          fn market_resolve_treasury_withdraw(
+            &mut self,
+            ft_token_id: AccountId,
+            receiver_id: AccountId,
+            amount: U128,
+          ) -> bool {
+            self.$tokens.market_resolve_treasury_withdraw(ft_token_id, receiver_id, amount)
+          }
+
+          fn market_fee_tiers(&self) -> Vec<FeeTier> {
+            self.$tokens.market_fee_tiers()
+          }
+
+          fn market_nft_contract_fee(&self, nft_contract_id: AccountId) -> Option<u16> {
+            self.$tokens.market_nft_contract_fee(nft_contract_id)
+          }
+
+          fn market_ft_token_fee(&self, ft_token_id: AccountId) -> Option<u16> {
+            self.$tokens.market_ft_token_fee(ft_token_id)
+          }
+
+          fn market_fee(
+            &self,
+            account_id: AccountId,
+            price: U128,
+            nft_contract_id: Option<AccountId>,
+            ft_token_id: Option<AccountId>,
+          ) -> U128 {
+            self.$tokens.market_fee(account_id, price, nft_contract_id, ft_token_id)
+          }
+
+          fn market_treasury(&self, ft_token_id: AccountId) -> U128 {
+            self.$tokens.market_treasury(ft_token_id)
+          }
+
+          fn market_fees_collected(&self, ft_token_id: AccountId) -> U128 {
+            self.$tokens.market_fees_collected(ft_token_id)
+          }
+
+          fn market_fees_collected_by_nft_contract_id(
+            &self,
+            nft_contract_id: AccountId,
+          ) -> std::collections::HashMap<AccountId, U128> {
+            self.$tokens.market_fees_collected_by_nft_contract_id(nft_contract_id)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_market_batch {
+    ($contract:ident, $tokens:ident $(, $assert_action:ident)?) => {
+        use $crate::market::{MarketBatch, SaleItem, SaleListing, SaleUpdate, SaleBatchFailure};
+
+        #[near_bindgen]
+        impl MarketBatch for $contract {
+          fn market_list_batch(&mut self, listings: Vec<SaleListing>) -> Promise {
+            $(self.$assert_action();)?
+            self.$tokens.market_list_batch(listings)
+          }
+
+          fn market_remove_batch(&mut self, items: Vec<SaleItem>) -> Vec<SaleBatchFailure> {
+            $(self.$assert_action();)?
+            self.$tokens.market_remove_batch(items)
+          }
+
+          fn market_update_batch(&mut self, updates: Vec<SaleUpdate>) -> Vec<SaleBatchFailure> {
+            $(self.$assert_action();)?
+            self.$tokens.market_update_batch(updates)
+          }
+
+          #[private]
+          fn market_resolve_list_batch(
+            &mut self,
+            owner_id: AccountId,
+            listings: Vec<SaleListing>,
+          ) -> Vec<SaleBatchFailure> {
+            self.$tokens.market_resolve_list_batch(owner_id, listings)
+          }
         }
     };
 }
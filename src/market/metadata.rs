@@ -1,17 +1,20 @@
 use near_sdk::AccountId;
-use std::collections::HashMap;
+use std::collections::{ HashMap, HashSet };
 use near_sdk::json_types::{ U128 };
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
 use near_sdk::serde::{ Deserialize, Serialize };
 use near_sdk::serde_json::to_string;
 use schemars::JsonSchema;
 use crate::metadata::FungibleTokenId;
+use crate::nft::{ Token, TokenTypes, TokenRarity };
 
 pub type SaleConditions = HashMap<FungibleTokenId, U128>;
 pub type Bids = HashMap<FungibleTokenId, Vec<Bid>>;
 pub type TokenId = String;
 
 pub type ContractAndTokenId = String;
+pub type CollectionOfferId = String;
+pub type BundleId = String;
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
@@ -20,6 +23,63 @@ pub struct Bid {
     pub price: U128,
 }
 
+/// Timed (english) auction, `start_at`, `end_at` and `extension_time` in nanoseconds
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Auction {
+    pub start_at: u64,
+    pub end_at: u64,
+    pub reserve_price: U128,
+    pub min_bid_increment: U128,
+    /// bid in the last `extension_time` of an auction moves its end to `extension_time` after the bid
+    pub extension_time: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PriceDecay {
+    Linear,
+    Stepwise {
+        step: u64,
+    },
+}
+
+/// Descending price (dutch) auction, price falls from `start_price` at `start_at`
+/// to `floor_price` at `end_at` (nanoseconds)
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DutchAuction {
+    pub start_price: U128,
+    pub floor_price: U128,
+    pub start_at: u64,
+    pub end_at: u64,
+    pub decay: PriceDecay,
+}
+
+impl DutchAuction {
+    pub fn current_price(&self, now: u64) -> u128 {
+        if now <= self.start_at {
+            return self.start_price.0;
+        }
+        if now >= self.end_at {
+            return self.floor_price.0;
+        }
+
+        let duration = (self.end_at - self.start_at) as u128;
+        let elapsed = match self.decay {
+            PriceDecay::Linear => now - self.start_at,
+            PriceDecay::Stepwise { step } => ((now - self.start_at) / step) * step,
+        } as u128;
+        let diff = self.start_price.0 - self.floor_price.0;
+        let decrease = diff
+            .checked_mul(elapsed)
+            .map(|value| value / duration)
+            .unwrap_or_else(|| (diff / duration) * elapsed);
+
+        self.start_price.0 - decrease
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Sale {
@@ -31,6 +91,32 @@ pub struct Sale {
     pub bids: Bids,
     pub created_at: u64,
     pub is_auction: bool,
+    pub auction: Option<Auction>,
+    pub dutch_auction: Option<DutchAuction>,
+    pub starts_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub types: Option<TokenTypes>,
+    pub rarity: Option<TokenRarity>,
+    /// only these accounts can buy a private sale
+    pub buyers: Option<HashSet<AccountId>>,
+}
+
+impl Sale {
+    pub fn is_started(&self, now: u64) -> bool {
+        self.starts_at.map(|starts_at| now >= starts_at).unwrap_or(true)
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.map(|expires_at| now >= expires_at).unwrap_or(false)
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.buyers.is_some()
+    }
+
+    pub fn is_buyer_allowed(&self, account_id: &AccountId) -> bool {
+        self.buyers.as_ref().map(|buyers| buyers.contains(account_id)).unwrap_or(true)
+    }
 }
 
 impl ToString for Sale {
@@ -45,6 +131,13 @@ impl ToString for Sale {
                 bids: self.bids.clone(),
                 created_at: self.created_at.clone(),
                 is_auction: self.is_auction.clone(),
+                auction: self.auction.clone(),
+                dutch_auction: self.dutch_auction.clone(),
+                starts_at: self.starts_at.clone(),
+                expires_at: self.expires_at.clone(),
+                types: self.types.clone(),
+                rarity: self.rarity.clone(),
+                buyers: self.buyers.clone(),
             })
         )
             .ok()
@@ -65,4 +158,237 @@ pub struct MarketOnNftApproveArgs {
     pub sale_conditions: SaleConditions,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_auction: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auction: Option<Auction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dutch_auction: Option<DutchAuction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyers: Option<HashSet<AccountId>>,
+}
+
+/// Standing offer for any token of `nft_contract_id` matching `types` and `rarity`
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionOffer {
+    pub offer_id: CollectionOfferId,
+    pub owner_id: AccountId,
+    pub nft_contract_id: AccountId,
+    pub ft_token_id: FungibleTokenId,
+    pub price: U128,
+    pub types: Option<TokenTypes>,
+    pub rarity: Option<TokenRarity>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+}
+
+impl CollectionOffer {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.map(|expires_at| now >= expires_at).unwrap_or(false)
+    }
+
+    pub fn matches(&self, token: &Token) -> bool {
+        if let Some(rarity) = &self.rarity {
+            if token.rarity.as_ref() != Some(rarity) {
+                return false;
+            }
+        }
+
+        if let Some(types) = &self.types {
+            return is_types_match(types, &token.types);
+        }
+
+        true
+    }
+}
+
+/// every key of `types` is present in `token_types` with the same value
+fn is_types_match(types: &TokenTypes, token_types: &Option<TokenTypes>) -> bool {
+    let token_types = if let Some(token_types) = token_types {
+        token_types
+    } else {
+        return false;
+    };
+
+    types.iter().all(|(key, value)| token_types.get(key) == Some(value))
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketOnFtTransferCollectionOfferArgs {
+    pub nft_contract_id: AccountId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<TokenTypes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rarity: Option<TokenRarity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketOnNftApproveCollectionOfferArgs {
+    pub offer_id: CollectionOfferId,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BundleItem {
+    pub nft_contract_id: AccountId,
+    pub token_id: TokenId,
+    pub approval_id: u64,
+}
+
+/// Several tokens, possibly from different contracts, sold together for a single price
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Bundle {
+    pub bundle_id: BundleId,
+    pub owner_id: AccountId,
+    pub items: Vec<BundleItem>,
+    pub sale_conditions: SaleConditions,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketOnFtTransferBundleArgs {
+    pub bundle_id: BundleId,
+}
+
+/// Fee in basis points paid by accounts with at least `reputation`
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeTier {
+    pub reputation: u32,
+    pub fee: u16,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum SaleSort {
+    PriceAsc,
+    PriceDesc,
+    CreatedAtAsc,
+    CreatedAtDesc,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleFilter {
+    #[serde(default)]
+    pub ft_token_id: Option<FungibleTokenId>,
+    #[serde(default)]
+    pub min_price: Option<U128>,
+    #[serde(default)]
+    pub max_price: Option<U128>,
+    #[serde(default)]
+    pub is_auction: Option<bool>,
+    #[serde(default)]
+    pub nft_contract_id: Option<AccountId>,
+    #[serde(default)]
+    pub types: Option<TokenTypes>,
+    #[serde(default)]
+    pub rarity: Option<TokenRarity>,
+    #[serde(default)]
+    pub hide_expired: Option<bool>,
+}
+
+impl SaleFilter {
+    /// `sale` is expected to have its current price in `sale_conditions`
+    pub fn matches(&self, sale: &Sale, now: u64) -> bool {
+        if self.hide_expired.unwrap_or(false) && sale.is_expired(now) {
+            return false;
+        }
+
+        if let Some(nft_contract_id) = &self.nft_contract_id {
+            if &sale.nft_contract_id != nft_contract_id {
+                return false;
+            }
+        }
+
+        if let Some(is_auction) = self.is_auction {
+            let is_sale_auction = sale.is_auction || sale.dutch_auction.is_some();
+            if is_sale_auction != is_auction {
+                return false;
+            }
+        }
+
+        let min_price = self.min_price.map(|price| price.0).unwrap_or(0);
+        let max_price = self.max_price.map(|price| price.0).unwrap_or(u128::MAX);
+        let is_price_match = |price: &U128| price.0 >= min_price && price.0 <= max_price;
+        let is_price_match = if let Some(ft_token_id) = &self.ft_token_id {
+            sale.sale_conditions.get(ft_token_id).map(is_price_match).unwrap_or(false)
+        } else {
+            sale.sale_conditions.values().any(is_price_match)
+        };
+        if !is_price_match {
+            return false;
+        }
+
+        if let Some(rarity) = &self.rarity {
+            if sale.rarity.as_ref() != Some(rarity) {
+                return false;
+            }
+        }
+
+        if let Some(types) = &self.types {
+            return is_types_match(types, &sale.types);
+        }
+
+        true
+    }
+}
+
+/// Last index entry of a page, `key` is price or `created_at` depending on sort
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleCursor {
+    pub key: U128,
+    pub contract_and_token_id: ContractAndTokenId,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SalesPage {
+    pub sales: Vec<Sale>,
+    pub next_cursor: Option<SaleCursor>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleItem {
+    pub nft_contract_id: AccountId,
+    pub token_id: TokenId,
+}
+
+/// Fixed price listing of an already approved token
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleListing {
+    pub nft_contract_id: AccountId,
+    pub token_id: TokenId,
+    pub approval_id: u64,
+    pub sale_conditions: SaleConditions,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleUpdate {
+    pub nft_contract_id: AccountId,
+    pub token_id: TokenId,
+    pub sale_conditions: SaleConditions,
+}
+
+/// Batch item that was skipped and why
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleBatchFailure {
+    pub nft_contract_id: AccountId,
+    pub token_id: TokenId,
+    pub error: String,
 }
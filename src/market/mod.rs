@@ -1,6 +1,6 @@
 pub use macros::*;
 
-pub use self::base::{MarketCore, MarketFeature};
+pub use self::base::{MarketCore, MarketCollectionOffers, MarketBundles, MarketFees, MarketBatch, MarketFeature};
 pub use self::enumeration::MarketEnumeration;
 pub use self::metadata::*;
 
@@ -11,6 +11,8 @@ pub mod metadata;
 pub mod macros;
 pub mod events;
 pub use self::events::*;
+
+mod test;
 // mod approved_check;
 
 // pub trait RentFeatureReceiver {
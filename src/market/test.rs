@@ -0,0 +1,1799 @@
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use crate::market::{ MarketFeature, MarketCore, MarketCollectionOffers, MarketBundles, MarketFees, MarketBatch, MarketEnumeration };
+    use crate::market::{ Auction, Bid, DutchAuction, PriceDecay, MarketOnNftApproveArgs, Sale, SaleConditions };
+    use crate::market::{ CollectionOffer, MarketOnNftApproveCollectionOfferArgs, BundleItem, FeeTier };
+    use crate::market::{ MarketOnFtTransferArgs, SaleFilter, SaleSort, SalesPage };
+    use crate::market::{ SaleBatchFailure, SaleItem, SaleListing, SaleUpdate };
+    use crate::nft::{ Token, TokenTypes, Payout };
+    use near_sdk::test_utils::{ accounts, get_created_receipts, VMContextBuilder };
+    use near_sdk::mock::VmAction;
+    use near_sdk::{ AccountId, BorshStorageKey, Balance, testing_env, PromiseOrValue, PromiseResult, RuntimeFeesConfig, VMConfig };
+    use near_sdk::json_types::{ U128, U64 };
+    use near_sdk::borsh::{ self, BorshSerialize };
+    use std::collections::{ HashMap, HashSet };
+    use crate::utils::near_ft;
+
+    const NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+    const MINUTE: u64 = 60_000_000_000;
+    const HOUR: u64 = 3_600_000_000_000;
+
+    /// Helper structure for keys of the persistent collections.
+    #[derive(BorshSerialize, BorshStorageKey)]
+    pub enum StorageKey {
+        Sales,
+        ByOwnerId,
+        ByNftContractId,
+        FtTokenIds,
+        StorageDeposits,
+        CollectionOffers,
+        CollectionOffersByOwnerId,
+        CollectionOffersByNftContractId,
+        Bundles,
+        BundlesByOwnerId,
+        BundleByTokenId,
+        BundleClaims,
+        FeeByNftContractId,
+        FeeByFtTokenId,
+        Treasury,
+        FeesCollected,
+        FeesCollectedByNftContractId,
+        SalesByExpiresAt,
+        SalesByPrice,
+        SalesByCreatedAt,
+        PrivateSalesByBuyerId,
+        Reputation,
+    }
+
+    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
+
+    fn get_instance() -> MarketFeature {
+        MarketFeature::new(
+            accounts(0),
+            Some(vec![ft()]),
+            None,
+            StorageKey::Sales,
+            StorageKey::ByOwnerId,
+            StorageKey::ByNftContractId,
+            StorageKey::FtTokenIds,
+            StorageKey::StorageDeposits,
+            StorageKey::CollectionOffers,
+            StorageKey::CollectionOffersByOwnerId,
+            StorageKey::CollectionOffersByNftContractId,
+            StorageKey::Bundles,
+            StorageKey::BundlesByOwnerId,
+            StorageKey::BundleByTokenId,
+            StorageKey::BundleClaims,
+            StorageKey::FeeByNftContractId,
+            StorageKey::FeeByFtTokenId,
+            StorageKey::Treasury,
+            StorageKey::FeesCollected,
+            StorageKey::FeesCollectedByNftContractId,
+            StorageKey::SalesByExpiresAt,
+            StorageKey::SalesByPrice,
+            StorageKey::SalesByCreatedAt,
+            StorageKey::PrivateSalesByBuyerId,
+            Some(StorageKey::Reputation)
+        )
+    }
+
+    fn nft() -> AccountId {
+        AccountId::new_unchecked("nft".to_string())
+    }
+
+    fn ft() -> AccountId {
+        AccountId::new_unchecked("ft".to_string())
+    }
+
+    fn near_conditions(price: Balance) -> SaleConditions {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(near_ft(), U128(price));
+        sale_conditions
+    }
+
+    fn sale_args(sale_conditions: SaleConditions) -> MarketOnNftApproveArgs {
+        MarketOnNftApproveArgs {
+            sale_conditions,
+            is_auction: None,
+            auction: None,
+            dutch_auction: None,
+            starts_at: None,
+            expires_at: None,
+            buyers: None,
+        }
+    }
+
+    /// token listed by accounts(1) at block timestamp 0
+    fn add_sale(instance: &mut MarketFeature, token_id: &str, args: &MarketOnNftApproveArgs) {
+        add_sale_at(instance, token_id, args, 0);
+    }
+
+    fn add_sale_at(
+        instance: &mut MarketFeature,
+        token_id: &str,
+        args: &MarketOnNftApproveArgs,
+        now: u64
+    ) {
+        let mut context = get_context(nft());
+        testing_env!(context.block_timestamp(now).build());
+
+        instance.internal_on_nft_approve(&args, &nft(), &token_id.to_string(), &accounts(1), &1);
+    }
+
+    fn add_auction(instance: &mut MarketFeature) {
+        let mut args = sale_args(near_conditions(NEAR));
+
+        args.auction = Some(Auction {
+            start_at: 0,
+            end_at: HOUR,
+            reserve_price: U128(2 * NEAR),
+            min_bid_increment: U128(NEAR / 10),
+            extension_time: 10 * MINUTE,
+        });
+
+        add_sale(instance, "1", &args);
+    }
+
+    fn bid(instance: &mut MarketFeature, buyer_id: AccountId, amount: Balance, now: u64) {
+        let mut context = get_context(buyer_id);
+        testing_env!(context.block_timestamp(now).attached_deposit(amount).build());
+
+        instance.market_offer(nft(), "1".to_string());
+    }
+
+    fn last_bid(instance: &MarketFeature) -> (AccountId, U128) {
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+        let bid = sale.bids.get(&near_ft()).unwrap().last().unwrap().clone();
+
+        (bid.owner_id, bid.price)
+    }
+
+    #[test]
+    fn test_auction_bid() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), NEAR, MINUTE);
+
+        assert_eq!(last_bid(&instance), (accounts(2), U128(NEAR)));
+
+        bid(&mut instance, accounts(3), NEAR + NEAR / 10, 2 * MINUTE);
+
+        assert_eq!(last_bid(&instance), (accounts(3), U128(NEAR + NEAR / 10)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bid must be at least")]
+    fn test_auction_bid_increment() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), NEAR, MINUTE);
+        bid(&mut instance, accounts(3), NEAR + NEAR / 20, 2 * MINUTE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than reserve price")]
+    fn test_auction_bid_below_start_price() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), NEAR / 2, MINUTE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Auction is ended")]
+    fn test_auction_bid_ended() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), NEAR, HOUR);
+    }
+
+    #[test]
+    fn test_auction_anti_snipe() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        // early bid keeps the end
+        bid(&mut instance, accounts(2), NEAR, MINUTE);
+
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+
+        assert_eq!(sale.auction.unwrap().end_at, HOUR);
+
+        // bid in the last minutes moves the end
+        let now = HOUR - 5 * MINUTE;
+
+        bid(&mut instance, accounts(3), 2 * NEAR, now);
+
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+
+        assert_eq!(sale.auction.unwrap().end_at, now + 10 * MINUTE);
+
+        // and it can be outbid after the original end
+        bid(&mut instance, accounts(2), 3 * NEAR, HOUR + MINUTE);
+
+        assert_eq!(last_bid(&instance), (accounts(2), U128(3 * NEAR)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Auction is not ended")]
+    fn test_auction_settle_not_ended() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), 2 * NEAR, MINUTE);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR - MINUTE).build());
+
+        instance.market_auction_settle(nft(), "1".to_string());
+    }
+
+    #[test]
+    fn test_auction_settle() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), 2 * NEAR, MINUTE);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        instance.market_auction_settle(nft(), "1".to_string());
+
+        // the token is transferred to the winner, the sale is removed before the transfer
+        assert!(instance.market_sale(nft(), "1".to_string()).is_none());
+        assert_eq!(instance.market_supply_by_owner_id(accounts(1)).0, 0);
+    }
+
+    #[test]
+    fn test_auction_settle_reserve_not_met() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), NEAR, MINUTE);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        instance.market_auction_settle(nft(), "1".to_string());
+
+        assert!(instance.market_sale(nft(), "1".to_string()).is_none());
+        assert_eq!(instance.market_supply_sales().0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Auction extension time must be positive")]
+    fn test_auction_extension_time_too_long() {
+        let mut instance = get_instance();
+        let mut args = sale_args(near_conditions(NEAR));
+
+        args.auction = Some(Auction {
+            start_at: 0,
+            end_at: HOUR,
+            reserve_price: U128(2 * NEAR),
+            min_bid_increment: U128(NEAR / 10),
+            extension_time: 2 * HOUR,
+        });
+
+        add_sale(&mut instance, "1", &args);
+    }
+
+    /// sale and winning bid passed to the settle resolver
+    fn resolve_settle_args() -> (Sale, U128, U128) {
+        let args = get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .find_map(|action| {
+                match action {
+                    VmAction::FunctionCall { function_name, args, .. } if
+                        function_name == "market_resolve_auction_settle"
+                    => Some(args),
+                    _ => None,
+                }
+            })
+            .expect("No settle");
+        let args: near_sdk::serde_json::Value = near_sdk::serde_json::from_slice(&args).unwrap();
+
+        (
+            near_sdk::serde_json::from_value(args["sale"].clone()).unwrap(),
+            near_sdk::serde_json::from_value(args["price"].clone()).unwrap(),
+            near_sdk::serde_json::from_value(args["fee"].clone()).unwrap(),
+        )
+    }
+
+    /// NEAR transfers and ft_transfer calls of the current call
+    fn refunds() -> Vec<(AccountId, String)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id.clone();
+
+                receipt.actions.into_iter().filter_map(move |action| {
+                    match action {
+                        VmAction::Transfer { deposit } => {
+                            Some((receiver_id.clone(), deposit.to_string()))
+                        }
+                        VmAction::FunctionCall { function_name, args, .. } if
+                            function_name == "ft_transfer"
+                        => {
+                            let args: near_sdk::serde_json::Value = near_sdk::serde_json
+                                ::from_slice(&args)
+                                .unwrap();
+
+                            Some((
+                                args["receiver_id"].as_str().unwrap().parse().unwrap(),
+                                args["amount"].as_str().unwrap().to_string(),
+                            ))
+                        }
+                        _ => None,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn resolve_with(result: PromiseResult) {
+        resolve_with_all(vec![result]);
+    }
+
+    fn resolve_with_all(results: Vec<PromiseResult>) {
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            results
+        );
+    }
+
+    #[test]
+    fn test_auction_settle_failed() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), 2 * NEAR, MINUTE);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        instance.market_auction_settle(nft(), "1".to_string());
+
+        let (sale, price, fee) = resolve_settle_args();
+
+        assert_eq!(price.0 + fee.0, 2 * NEAR);
+
+        resolve_with(PromiseResult::Failed);
+
+        instance.market_resolve_auction_settle(near_ft(), accounts(2), sale, price, fee);
+
+        // the whole winning bid is returned
+        assert_eq!(refunds(), vec![(accounts(2), (2 * NEAR).to_string())]);
+    }
+
+    #[test]
+    fn test_auction_settle_failed_ft() {
+        let mut instance = get_instance();
+        add_auction(&mut instance);
+
+        bid(&mut instance, accounts(2), 2 * NEAR, MINUTE);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        instance.market_auction_settle(nft(), "1".to_string());
+
+        let (mut sale, price, fee) = resolve_settle_args();
+
+        // bid left in another token
+        sale.bids.insert(near_ft(), vec![Bid { owner_id: accounts(3), price: U128(NEAR) }]);
+
+        resolve_with(PromiseResult::Failed);
+
+        instance.market_resolve_auction_settle(ft(), accounts(2), sale, price, fee);
+
+        // winning bid goes back with ft_transfer, other bids are refunded too
+        let mut refunds = refunds();
+        refunds.sort();
+
+        assert_eq!(
+            refunds,
+            vec![(accounts(2), (2 * NEAR).to_string()), (accounts(3), NEAR.to_string())]
+        );
+    }
+
+    fn dutch_auction(decay: PriceDecay) -> DutchAuction {
+        DutchAuction {
+            start_price: U128(10 * NEAR),
+            floor_price: U128(2 * NEAR),
+            start_at: HOUR,
+            end_at: 5 * HOUR,
+            decay,
+        }
+    }
+
+    #[test]
+    fn test_dutch_price_linear() {
+        let auction = dutch_auction(PriceDecay::Linear);
+
+        assert_eq!(auction.current_price(0), 10 * NEAR);
+        assert_eq!(auction.current_price(HOUR), 10 * NEAR);
+        assert_eq!(auction.current_price(2 * HOUR), 8 * NEAR);
+        assert_eq!(auction.current_price(3 * HOUR), 6 * NEAR);
+        assert_eq!(auction.current_price(3 * HOUR + 30 * MINUTE), 5 * NEAR);
+        assert_eq!(auction.current_price(5 * HOUR), 2 * NEAR);
+        assert_eq!(auction.current_price(10 * HOUR), 2 * NEAR);
+    }
+
+    #[test]
+    fn test_dutch_price_stepwise() {
+        let auction = dutch_auction(PriceDecay::Stepwise { step: HOUR });
+
+        assert_eq!(auction.current_price(HOUR), 10 * NEAR);
+        // price is kept until the next step
+        assert_eq!(auction.current_price(2 * HOUR - 1), 10 * NEAR);
+        assert_eq!(auction.current_price(2 * HOUR), 8 * NEAR);
+        assert_eq!(auction.current_price(3 * HOUR + 30 * MINUTE), 6 * NEAR);
+        assert_eq!(auction.current_price(5 * HOUR - 1), 4 * NEAR);
+        assert_eq!(auction.current_price(5 * HOUR), 2 * NEAR);
+    }
+
+    #[test]
+    fn test_dutch_price_rounding() {
+        let auction = DutchAuction {
+            start_price: U128(10),
+            floor_price: U128(0),
+            start_at: 0,
+            end_at: 3,
+            decay: PriceDecay::Linear,
+        };
+
+        // decrease is rounded down, so the price never falls below the decayed value
+        assert_eq!(auction.current_price(1), 7);
+        assert_eq!(auction.current_price(2), 4);
+    }
+
+    #[test]
+    fn test_dutch_purchase_current_price() {
+        let mut instance = get_instance();
+
+        let mut args = sale_args(near_conditions(10 * NEAR));
+        args.dutch_auction = Some(dutch_auction(PriceDecay::Linear));
+
+        add_sale(&mut instance, "1", &args);
+
+        // views report the decayed price
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(3 * HOUR).build());
+
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+
+        assert_eq!(sale.sale_conditions.get(&near_ft()), Some(&U128(6 * NEAR)));
+
+        // the current price is enough, the rest of the deposit is returned
+        bid(&mut instance, accounts(2), 7 * NEAR, 3 * HOUR);
+
+        assert!(instance.market_sale(nft(), "1".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit must be greater than current price")]
+    fn test_dutch_purchase_below_current_price() {
+        let mut instance = get_instance();
+
+        let mut args = sale_args(near_conditions(10 * NEAR));
+        args.dutch_auction = Some(dutch_auction(PriceDecay::Stepwise { step: HOUR }));
+
+        add_sale(&mut instance, "1", &args);
+
+        bid(&mut instance, accounts(2), 7 * NEAR, 3 * HOUR - 1);
+    }
+
+    fn token(owner_id: AccountId, rarity: Option<u8>, types: Option<TokenTypes>) -> Token {
+        Token {
+            token_id: "1".to_string(),
+            owner_id,
+            metadata: None,
+            approved_account_ids: None,
+            royalty: None,
+            bind_to_owner: None,
+            reveal_at: None,
+            rarity,
+            types,
+        }
+    }
+
+    fn types(pairs: &[(&str, &str)]) -> TokenTypes {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// offer of accounts(2) for 1 NEAR
+    fn add_collection_offer(
+        instance: &mut MarketFeature,
+        types: Option<TokenTypes>,
+        rarity: Option<u8>
+    ) -> CollectionOffer {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.attached_deposit(NEAR).build());
+
+        instance.market_collection_offer_create(nft(), types, rarity, Some(HOUR))
+    }
+
+    /// token "1" of accounts(1) approved for the offer
+    fn accept_collection_offer(instance: &mut MarketFeature, offer_id: &String) {
+        let mut context = get_context(nft());
+        testing_env!(context.build());
+
+        let args = MarketOnNftApproveCollectionOfferArgs { offer_id: offer_id.clone() };
+
+        instance.internal_on_nft_approve_collection_offer(
+            &args,
+            &nft(),
+            &"1".to_string(),
+            &accounts(1),
+            &1
+        );
+    }
+
+    #[test]
+    fn test_collection_offer_matches() {
+        let mut instance = get_instance();
+
+        let any = add_collection_offer(&mut instance, None, None);
+        let rare = add_collection_offer(&mut instance, None, Some(3));
+        let typed = add_collection_offer(
+            &mut instance,
+            Some(types(&[("race", "elf"), ("class", "mage")])),
+            None
+        );
+
+        let elf_mage = types(&[("race", "elf"), ("class", "mage"), ("weapon", "staff")]);
+        let elf_warrior = types(&[("race", "elf"), ("class", "warrior")]);
+
+        assert!(any.matches(&token(accounts(1), None, None)));
+
+        assert!(rare.matches(&token(accounts(1), Some(3), None)));
+        assert!(!rare.matches(&token(accounts(1), Some(2), None)));
+        assert!(!rare.matches(&token(accounts(1), None, None)));
+
+        // every type of the offer must be present, extra types of the token are ignored
+        assert!(typed.matches(&token(accounts(1), None, Some(elf_mage))));
+        assert!(!typed.matches(&token(accounts(1), None, Some(elf_warrior))));
+        assert!(!typed.matches(&token(accounts(1), None, None)));
+    }
+
+    #[test]
+    fn test_collection_offer_create_remove() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, None, Some(3));
+
+        assert_eq!(
+            instance.market_collection_offer(offer.offer_id.clone()).map(|offer| offer.price),
+            Some(U128(NEAR))
+        );
+        assert_eq!(instance.market_supply_collection_offers_by_owner_id(accounts(2)).0, 1);
+        assert_eq!(instance.market_supply_collection_offers_by_nft_contract_id(nft()).0, 1);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+
+        instance.market_collection_offer_remove(offer.offer_id.clone());
+
+        assert!(instance.market_collection_offer(offer.offer_id).is_none());
+        assert_eq!(instance.market_supply_collection_offers_by_owner_id(accounts(2)).0, 0);
+        assert_eq!(instance.market_supply_collection_offers_by_nft_contract_id(nft()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must be offer owner")]
+    fn test_collection_offer_remove_not_owner() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, None, None);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR - 1).build());
+
+        instance.market_collection_offer_remove(offer.offer_id);
+    }
+
+    #[test]
+    fn test_collection_offer_remove_expired() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, None, None);
+
+        // anyone can clean up an expired offer, the deposit goes back to its owner
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        instance.market_collection_offer_remove(offer.offer_id.clone());
+
+        assert!(instance.market_collection_offer(offer.offer_id).is_none());
+    }
+
+    #[test]
+    fn test_collection_offer_accept() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, None, None);
+
+        accept_collection_offer(&mut instance, &offer.offer_id);
+
+        // taken out while the token is transferred
+        assert!(instance.market_collection_offer(offer.offer_id.clone()).is_none());
+
+        // failed transfer puts it back
+        resolve_with(PromiseResult::Failed);
+
+        let is_accepted = instance.market_resolve_collection_offer(
+            offer.clone(),
+            "1".to_string(),
+            accounts(1),
+            offer.price.clone()
+        );
+
+        assert!(!is_accepted);
+        assert!(instance.market_collection_offer(offer.offer_id).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot accept your own offer")]
+    fn test_collection_offer_accept_own() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, None, None);
+
+        let mut context = get_context(nft());
+        testing_env!(context.build());
+
+        let args = MarketOnNftApproveCollectionOfferArgs { offer_id: offer.offer_id };
+
+        instance.internal_on_nft_approve_collection_offer(
+            &args,
+            &nft(),
+            &"1".to_string(),
+            &accounts(2),
+            &1
+        );
+    }
+
+    #[test]
+    fn test_collection_offer_token_not_matched() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, Some(types(&[("race", "elf")])), Some(3));
+
+        accept_collection_offer(&mut instance, &offer.offer_id);
+
+        assert!(instance.market_collection_offer(offer.offer_id.clone()).is_none());
+
+        let orc = token(accounts(1), Some(3), Some(types(&[("race", "orc")])));
+
+        resolve_with(PromiseResult::Successful(near_sdk::serde_json::to_vec(&Some(orc)).unwrap()));
+
+        let result = instance.market_resolve_collection_offer_token(
+            offer.clone(),
+            "1".to_string(),
+            accounts(1),
+            1
+        );
+
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+        assert!(instance.market_collection_offer(offer.offer_id).is_some());
+    }
+
+    #[test]
+    fn test_collection_offer_token_matched() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, Some(types(&[("race", "elf")])), Some(3));
+
+        accept_collection_offer(&mut instance, &offer.offer_id);
+
+        let elf = token(accounts(1), Some(3), Some(types(&[("race", "elf")])));
+
+        resolve_with(PromiseResult::Successful(near_sdk::serde_json::to_vec(&Some(elf)).unwrap()));
+
+        let result = instance.market_resolve_collection_offer_token(
+            offer.clone(),
+            "1".to_string(),
+            accounts(1),
+            1
+        );
+
+        // token is transferred, the offer stays taken out
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+        assert!(instance.market_collection_offer(offer.offer_id).is_none());
+    }
+
+    #[test]
+    fn test_collection_offer_token_other_owner() {
+        let mut instance = get_instance();
+
+        let offer = add_collection_offer(&mut instance, None, Some(3));
+
+        accept_collection_offer(&mut instance, &offer.offer_id);
+
+        // the token was transferred before the check
+        resolve_with(
+            PromiseResult::Successful(
+                near_sdk::serde_json::to_vec(&Some(token(accounts(3), Some(3), None))).unwrap()
+            )
+        );
+
+        let result = instance.market_resolve_collection_offer_token(
+            offer.clone(),
+            "1".to_string(),
+            accounts(1),
+            1
+        );
+
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+        assert!(instance.market_collection_offer(offer.offer_id).is_some());
+    }
+
+    fn bundle_item(token_id: &str) -> BundleItem {
+        BundleItem {
+            nft_contract_id: nft(),
+            token_id: token_id.to_string(),
+            approval_id: 1,
+        }
+    }
+
+    /// token of accounts(1) approved for market
+    fn approved_token(token_id: &str) -> PromiseResult {
+        let mut token = token(accounts(1), None, None);
+        token.token_id = token_id.to_string();
+        token.approved_account_ids = Some(vec![(accounts(0), 1)].into_iter().collect());
+
+        PromiseResult::Successful(near_sdk::serde_json::to_vec(&Some(token)).unwrap())
+    }
+
+    fn payout_of(price: Balance) -> PromiseResult {
+        let payout = Payout {
+            payout: vec![(accounts(1), U128(price))].into_iter().collect(),
+        };
+
+        PromiseResult::Successful(near_sdk::serde_json::to_vec(&payout).unwrap())
+    }
+
+    /// bundle of accounts(1) for 2 NEAR
+    fn add_bundle(instance: &mut MarketFeature, token_ids: &[&str]) -> String {
+        let items: Vec<BundleItem> = token_ids
+            .iter()
+            .map(|token_id| bundle_item(token_id))
+            .collect();
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        instance.market_bundle_create(items.clone(), near_conditions(2 * NEAR));
+
+        resolve_with_all(
+            token_ids
+                .iter()
+                .map(|token_id| approved_token(token_id))
+                .collect()
+        );
+
+        instance.market_resolve_bundle_create(accounts(1), items, near_conditions(2 * NEAR)).bundle_id
+    }
+
+    /// accounts(2) pays for the bundle, returns price without fee
+    fn buy_bundle(instance: &mut MarketFeature, bundle_id: &String) -> Balance {
+        let fee = instance.market_fee(accounts(2), U128(2 * NEAR), None, None).0;
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.attached_deposit(2 * NEAR).build());
+
+        instance.market_bundle_offer(bundle_id.clone());
+
+        2 * NEAR - fee
+    }
+
+    #[test]
+    fn test_bundle_create() {
+        let mut instance = get_instance();
+
+        let bundle_id = add_bundle(&mut instance, &["1", "2"]);
+
+        let bundle = instance.market_bundle(bundle_id).unwrap();
+
+        assert_eq!(bundle.items.len(), 2);
+        assert_eq!(instance.market_supply_bundles_by_owner_id(accounts(1)).0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token already listed")]
+    fn test_bundle_token_sale() {
+        let mut instance = get_instance();
+
+        add_bundle(&mut instance, &["1", "2"]);
+
+        add_sale(&mut instance, "2", &sale_args(near_conditions(NEAR)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Token already listed")]
+    fn test_bundle_token_in_two_bundles() {
+        let mut instance = get_instance();
+
+        add_bundle(&mut instance, &["1", "2"]);
+        add_bundle(&mut instance, &["2", "3"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token already listed")]
+    fn test_bundle_token_listed_while_checked() {
+        let mut instance = get_instance();
+
+        let items = vec![bundle_item("1"), bundle_item("2")];
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        instance.market_bundle_create(items.clone(), near_conditions(2 * NEAR));
+
+        add_sale(&mut instance, "1", &sale_args(near_conditions(NEAR)));
+
+        resolve_with_all(vec![approved_token("1"), approved_token("2")]);
+
+        instance.market_resolve_bundle_create(accounts(1), items, near_conditions(2 * NEAR));
+    }
+
+    #[test]
+    fn test_bundle_remove() {
+        let mut instance = get_instance();
+
+        let bundle_id = add_bundle(&mut instance, &["1", "2"]);
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        instance.market_bundle_remove(bundle_id);
+
+        // tokens can be listed again
+        add_sale(&mut instance, "1", &sale_args(near_conditions(NEAR)));
+        add_bundle(&mut instance, &["2", "3"]);
+
+        assert_eq!(instance.market_supply_sales().0, 1);
+        assert_eq!(instance.market_supply_bundles().0, 1);
+    }
+
+    #[test]
+    fn test_bundle_purchase() {
+        let mut instance = get_instance();
+
+        let bundle_id = add_bundle(&mut instance, &["1", "2"]);
+        let bundle = instance.market_bundle(bundle_id.clone()).unwrap();
+        let price = buy_bundle(&mut instance, &bundle_id);
+
+        // taken out while the tokens are transferred
+        assert!(instance.market_bundle(bundle_id).is_none());
+
+        resolve_with_all(vec![payout_of(price / 2), payout_of(price / 2)]);
+
+        let refund = instance.market_resolve_bundle_purchase(
+            near_ft(),
+            accounts(2),
+            bundle,
+            U128(price)
+        );
+
+        assert_eq!(refund.0, 0);
+        assert_eq!(instance.market_fees_collected(near_ft()).0, 2 * NEAR - price);
+        assert_eq!(instance.market_treasury(near_ft()).0, 2 * NEAR - price);
+    }
+
+    #[test]
+    fn test_bundle_purchase_rollback() {
+        let mut instance = get_instance();
+
+        let bundle_id = add_bundle(&mut instance, &["1", "2"]);
+        let bundle = instance.market_bundle(bundle_id.clone()).unwrap();
+        let price = buy_bundle(&mut instance, &bundle_id);
+
+        // second token was not transferred, the first one goes back and the buyer is refunded
+        resolve_with_all(vec![payout_of(price / 2), PromiseResult::Failed]);
+
+        let refund = instance.market_resolve_bundle_purchase(
+            near_ft(),
+            accounts(2),
+            bundle,
+            U128(price)
+        );
+
+        assert_eq!(refund.0, 0);
+        assert_eq!(instance.market_fees_collected(near_ft()).0, 0);
+        assert_eq!(instance.market_treasury(near_ft()).0, 0);
+
+        // tokens are not listed anymore
+        add_sale(&mut instance, "1", &sale_args(near_conditions(NEAR)));
+    }
+
+    #[test]
+    fn test_bundle_transfer_claim() {
+        let mut instance = get_instance();
+
+        let tokens = vec![(nft(), "1".to_string()), (nft(), "2".to_string())];
+
+        // second token is left on market
+        resolve_with_all(vec![PromiseResult::Successful(vec![]), PromiseResult::Failed]);
+
+        instance.market_resolve_bundle_transfer(accounts(2), tokens);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+
+        instance.market_bundle_claim(nft(), "2".to_string());
+
+        // claim is taken while the token is transferred, failure puts it back
+        resolve_with(PromiseResult::Failed);
+
+        instance.market_resolve_bundle_transfer(accounts(2), vec![(nft(), "2".to_string())]);
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+
+        instance.market_bundle_claim(nft(), "2".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "No claim")]
+    fn test_bundle_claim_transferred() {
+        let mut instance = get_instance();
+
+        resolve_with_all(vec![PromiseResult::Successful(vec![]), PromiseResult::Failed]);
+
+        instance.market_resolve_bundle_transfer(
+            accounts(2),
+            vec![(nft(), "1".to_string()), (nft(), "2".to_string())]
+        );
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+
+        instance.market_bundle_claim(nft(), "1".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Must be claim receiver")]
+    fn test_bundle_claim_not_receiver() {
+        let mut instance = get_instance();
+
+        resolve_with(PromiseResult::Failed);
+
+        instance.market_resolve_bundle_transfer(accounts(2), vec![(nft(), "1".to_string())]);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        instance.market_bundle_claim(nft(), "1".to_string());
+    }
+
+    fn fee_of(instance: &MarketFeature, account_id: AccountId, ft_token_id: AccountId) -> Balance {
+        instance.market_fee(account_id, U128(100 * NEAR), Some(nft()), Some(ft_token_id)).0
+    }
+
+    #[test]
+    fn test_fee_tiers() {
+        let mut instance = get_instance();
+
+        let reputation = instance.reputation.as_mut().unwrap();
+        reputation.internal_add_reputation(&accounts(3), &50_001);
+        reputation.internal_add_reputation(&accounts(4), &100_000);
+
+        assert_eq!(fee_of(&instance, accounts(2), near_ft()), 3 * NEAR);
+        assert_eq!(fee_of(&instance, accounts(3), near_ft()), 2 * NEAR);
+        assert_eq!(fee_of(&instance, accounts(4), near_ft()), NEAR);
+
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        instance.market_set_fee_tiers(
+            vec![FeeTier { reputation: 0, fee: 500 }, FeeTier { reputation: 100_000, fee: 0 }]
+        );
+
+        assert_eq!(fee_of(&instance, accounts(3), near_ft()), 5 * NEAR);
+        assert_eq!(fee_of(&instance, accounts(4), near_ft()), 0);
+    }
+
+    #[test]
+    fn test_fee_override_priority() {
+        let mut instance = get_instance();
+
+        instance.reputation.as_mut().unwrap().internal_add_reputation(&accounts(4), &100_000);
+
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        // ft token override goes before reputation
+        instance.market_set_ft_token_fee(ft(), Some(400));
+
+        assert_eq!(fee_of(&instance, accounts(4), ft()), 4 * NEAR);
+        assert_eq!(fee_of(&instance, accounts(4), near_ft()), NEAR);
+
+        // nft contract override goes before ft token override
+        instance.market_set_nft_contract_fee(nft(), Some(50));
+
+        assert_eq!(fee_of(&instance, accounts(4), ft()), NEAR / 2);
+        assert_eq!(fee_of(&instance, accounts(4), near_ft()), NEAR / 2);
+
+        // removed overrides fall back
+        instance.market_set_nft_contract_fee(nft(), None);
+
+        assert_eq!(fee_of(&instance, accounts(4), ft()), 4 * NEAR);
+
+        instance.market_set_ft_token_fee(ft(), None);
+
+        assert_eq!(fee_of(&instance, accounts(4), ft()), NEAR);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee can't be more than 1000")]
+    fn test_fee_tier_max() {
+        let mut instance = get_instance();
+
+        instance.market_set_fee_tiers(vec![FeeTier { reputation: 0, fee: 1_001 }]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee can't be more than 1000")]
+    fn test_fee_nft_contract_max() {
+        let mut instance = get_instance();
+
+        instance.market_set_nft_contract_fee(nft(), Some(10_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee can't be more than 1000")]
+    fn test_fee_ft_token_max() {
+        let mut instance = get_instance();
+
+        instance.market_set_ft_token_fee(ft(), Some(10_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tiers must be sorted by reputation")]
+    fn test_fee_tiers_unsorted() {
+        let mut instance = get_instance();
+
+        instance.market_set_fee_tiers(
+            vec![
+                FeeTier { reputation: 0, fee: 300 },
+                FeeTier { reputation: 100, fee: 100 },
+                FeeTier { reputation: 50, fee: 200 }
+            ]
+        );
+    }
+
+    fn ft_conditions(price: Balance) -> SaleConditions {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft(), U128(price));
+        sale_conditions
+    }
+
+    fn ft_transfer(instance: &mut MarketFeature, amount: Balance) {
+        let mut context = get_context(ft());
+        testing_env!(context.build());
+
+        let args = MarketOnFtTransferArgs {
+            nft_contract_id: nft(),
+            token_id: "1".to_string(),
+        };
+
+        instance.internal_on_ft_transfer(&args, &ft(), &U128(amount), &accounts(2));
+    }
+
+    /// price and fee passed to the purchase resolver
+    fn resolve_purchase_args() -> (U128, U128) {
+        let args = get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .find_map(|action| {
+                match action {
+                    VmAction::FunctionCall { function_name, args, .. } if
+                        function_name == "market_resolve_purchase"
+                    => Some(args),
+                    _ => None,
+                }
+            })
+            .expect("No purchase");
+        let args: near_sdk::serde_json::Value = near_sdk::serde_json::from_slice(&args).unwrap();
+
+        (
+            near_sdk::serde_json::from_value(args["price"].clone()).unwrap(),
+            near_sdk::serde_json::from_value(args["fee"].clone()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_ft_purchase_fee() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &sale_args(ft_conditions(100 * NEAR)));
+
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+
+        // buyer pays the price, fee is taken from it once
+        ft_transfer(&mut instance, 100 * NEAR);
+
+        assert!(instance.market_sale(nft(), "1".to_string()).is_none());
+        assert_eq!(resolve_purchase_args(), (U128(97 * NEAR), U128(3 * NEAR)));
+
+        resolve_with(payout_of(97 * NEAR));
+
+        let refund = instance.market_resolve_purchase(
+            ft(),
+            accounts(2),
+            sale,
+            U128(97 * NEAR),
+            U128(3 * NEAR)
+        );
+
+        assert_eq!(refund.0, 0);
+        assert_eq!(instance.market_treasury(ft()).0, 3 * NEAR);
+        assert_eq!(instance.market_fees_collected(ft()).0, 3 * NEAR);
+        assert_eq!(
+            instance.market_fees_collected_by_nft_contract_id(nft()).get(&ft()),
+            Some(&U128(3 * NEAR))
+        );
+    }
+
+    #[test]
+    fn test_ft_purchase_failed() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &sale_args(ft_conditions(100 * NEAR)));
+
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+
+        ft_transfer(&mut instance, 100 * NEAR);
+
+        resolve_with(PromiseResult::Failed);
+
+        // everything is returned in ft_resolve_transfer, no fee is recorded
+        let refund = instance.market_resolve_purchase(
+            ft(),
+            accounts(2),
+            sale,
+            U128(97 * NEAR),
+            U128(3 * NEAR)
+        );
+
+        assert_eq!(refund.0, 100 * NEAR);
+        assert_eq!(instance.market_treasury(ft()).0, 0);
+        assert_eq!(instance.market_fees_collected(ft()).0, 0);
+    }
+
+    #[test]
+    fn test_ft_purchase_over_price() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &sale_args(ft_conditions(100 * NEAR)));
+
+        // amount other than the price is an offer, nothing is withheld
+        ft_transfer(&mut instance, 103 * NEAR);
+
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+        let bid = sale.bids.get(&ft()).unwrap().last().unwrap().clone();
+
+        assert_eq!(bid.price, U128(103 * NEAR));
+    }
+
+    #[test]
+    fn test_treasury_withdraw() {
+        let mut instance = get_instance();
+
+        instance.internal_collect_fee(&near_ft(), &nft(), 3 * NEAR);
+
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        // taken before the transfer
+        instance.market_treasury_withdraw(near_ft(), U128(2 * NEAR));
+
+        assert_eq!(instance.market_treasury(near_ft()).0, NEAR);
+
+        resolve_with(PromiseResult::Successful(vec![]));
+
+        assert!(instance.market_resolve_treasury_withdraw(near_ft(), accounts(0), U128(2 * NEAR)));
+        assert_eq!(instance.market_treasury(near_ft()).0, NEAR);
+        // collected fees are a history, withdrawals don't change them
+        assert_eq!(instance.market_fees_collected(near_ft()).0, 3 * NEAR);
+    }
+
+    #[test]
+    fn test_treasury_withdraw_rollback() {
+        let mut instance = get_instance();
+
+        instance.internal_collect_fee(&ft(), &nft(), 3 * NEAR);
+
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        instance.market_treasury_withdraw(ft(), U128(3 * NEAR));
+
+        assert_eq!(instance.market_treasury(ft()).0, 0);
+
+        // failed transfer puts the balance back
+        resolve_with(PromiseResult::Failed);
+
+        assert!(!instance.market_resolve_treasury_withdraw(ft(), accounts(0), U128(3 * NEAR)));
+        assert_eq!(instance.market_treasury(ft()).0, 3 * NEAR);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough funds in treasury")]
+    fn test_treasury_withdraw_too_much() {
+        let mut instance = get_instance();
+
+        instance.internal_collect_fee(&near_ft(), &nft(), 3 * NEAR);
+
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        instance.market_treasury_withdraw(near_ft(), U128(3 * NEAR + 1));
+    }
+
+    /// sales "1" and "2" expire in an hour and in two hours, "3" doesn't expire
+    fn add_expiring_sales(instance: &mut MarketFeature) {
+        for (token_id, expires_at) in [("1", Some(HOUR)), ("2", Some(2 * HOUR)), ("3", None)] {
+            let mut args = sale_args(near_conditions(NEAR));
+            args.expires_at = expires_at;
+
+            add_sale(instance, token_id, &args);
+        }
+    }
+
+    fn owner_sales(instance: &MarketFeature, from_index: u64, limit: u64) -> Vec<String> {
+        instance
+            .market_sales_by_owner_id(accounts(1), U64(from_index), limit, Some(true))
+            .into_iter()
+            .map(|sale| sale.token_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_remove_expired_sales() {
+        let mut instance = get_instance();
+
+        add_expiring_sales(&mut instance);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        assert_eq!(instance.market_remove_expired_sales(10), 1);
+        assert!(instance.market_sale(nft(), "1".to_string()).is_none());
+        assert_eq!(instance.market_supply_sales().0, 2);
+
+        // nothing else is expired yet
+        assert_eq!(instance.market_remove_expired_sales(10), 0);
+    }
+
+    #[test]
+    fn test_remove_expired_sales_limit() {
+        let mut instance = get_instance();
+
+        add_expiring_sales(&mut instance);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(3 * HOUR).build());
+
+        assert_eq!(instance.market_remove_expired_sales(1), 1);
+        assert!(instance.market_sale(nft(), "1".to_string()).is_none());
+
+        assert_eq!(instance.market_remove_expired_sales(1), 1);
+        assert_eq!(instance.market_remove_expired_sales(1), 0);
+
+        assert_eq!(instance.market_supply_sales().0, 1);
+        assert!(instance.market_sale(nft(), "3".to_string()).is_some());
+    }
+
+    #[test]
+    fn test_hide_expired_sales() {
+        let mut instance = get_instance();
+
+        add_expiring_sales(&mut instance);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        assert_eq!(owner_sales(&instance, 0, 10), vec!["2", "3"]);
+        // paging goes over the index, the expired sale shortens the first page
+        assert_eq!(owner_sales(&instance, 0, 2), vec!["2"]);
+        assert_eq!(owner_sales(&instance, 2, 2), vec!["3"]);
+
+        let sales = instance.market_sales_by_owner_id(accounts(1), U64(0), 10, None);
+
+        assert_eq!(sales.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sale is expired")]
+    fn test_expired_sale_purchase() {
+        let mut instance = get_instance();
+
+        add_expiring_sales(&mut instance);
+
+        bid(&mut instance, accounts(2), NEAR, HOUR);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sale expiration must be in the future")]
+    fn test_sale_expires_in_past() {
+        let mut instance = get_instance();
+
+        let mut args = sale_args(near_conditions(NEAR));
+        args.expires_at = Some(HOUR);
+
+        let mut context = get_context(nft());
+        testing_env!(context.block_timestamp(HOUR).build());
+
+        instance.internal_on_nft_approve(&args, &nft(), &"1".to_string(), &accounts(1), &1);
+    }
+
+    /// token "n" for n NEAR listed at n minutes
+    fn add_priced_sales(instance: &mut MarketFeature, prices: &[u64]) {
+        for price in prices {
+            add_sale_at(
+                instance,
+                &price.to_string(),
+                &sale_args(near_conditions((*price as u128) * NEAR)),
+                *price * MINUTE
+            );
+        }
+    }
+
+    fn price_filter(min_price: Option<Balance>, max_price: Option<Balance>) -> SaleFilter {
+        SaleFilter {
+            ft_token_id: Some(near_ft()),
+            min_price: min_price.map(U128),
+            max_price: max_price.map(U128),
+            ..Default::default()
+        }
+    }
+
+    fn token_ids(page: &SalesPage) -> Vec<String> {
+        page.sales
+            .iter()
+            .map(|sale| sale.token_id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_sales_price_cursor() {
+        let mut instance = get_instance();
+
+        add_priced_sales(&mut instance, &[3, 1, 5, 2, 4]);
+
+        let filter = Some(price_filter(None, None));
+
+        let page = instance.market_sales(filter.clone(), Some(SaleSort::PriceAsc), None, 2);
+
+        assert_eq!(token_ids(&page), vec!["1", "2"]);
+
+        let page = instance.market_sales(filter.clone(), Some(SaleSort::PriceAsc), page.next_cursor, 2);
+
+        assert_eq!(token_ids(&page), vec!["3", "4"]);
+
+        let page = instance.market_sales(filter.clone(), Some(SaleSort::PriceAsc), page.next_cursor, 2);
+
+        assert_eq!(token_ids(&page), vec!["5"]);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_sales_price_desc_range() {
+        let mut instance = get_instance();
+
+        add_priced_sales(&mut instance, &[3, 1, 5, 2, 4]);
+
+        let filter = Some(price_filter(Some(2 * NEAR), Some(4 * NEAR)));
+
+        let page = instance.market_sales(filter.clone(), Some(SaleSort::PriceDesc), None, 2);
+
+        assert_eq!(token_ids(&page), vec!["4", "3"]);
+
+        let page = instance.market_sales(filter, Some(SaleSort::PriceDesc), page.next_cursor, 2);
+
+        assert_eq!(token_ids(&page), vec!["2"]);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_sales_created_at_cursor() {
+        let mut instance = get_instance();
+
+        add_priced_sales(&mut instance, &[3, 1, 5, 2, 4]);
+
+        // newest first by default
+        let page = instance.market_sales(None, None, None, 3);
+
+        assert_eq!(token_ids(&page), vec!["5", "4", "3"]);
+
+        let page = instance.market_sales(None, None, page.next_cursor, 3);
+
+        assert_eq!(token_ids(&page), vec!["2", "1"]);
+        assert!(page.next_cursor.is_none());
+
+        let page = instance.market_sales(None, Some(SaleSort::CreatedAtAsc), None, 2);
+
+        assert_eq!(token_ids(&page), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_sales_filter() {
+        let mut instance = get_instance();
+
+        add_auction(&mut instance);
+        add_priced_sales(&mut instance, &[2]);
+
+        let filter = SaleFilter {
+            is_auction: Some(true),
+            ..Default::default()
+        };
+
+        let page = instance.market_sales(Some(filter), None, None, 10);
+
+        assert_eq!(token_ids(&page), vec!["1"]);
+
+        let filter = SaleFilter {
+            is_auction: Some(false),
+            ..Default::default()
+        };
+
+        let page = instance.market_sales(Some(filter), None, None, 10);
+
+        assert_eq!(token_ids(&page), vec!["2"]);
+    }
+
+    #[test]
+    fn test_sales_dutch_current_price() {
+        let mut instance = get_instance();
+
+        add_priced_sales(&mut instance, &[5, 7]);
+
+        let mut args = sale_args(near_conditions(10 * NEAR));
+        args.dutch_auction = Some(dutch_auction(PriceDecay::Linear));
+
+        add_sale_at(&mut instance, "dutch", &args, 10 * MINUTE);
+
+        // current price of the dutch auction is 6 NEAR
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(3 * HOUR).build());
+
+        // dutch auctions are not in the price index, its start price would put it last
+        let filter = Some(price_filter(None, Some(10 * NEAR)));
+        let page = instance.market_sales(filter, Some(SaleSort::PriceAsc), None, 10);
+
+        assert_eq!(token_ids(&page), vec!["5", "7"]);
+
+        // and are matched by their current price otherwise
+        let filter = Some(price_filter(None, Some(6 * NEAR)));
+        let page = instance.market_sales(filter, None, None, 10);
+
+        assert_eq!(token_ids(&page), vec!["dutch", "5"]);
+        assert_eq!(page.sales[0].sale_conditions.get(&near_ft()), Some(&U128(6 * NEAR)));
+    }
+
+    #[test]
+    fn test_sales_dutch_removed() {
+        let mut instance = get_instance();
+
+        let mut args = sale_args(near_conditions(10 * NEAR));
+        args.dutch_auction = Some(dutch_auction(PriceDecay::Linear));
+
+        add_sale(&mut instance, "1", &args);
+
+        bid(&mut instance, accounts(2), 10 * NEAR, 2 * HOUR);
+
+        let page = instance.market_sales(Some(price_filter(None, None)), None, None, 10);
+
+        assert!(page.sales.is_empty());
+        assert!(instance.sales_by_price.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Sorting by price requires ft_token_id")]
+    fn test_sales_price_sort_without_token() {
+        let instance = get_instance();
+
+        instance.market_sales(None, Some(SaleSort::PriceAsc), None, 10);
+    }
+
+    fn private_sale_args(buyers: Vec<AccountId>) -> MarketOnNftApproveArgs {
+        let mut args = sale_args(near_conditions(NEAR));
+        args.buyers = Some(buyers.into_iter().collect::<HashSet<_>>());
+        args
+    }
+
+    #[test]
+    fn test_private_sale_supply() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &private_sale_args(vec![accounts(2)]));
+
+        assert_eq!(instance.market_supply_sales().0, 0);
+        assert_eq!(instance.market_supply_by_owner_id(accounts(1)).0, 0);
+        assert_eq!(instance.market_supply_by_nft_contract_id(nft()).0, 0);
+
+        add_sale(&mut instance, "2", &sale_args(near_conditions(NEAR)));
+
+        assert_eq!(instance.market_supply_sales().0, 1);
+        assert_eq!(instance.market_supply_by_owner_id(accounts(1)).0, 1);
+        assert_eq!(instance.market_supply_by_nft_contract_id(nft()).0, 1);
+
+        // list views agree with supply views
+        let sales = instance.market_sales_by_owner_id(accounts(1), U64(0), 10, None);
+
+        assert_eq!(sales.len(), 1);
+        assert_eq!(sales[0].token_id, "2");
+
+        let sales = instance.market_sales_by_nft_contract_id(nft(), U64(0), 10, None);
+
+        assert_eq!(sales.len(), 1);
+        assert_eq!(token_ids(&instance.market_sales(None, None, None, 10)), vec!["2"]);
+    }
+
+    #[test]
+    fn test_private_sale_by_buyer_id() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &private_sale_args(vec![accounts(2), accounts(3)]));
+
+        assert_eq!(instance.market_supply_private_sales_by_buyer_id(accounts(2)).0, 1);
+        assert_eq!(instance.market_supply_private_sales_by_buyer_id(accounts(3)).0, 1);
+        assert_eq!(instance.market_supply_private_sales_by_buyer_id(accounts(4)).0, 0);
+
+        let sales = instance.market_private_sales_by_buyer_id(accounts(2), U64(0), 10, None);
+
+        assert_eq!(sales.len(), 1);
+        assert_eq!(sales[0].token_id, "1");
+    }
+
+    #[test]
+    fn test_private_sale_purchase() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &private_sale_args(vec![accounts(2)]));
+        add_sale(&mut instance, "2", &sale_args(near_conditions(NEAR)));
+
+        bid(&mut instance, accounts(2), NEAR, MINUTE);
+
+        assert!(instance.market_sale(nft(), "1".to_string()).is_none());
+        assert_eq!(instance.market_supply_private_sales_by_buyer_id(accounts(2)).0, 0);
+        assert_eq!(instance.market_supply_sales().0, 1);
+        assert_eq!(instance.market_supply_by_owner_id(accounts(1)).0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Sale is private")]
+    fn test_private_sale_not_allowed() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &private_sale_args(vec![accounts(2)]));
+
+        bid(&mut instance, accounts(3), NEAR, MINUTE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot sell to yourself")]
+    fn test_private_sale_to_owner() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &private_sale_args(vec![accounts(1)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Private sale must have buyers")]
+    fn test_private_sale_no_buyers() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &private_sale_args(vec![]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Private sale can't have more than 10 buyers")]
+    fn test_private_sale_too_many_buyers() {
+        let mut instance = get_instance();
+
+        let buyers = (0..11)
+            .map(|index| AccountId::new_unchecked(format!("buyer{}", index)))
+            .collect();
+
+        add_sale(&mut instance, "1", &private_sale_args(buyers));
+    }
+
+    fn failed(failures: &[SaleBatchFailure]) -> Vec<(String, String)> {
+        failures
+            .iter()
+            .map(|failure| (failure.token_id.clone(), failure.error.clone()))
+            .collect()
+    }
+
+    fn sale_item(token_id: &str) -> SaleItem {
+        SaleItem {
+            nft_contract_id: nft(),
+            token_id: token_id.to_string(),
+        }
+    }
+
+    fn sale_update(token_id: &str, sale_conditions: SaleConditions) -> SaleUpdate {
+        SaleUpdate {
+            nft_contract_id: nft(),
+            token_id: token_id.to_string(),
+            sale_conditions,
+        }
+    }
+
+    fn sale_listing(token_id: &str, sale_conditions: SaleConditions) -> SaleListing {
+        SaleListing {
+            nft_contract_id: nft(),
+            token_id: token_id.to_string(),
+            approval_id: 1,
+            sale_conditions,
+        }
+    }
+
+    #[test]
+    fn test_update_batch_failures() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &sale_args(near_conditions(NEAR)));
+        add_sale(&mut instance, "2", &sale_args(near_conditions(NEAR)));
+
+        let mut args = sale_args(near_conditions(10 * NEAR));
+        args.dutch_auction = Some(dutch_auction(PriceDecay::Linear));
+
+        add_sale(&mut instance, "3", &args);
+
+        let mut unsupported = HashMap::new();
+        unsupported.insert(AccountId::new_unchecked("unknown".to_string()), U128(NEAR));
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let failures = instance.market_update_batch(
+            vec![
+                sale_update("1", near_conditions(2 * NEAR)),
+                sale_update("2", HashMap::new()),
+                sale_update("3", near_conditions(2 * NEAR)),
+                sale_update("4", near_conditions(2 * NEAR)),
+                sale_update("2", unsupported)
+            ]
+        );
+
+        assert_eq!(
+            failed(&failures),
+            vec![
+                ("2".to_string(), "Sale conditions are empty".to_string()),
+                ("3".to_string(), "Cannot update price of dutch auction".to_string()),
+                ("4".to_string(), "No sale".to_string()),
+                ("2".to_string(), "Token unknown not supported by this market".to_string())
+            ]
+        );
+
+        let sale = instance.market_sale(nft(), "1".to_string()).unwrap();
+
+        assert_eq!(sale.sale_conditions.get(&near_ft()), Some(&U128(2 * NEAR)));
+
+        let sale = instance.market_sale(nft(), "2".to_string()).unwrap();
+
+        assert_eq!(sale.sale_conditions, near_conditions(NEAR));
+    }
+
+    #[test]
+    fn test_update_batch_not_owner() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "1", &sale_args(near_conditions(NEAR)));
+
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+
+        let failures = instance.market_update_batch(vec![sale_update("1", near_conditions(2 * NEAR))]);
+
+        assert_eq!(failed(&failures), vec![("1".to_string(), "Must be sale owner".to_string())]);
+    }
+
+    #[test]
+    fn test_remove_batch_failures() {
+        let mut instance = get_instance();
+
+        add_auction(&mut instance);
+        add_sale(&mut instance, "2", &sale_args(near_conditions(NEAR)));
+
+        bid(&mut instance, accounts(2), NEAR, MINUTE);
+
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let failures = instance.market_remove_batch(
+            vec![sale_item("1"), sale_item("2"), sale_item("3")]
+        );
+
+        assert_eq!(
+            failed(&failures),
+            vec![
+                ("1".to_string(), "Cannot remove auction with bids".to_string()),
+                ("3".to_string(), "Not found sale".to_string())
+            ]
+        );
+        assert!(instance.market_sale(nft(), "1".to_string()).is_some());
+        assert!(instance.market_sale(nft(), "2".to_string()).is_none());
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.build());
+
+        let failures = instance.market_remove_batch(vec![sale_item("1")]);
+
+        assert_eq!(failed(&failures), vec![("1".to_string(), "Must be sale owner".to_string())]);
+    }
+
+    #[test]
+    fn test_list_batch_failures() {
+        let mut instance = get_instance();
+
+        add_sale(&mut instance, "2", &sale_args(near_conditions(NEAR)));
+
+        let mut not_owned = token(accounts(3), None, None);
+        not_owned.token_id = "3".to_string();
+        not_owned.approved_account_ids = Some(vec![(accounts(0), 1)].into_iter().collect());
+
+        let mut not_approved = token(accounts(1), None, None);
+        not_approved.token_id = "4".to_string();
+        not_approved.approved_account_ids = Some(vec![(accounts(0), 2)].into_iter().collect());
+
+        resolve_with_all(
+            vec![
+                approved_token("1"),
+                approved_token("2"),
+                PromiseResult::Successful(near_sdk::serde_json::to_vec(&Some(not_owned)).unwrap()),
+                PromiseResult::Successful(near_sdk::serde_json::to_vec(&Some(not_approved)).unwrap()),
+                approved_token("5"),
+                PromiseResult::Failed
+            ]
+        );
+
+        let failures = instance.market_resolve_list_batch(
+            accounts(1),
+            vec![
+                sale_listing("1", near_conditions(NEAR)),
+                sale_listing("2", near_conditions(NEAR)),
+                sale_listing("3", near_conditions(NEAR)),
+                sale_listing("4", near_conditions(NEAR)),
+                sale_listing("5", HashMap::new()),
+                sale_listing("6", near_conditions(NEAR))
+            ]
+        );
+
+        assert_eq!(
+            failed(&failures),
+            vec![
+                ("2".to_string(), "Token already listed".to_string()),
+                ("3".to_string(), "Must be token owner".to_string()),
+                ("4".to_string(), "Token is not approved for market".to_string()),
+                ("5".to_string(), "Sale conditions are empty".to_string()),
+                ("6".to_string(), "No token".to_string())
+            ]
+        );
+        assert!(instance.market_sale(nft(), "1".to_string()).is_some());
+        assert_eq!(instance.market_supply_sales().0, 2);
+    }
+}
@@ -0,0 +1,15 @@
+use near_sdk::json_types::U128;
+use near_sdk::{ ext_contract, AccountId, Gas };
+
+pub const GAS_FOR_MT_TRANSFER: Gas = Gas(25_000_000_000_000);
+
+#[ext_contract(ext_mt)]
+trait ExtMultiFungibleToken {
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<AccountId>,
+        amounts: Vec<U128>,
+        memo: Option<String>
+    );
+}
@@ -3,6 +3,7 @@ pub use macros::*;
 
 pub use self::core::MultiFungibleTokenCore;
 pub use self::events::*;
+pub use self::external::{ ext_mt, GAS_FOR_MT_TRANSFER };
 pub use self::metadata::*;
 pub use self::receiver::MultiFungibleTokenReceiver;
 pub use self::resolver::MultiFungibleTokenResolver;
@@ -10,6 +11,7 @@ pub use self::resolver::MultiFungibleTokenResolver;
 pub mod core;
 pub mod core_impl;
 pub mod events;
+pub mod external;
 pub mod macros;
 pub mod metadata;
 pub mod receiver;
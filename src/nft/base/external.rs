@@ -1,4 +1,4 @@
-use crate::nft::TokenId;
+use crate::nft::{ TokenId, Token };
 use near_sdk::json_types::U128;
 use near_sdk::{ext_contract, AccountId};
 
@@ -20,4 +20,5 @@ trait ExtNftReceiver {
         max_len_payout: u32,
         memo: Option<String>,
     );
+    fn nft_token(&self, token_id: TokenId) -> Option<Token>;
 }
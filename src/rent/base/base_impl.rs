@@ -1,14 +1,27 @@
-use near_sdk::{ AccountId, env, IntoStorageKey, ext_contract, Gas, Promise, is_promise_success };
+use std::collections::HashMap;
+use near_sdk::{ PromiseOrValue, AccountId, env, IntoStorageKey, ext_contract, Gas, Promise, is_promise_success };
 use near_sdk::json_types::{ U128 };
 use near_sdk::collections::{ LookupMap, UnorderedSet, TreeMap };
 use near_sdk::borsh::{ self, BorshDeserialize, BorshSerialize };
-use crate::rent::events::{ RentClaim };
-use crate::rent::{ TokenId, contract_token_id, RentFeatureResolve, RentFeatureCore, Rent };
-use crate::nft::base::external::ext_nft;
+use crate::rent::events::{ RentTerminateCancel, RentCollateralClaim };
+use crate::rent::{
+    TokenId,
+    contract_token_id,
+    RentFeatureResolve,
+    RentFeatureCore,
+    Rent,
+    RentPayment,
+    RentTermination,
+    RentEarnings,
+    PriceTier,
+    RentRecord,
+    RentStats,
+    RentBooking,
+    RentSublet,
+};
 use crate::utils::near_ft;
 
 pub const GAS_FOR_RENT_PAY: Gas = Gas(60_000_000_000_000);
-const GAS_FOR_NFT_TRANSFER: Gas = Gas(15_000_000_000_000);
 const GAS_FOR_RENT_CLAIM: Gas = Gas(60_000_000_000_000);
 
 #[ext_contract(ext_self)]
@@ -31,6 +44,14 @@ trait ExtSelf {
         owner_id: AccountId,
         renter_id: AccountId
     );
+    fn rent_resolve_claim_expired(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        renter_id: AccountId,
+        keeper_id: AccountId
+    );
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -51,10 +72,37 @@ pub struct RentFeature {
     pub rent_tokens_per_account: LookupMap<AccountId, UnorderedSet<TokenId>>,
     //
     pub rent_tokens_by_contract: TreeMap<AccountId, UnorderedSet<TokenId>>,
+    // payment info of paid rents
+    pub rents_payment_by_id: LookupMap<TokenId, RentPayment>,
+    // proposed early terminations
+    pub rents_termination_by_id: LookupMap<TokenId, RentTermination>,
+    // revenue share earnings of current rents
+    pub rents_earnings_by_id: LookupMap<TokenId, RentEarnings>,
+    // not claimed earnings per account
+    pub earnings_per_account: LookupMap<AccountId, HashMap<AccountId, U128>>,
+    // paid rents ordered by end time
+    pub rents_by_end_at: TreeMap<(u64, TokenId), ()>,
+    // reward per claimed rent for `rent_claim_expired` callers
+    pub keeper_reward: u128,
+    pub keeper_pool: u128,
+    // last ended rents per token and per renter
+    pub rent_history_by_id: LookupMap<TokenId, Vec<RentRecord>>,
+    pub rent_history_per_account: LookupMap<AccountId, Vec<RentRecord>>,
+    pub rent_stats_by_id: LookupMap<TokenId, RentStats>,
+    // future rents per token ordered by start
+    pub rent_bookings_by_id: LookupMap<TokenId, Vec<RentBooking>>,
+    // remaining time of paid rents listed by renters
+    pub rent_sublets_by_id: LookupMap<TokenId, RentSublet>,
+    // ended rents with the asset kept by the contract for the bookings
+    pub rents_kept: UnorderedSet<TokenId>,
+    // last listing number of the mt token, listing ids are never reused
+    pub rent_mt_nonce_by_id: LookupMap<TokenId, u64>,
+    // removed rents with the asset on the way back to the owner
+    pub rents_removed_by_id: LookupMap<TokenId, Rent>,
 }
 
 impl RentFeature {
-    pub fn new<R0, R1, R2, R3, R4, R5, R6, R7>(
+    pub fn new<R0, R1, R2, R3, R4, R5, R6, R7, R8, R9, R10, R11, R12, R13, R14, R15, R16, R17, R18, R19, R20>(
         approved_owner_prefix: Option<R0>,
         rents_current_prefix: R1,
         rents_pending_prefix: R2,
@@ -62,7 +110,20 @@ impl RentFeature {
         rent_tokens_per_account_prefix: R4,
         rents_per_account_prefix: R5,
         rents_at_prefix: R6,
-        rent_tokens_by_contract_prefix: R7
+        rent_tokens_by_contract_prefix: R7,
+        rents_payment_prefix: R8,
+        rents_termination_prefix: R9,
+        rents_earnings_prefix: R10,
+        earnings_per_account_prefix: R11,
+        rents_by_end_at_prefix: R12,
+        rent_history_prefix: R13,
+        rent_history_per_account_prefix: R14,
+        rent_stats_prefix: R15,
+        rent_bookings_prefix: R16,
+        rent_sublets_prefix: R17,
+        rents_kept_prefix: R18,
+        rent_mt_nonce_prefix: R19,
+        rents_removed_prefix: R20
     )
         -> Self
         where
@@ -73,7 +134,20 @@ impl RentFeature {
             R4: IntoStorageKey,
             R5: IntoStorageKey,
             R6: IntoStorageKey,
-            R7: IntoStorageKey
+            R7: IntoStorageKey,
+            R8: IntoStorageKey,
+            R9: IntoStorageKey,
+            R10: IntoStorageKey,
+            R11: IntoStorageKey,
+            R12: IntoStorageKey,
+            R13: IntoStorageKey,
+            R14: IntoStorageKey,
+            R15: IntoStorageKey,
+            R16: IntoStorageKey,
+            R17: IntoStorageKey,
+            R18: IntoStorageKey,
+            R19: IntoStorageKey,
+            R20: IntoStorageKey
     {
         let this = Self {
             approved_owner_by_id: approved_owner_prefix.map(LookupMap::new),
@@ -84,10 +158,98 @@ impl RentFeature {
             rents_per_account: TreeMap::new(rents_per_account_prefix),
             rents_end_by_id: LookupMap::new(rents_at_prefix),
             rent_tokens_by_contract: TreeMap::new(rent_tokens_by_contract_prefix),
+            rents_payment_by_id: LookupMap::new(rents_payment_prefix),
+            rents_termination_by_id: LookupMap::new(rents_termination_prefix),
+            rents_earnings_by_id: LookupMap::new(rents_earnings_prefix),
+            earnings_per_account: LookupMap::new(earnings_per_account_prefix),
+            rents_by_end_at: TreeMap::new(rents_by_end_at_prefix),
+            keeper_reward: 0,
+            keeper_pool: 0,
+            rent_history_by_id: LookupMap::new(rent_history_prefix),
+            rent_history_per_account: LookupMap::new(rent_history_per_account_prefix),
+            rent_stats_by_id: LookupMap::new(rent_stats_prefix),
+            rent_bookings_by_id: LookupMap::new(rent_bookings_prefix),
+            rent_sublets_by_id: LookupMap::new(rent_sublets_prefix),
+            rents_kept: UnorderedSet::new(rents_kept_prefix),
+            rent_mt_nonce_by_id: LookupMap::new(rent_mt_nonce_prefix),
+            rents_removed_by_id: LookupMap::new(rents_removed_prefix),
         };
 
         this
     }
+
+    pub(crate) fn internal_rent_claim(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        renter_id: &AccountId,
+        keeper_id: &Option<AccountId>,
+        resolve_gas: Gas
+    ) -> Option<Promise> {
+        let id = contract_token_id(&contract_id, &token_id);
+
+        // not returned collateralized nft, the owner takes the collateral
+        if let Some(payment) = self.rents_payment_by_id.get(&id) {
+            if payment.collateral.0 > 0 {
+                self.internal_remove_paid_rent(&owner_id, &renter_id, &contract_id, &token_id);
+
+                (RentCollateralClaim {
+                    token_id: &token_id,
+                    contract_id: &contract_id,
+                    owner_id: &owner_id,
+                    renter_id: &renter_id,
+                    ft_token_id: &payment.ft_token_id,
+                    collateral: &payment.collateral,
+                }).emit();
+
+                if let Some(keeper_id) = keeper_id {
+                    self.internal_pay_keeper_reward(&keeper_id);
+                }
+
+                return Some(
+                    self.internal_rent_transfer_promise(
+                        &payment.ft_token_id,
+                        &owner_id,
+                        &payment.collateral
+                    )
+                );
+            }
+        }
+
+        if self.internal_keep_rent_asset(&id) {
+            self.internal_claim_rent(&contract_id, &token_id, &owner_id, &renter_id);
+
+            if let Some(keeper_id) = keeper_id {
+                self.internal_pay_keeper_reward(&keeper_id);
+            }
+
+            return None;
+        }
+
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+        let resolve = ext_self::ext(env::current_account_id()).with_static_gas(resolve_gas);
+
+        // keeper is rewarded only for the returned asset
+        let resolve = if let Some(keeper_id) = keeper_id {
+            resolve.rent_resolve_claim_expired(
+                contract_id.clone(),
+                token_id.clone(),
+                owner_id.clone(),
+                renter_id.clone(),
+                keeper_id.clone()
+            )
+        } else {
+            resolve.rent_resolve_claim(
+                contract_id.clone(),
+                token_id.clone(),
+                owner_id.clone(),
+                renter_id.clone()
+            )
+        };
+
+        Some(self.internal_rent_asset_transfer(&rent, &owner_id).then(resolve))
+    }
 }
 
 impl RentFeatureCore for RentFeature {
@@ -121,6 +283,26 @@ impl RentFeatureCore for RentFeature {
         );
     }
 
+    fn rent_update_price_tiers(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        ft_token_id: AccountId,
+        price_tiers: Vec<PriceTier>
+    ) {
+        let account_id = env::predecessor_account_id();
+
+        self.assert_approved(&contract_id, &token_id);
+
+        self.internal_rent_update_price_tiers(
+            &contract_id,
+            &token_id,
+            &account_id,
+            &ft_token_id,
+            &price_tiers
+        );
+    }
+
     fn rent_remove(&mut self, contract_id: AccountId, token_id: TokenId) {
         let account_id = env::predecessor_account_id();
 
@@ -134,22 +316,24 @@ impl RentFeatureCore for RentFeature {
         contract_id: AccountId,
         token_id: TokenId,
         time: u64,
-        receiver_id: AccountId
-    ) -> Promise {
+        receiver_id: AccountId,
+        starts_at: Option<u64>
+    ) -> PromiseOrValue<U128> {
         let deposit = env::attached_deposit();
 
-        self.internal_process_purchase(
+        self.internal_rent_pay(
             &contract_id,
             &token_id,
             &env::predecessor_account_id(),
             &receiver_id,
             &time,
             &near_ft(),
-            &U128::from(deposit)
+            &U128::from(deposit),
+            &starts_at
         )
     }
 
-    fn rent_claim(&mut self, contract_id: AccountId, token_id: TokenId) -> Promise {
+    fn rent_claim(&mut self, contract_id: AccountId, token_id: TokenId) -> Option<Promise> {
         let account_id = env::predecessor_account_id();
         let id = contract_token_id(&contract_id, &token_id);
 
@@ -162,17 +346,66 @@ impl RentFeatureCore for RentFeature {
 
         assert!(is_ended, "Rent is not expired");
 
-        ext_nft
-            ::ext(contract_id.clone())
-            .with_static_gas(GAS_FOR_NFT_TRANSFER)
-            .with_attached_deposit(1)
-            .nft_transfer(rent.owner_id.clone(), token_id.clone(), None, None)
-            .then(
-                ext_self
-                    ::ext(env::current_account_id())
-                    .with_static_gas(env::prepaid_gas() - GAS_FOR_RENT_CLAIM)
-                    .rent_resolve_claim(contract_id, token_id, account_id, renter_id)
-            )
+        self.internal_rent_claim(
+            &contract_id,
+            &token_id,
+            &account_id,
+            &renter_id,
+            &None,
+            env::prepaid_gas() - GAS_FOR_RENT_CLAIM
+        )
+    }
+
+    // #[payable]
+    fn rent_extend(&mut self, contract_id: AccountId, token_id: TokenId, time: u64) {
+        let deposit = env::attached_deposit();
+
+        self.internal_rent_extend(
+            &contract_id,
+            &token_id,
+            &env::predecessor_account_id(),
+            &time,
+            &near_ft(),
+            &U128::from(deposit)
+        )
+    }
+
+    // #[payable]
+    fn rent_terminate(&mut self, contract_id: AccountId, token_id: TokenId) -> Option<Promise> {
+        let deposit = env::attached_deposit();
+
+        self.internal_rent_terminate(
+            &contract_id,
+            &token_id,
+            &env::predecessor_account_id(),
+            &near_ft(),
+            &U128::from(deposit)
+        )
+    }
+
+    fn rent_terminate_cancel(&mut self, contract_id: AccountId, token_id: TokenId) {
+        let account_id = env::predecessor_account_id();
+        let id = contract_token_id(&contract_id, &token_id);
+
+        let termination = self.rents_termination_by_id
+            .get(&id)
+            .expect("Not found termination");
+
+        assert_eq!(termination.account_id, account_id, "Not authorized");
+
+        self.internal_remove_termination(&id);
+
+        (RentTerminateCancel {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            account_id: &account_id,
+        }).emit();
+    }
+
+    fn rent_terminate_refund(&self, contract_id: AccountId, token_id: TokenId) -> U128 {
+        let id = contract_token_id(&contract_id, &token_id);
+
+        U128::from(self.internal_rent_refund(&id))
     }
 
     fn rent_is_ended(&self, contract_id: AccountId, token_id: TokenId) -> bool {
@@ -185,6 +418,22 @@ impl RentFeatureCore for RentFeature {
         self.rents_pending.len()
     }
 
+    fn rent_price(
+        &self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        ft_token_id: AccountId,
+        time: u64
+    ) -> U128 {
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent = self.rents_by_id.get(&id).expect("Not found rent");
+
+        U128(
+            self.internal_rent_price(&rent, &ft_token_id, &time) +
+                self.internal_rent_collateral(&rent, &ft_token_id)
+        )
+    }
+
     fn rent_is_approved(
         &self,
         contract_id: AccountId,
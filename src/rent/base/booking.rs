@@ -0,0 +1,317 @@
+use near_sdk::{ AccountId, Promise, PromiseOrValue, env, ext_contract, is_promise_success };
+use near_sdk::json_types::U128;
+use crate::nft::base::GAS_FOR_NFT_TRANSFER;
+use crate::nft::base::external::ext_nft;
+use crate::rent::base::GAS_FOR_RENT_PAY;
+use crate::rent::base::internal::{ RENT_MAX_SHARE, RENT_TIME_NAX };
+use crate::rent::{
+    RentFeature,
+    RentFeatureBooking,
+    RentBooking,
+    RentBook,
+    RentBookingCancel,
+    Rent,
+    RentAsset,
+    TokenId,
+    contract_token_id,
+};
+
+pub const MAX_RENT_BOOKINGS: usize = 10;
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn rent_resolve_booking(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        booking: RentBooking
+    ) -> U128;
+}
+
+impl RentFeature {
+    pub(crate) fn assert_no_booking_overlap(&self, id: &TokenId, from: u64, to: u64) {
+        if let Some(bookings) = self.rent_bookings_by_id.get(&id) {
+            let is_overlap = bookings
+                .iter()
+                .any(|booking| booking.starts_at < to && from < booking.ends_at);
+
+            assert!(!is_overlap, "Time is already booked");
+        }
+    }
+
+    /// rents now or books the future time when `starts_at` is given
+    pub fn internal_rent_pay(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        buyer_id: &AccountId,
+        receiver_id: &AccountId,
+        time: &u64,
+        ft_token_id: &AccountId,
+        deposit: &U128,
+        starts_at: &Option<u64>
+    ) -> PromiseOrValue<U128> {
+        match starts_at {
+            Some(starts_at) if starts_at > &env::block_timestamp() => {
+                self.internal_rent_book(
+                    &contract_id,
+                    &token_id,
+                    &receiver_id,
+                    &time,
+                    &ft_token_id,
+                    &deposit,
+                    &starts_at
+                );
+
+                PromiseOrValue::Value(U128(0))
+            }
+            _ =>
+                self
+                    .internal_process_purchase(
+                        &contract_id,
+                        &token_id,
+                        &buyer_id,
+                        &receiver_id,
+                        &time,
+                        &ft_token_id,
+                        &deposit
+                    ),
+        }
+    }
+
+    pub(crate) fn internal_rent_book(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        receiver_id: &AccountId,
+        time: &u64,
+        ft_token_id: &AccountId,
+        deposit: &U128,
+        starts_at: &u64
+    ) {
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent: Rent = self.rents_by_id.get(&id).expect("Token is not available for rent");
+
+        assert!(
+            rent.collateral.is_none() && rent.revenue_share.is_none(),
+            "Rent can not be booked"
+        );
+        assert_ne!(receiver_id, &rent.owner_id, "Not rent owned token");
+        assert!(time >= &rent.min_time && time <= &rent.max_time, "Invalid rent time");
+        assert!(starts_at - env::block_timestamp() <= RENT_TIME_NAX, "Booking is too far");
+
+        let price = self.internal_rent_price(&rent, &ft_token_id, &time);
+
+        assert!(
+            deposit.0 == price,
+            "Invalid attached deposit {}, price {}",
+            deposit.0.to_string(),
+            price.to_string()
+        );
+
+        let ends_at = starts_at + time;
+
+        if self.rents_current.get(&id).is_some() {
+            let end_time = self.rents_end_by_id.get(&id).expect("Not found rent");
+
+            assert!(starts_at >= &end_time, "Time is already rented");
+        }
+
+        self.assert_no_booking_overlap(&id, *starts_at, ends_at);
+
+        let mut bookings = self.rent_bookings_by_id.get(&id).unwrap_or_default();
+
+        assert!(bookings.len() < MAX_RENT_BOOKINGS, "Too many bookings");
+
+        let booking = RentBooking {
+            renter_id: receiver_id.clone(),
+            starts_at: starts_at.clone(),
+            ends_at,
+            ft_token_id: ft_token_id.clone(),
+            price: deposit.clone(),
+        };
+
+        let index = bookings
+            .iter()
+            .position(|item| item.starts_at > booking.starts_at)
+            .unwrap_or(bookings.len());
+
+        bookings.insert(index, booking.clone());
+        self.rent_bookings_by_id.insert(&id, &bookings);
+
+        (RentBook {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            renter_id: &booking.renter_id,
+            starts_at: &booking.starts_at,
+            ends_at: &booking.ends_at,
+            ft_token_id: &booking.ft_token_id,
+            price: &booking.price,
+        }).emit();
+    }
+
+    pub(crate) fn internal_set_bookings(&mut self, id: &TokenId, bookings: &Vec<RentBooking>) {
+        if bookings.is_empty() {
+            self.rent_bookings_by_id.remove(&id);
+        } else {
+            self.rent_bookings_by_id.insert(&id, &bookings);
+        }
+    }
+
+    /// full refund of the bookings when the rent is not listed anymore
+    pub(crate) fn internal_refund_bookings(&mut self, id: &TokenId, rent: &Rent) {
+        if let Some(bookings) = self.rent_bookings_by_id.remove(&id) {
+            for booking in bookings {
+                self.internal_cancel_booking(
+                    &rent.contract_id,
+                    &rent.token_id,
+                    &rent.owner_id,
+                    &booking,
+                    0
+                );
+            }
+        }
+    }
+
+    pub(crate) fn internal_cancel_booking(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        booking: &RentBooking,
+        fee: u128
+    ) {
+        let refund = U128(booking.price.0 - fee);
+
+        self.internal_rent_transfer(&booking.ft_token_id, &booking.renter_id, &refund);
+        self.internal_rent_transfer(&booking.ft_token_id, &owner_id, &U128(fee));
+
+        (RentBookingCancel {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            renter_id: &booking.renter_id,
+            starts_at: &booking.starts_at,
+            refund: &refund,
+            fee: &U128(fee),
+        }).emit();
+    }
+}
+
+impl RentFeatureBooking for RentFeature {
+    fn rent_bookings(&self, contract_id: AccountId, token_id: TokenId) -> Vec<RentBooking> {
+        let id = contract_token_id(&contract_id, &token_id);
+
+        self.rent_bookings_by_id.get(&id).unwrap_or_default()
+    }
+
+    fn rent_booking_cancel(&mut self, contract_id: AccountId, token_id: TokenId, starts_at: u64) {
+        let account_id = env::predecessor_account_id();
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+
+        let mut bookings = self.rent_bookings_by_id.get(&id).expect("Not found booking");
+        let index = bookings
+            .iter()
+            .position(|booking| booking.starts_at == starts_at)
+            .expect("Not found booking");
+        let booking = bookings.remove(index);
+
+        assert_eq!(booking.renter_id, account_id, "Not authorized");
+        assert!(env::block_timestamp() < booking.starts_at, "Booking is already started");
+
+        self.internal_set_bookings(&id, &bookings);
+
+        let fee =
+            (booking.price.0 * (rent.cancel_fee.unwrap_or(0) as u128)) / (RENT_MAX_SHARE as u128);
+
+        self.internal_cancel_booking(&contract_id, &token_id, &rent.owner_id, &booking, fee);
+    }
+
+    fn rent_booking_start(&mut self, contract_id: AccountId, token_id: TokenId) -> Option<Promise> {
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+        let now = env::block_timestamp();
+
+        let mut bookings = self.rent_bookings_by_id.get(&id).expect("Not found booking");
+
+        assert!(bookings[0].starts_at <= now, "Booking is not started");
+
+        let booking = bookings.remove(0);
+
+        self.internal_set_bookings(&id, &bookings);
+
+        // not started in time, the renter gets full refund
+        if booking.ends_at <= now {
+            self.internal_cancel_booking(&contract_id, &token_id, &rent.owner_id, &booking, 0);
+
+            return None;
+        }
+
+        assert!(self.rents_current.get(&id).is_none(), "Token is already in rent");
+
+        self.rents_pending.remove(&id);
+
+        let is_kept = self.rents_kept.remove(&id);
+
+        // mt amount and kept nft are already in the contract
+        if rent.asset != RentAsset::Nft || is_kept {
+            self.internal_start_rent(
+                &contract_id,
+                &token_id,
+                &rent.owner_id,
+                &booking.renter_id,
+                &(booking.ends_at - booking.starts_at),
+                &booking.ends_at,
+                &booking.ft_token_id,
+                &booking.price
+            );
+
+            return None;
+        }
+
+        Some(
+            ext_nft
+                ::ext(contract_id.clone())
+                .with_static_gas(GAS_FOR_NFT_TRANSFER)
+                .with_attached_deposit(1)
+                .nft_transfer(env::current_account_id(), token_id.clone(), None, None)
+                .then(
+                    ext_self
+                        ::ext(env::current_account_id())
+                        .with_static_gas(env::prepaid_gas() - GAS_FOR_RENT_PAY)
+                        .rent_resolve_booking(contract_id, token_id, rent.owner_id, booking)
+                )
+        )
+    }
+
+    // #[private]
+    fn rent_resolve_booking(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        booking: RentBooking
+    ) -> U128 {
+        if !is_promise_success() {
+            let id = contract_token_id(&contract_id, &token_id);
+
+            self.rents_pending.insert(&id);
+            self.internal_cancel_booking(&contract_id, &token_id, &owner_id, &booking, 0);
+
+            return U128(0);
+        }
+
+        self.internal_resolve_purchase(
+            &contract_id,
+            &token_id,
+            &booking.renter_id,
+            &owner_id,
+            &booking.renter_id,
+            &(booking.ends_at - booking.starts_at),
+            &booking.ends_at,
+            &booking.ft_token_id,
+            &booking.price
+        )
+    }
+}
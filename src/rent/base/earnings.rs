@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use near_sdk::{ AccountId, Gas, Promise, env, ext_contract, is_promise_success };
+use near_sdk::json_types::U128;
+use crate::rent::base::internal::RENT_MAX_SHARE;
+use crate::rent::{
+    RentFeature,
+    RentFeatureEarnings,
+    RentEarnings,
+    RentEarn,
+    RentEarningsClaim,
+    RentEarningsClaimFail,
+    TokenId,
+    Rent,
+    contract_token_id,
+};
+
+const GAS_FOR_RESOLVE_EARNINGS_CLAIM: Gas = Gas(10_000_000_000_000);
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn rent_resolve_earnings_claim(
+        &mut self,
+        account_id: AccountId,
+        ft_token_id: AccountId,
+        amount: U128
+    ) -> bool;
+}
+
+impl RentFeature {
+    pub(crate) fn internal_rent_earn(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        ft_token_id: &AccountId,
+        amount: &U128
+    ) {
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+        let renter_id = self.rents_current.get(&id).expect("Not found renter");
+        let revenue_share = rent.revenue_share.expect("Rent without revenue share");
+
+        assert!(amount.0 > 0, "Amount must be greater than 0");
+
+        let owner_amount = U128(amount.0 * (revenue_share.owner_share as u128) / (RENT_MAX_SHARE as u128));
+        let renter_amount = U128(amount.0 - owner_amount.0);
+
+        let mut earnings = self.rents_earnings_by_id.get(&id).unwrap_or_default();
+
+        add_amount(&mut earnings.owner, &ft_token_id, owner_amount.0);
+        add_amount(&mut earnings.renter, &ft_token_id, renter_amount.0);
+
+        self.rents_earnings_by_id.insert(&id, &earnings);
+        self.internal_add_earnings(&rent.owner_id, &ft_token_id, owner_amount.0);
+        self.internal_add_earnings(&renter_id, &ft_token_id, renter_amount.0);
+
+        (RentEarn {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            ft_token_id: &ft_token_id,
+            owner_id: &rent.owner_id,
+            renter_id: &renter_id,
+            owner_amount: &owner_amount,
+            renter_amount: &renter_amount,
+        }).emit();
+    }
+
+    pub(crate) fn internal_add_earnings(
+        &mut self,
+        account_id: &AccountId,
+        ft_token_id: &AccountId,
+        amount: u128
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        let mut balances = self.earnings_per_account.get(&account_id).unwrap_or_default();
+
+        add_amount(&mut balances, &ft_token_id, amount);
+
+        self.earnings_per_account.insert(&account_id, &balances);
+    }
+}
+
+fn add_amount(balances: &mut HashMap<AccountId, U128>, ft_token_id: &AccountId, amount: u128) {
+    let balance = balances.get(ft_token_id).map(|balance| balance.0).unwrap_or(0);
+
+    balances.insert(ft_token_id.clone(), U128(balance + amount));
+}
+
+impl RentFeatureEarnings for RentFeature {
+    fn rent_earnings(&self, contract_id: AccountId, token_id: TokenId) -> Option<RentEarnings> {
+        self.rents_earnings_by_id.get(&contract_token_id(&contract_id, &token_id))
+    }
+
+    fn rent_earnings_for_account(&self, account_id: AccountId) -> HashMap<AccountId, U128> {
+        self.earnings_per_account.get(&account_id).unwrap_or_default()
+    }
+
+    fn rent_earnings_claim(&mut self, ft_token_id: AccountId) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let mut balances = self.earnings_per_account.get(&account_id).expect("Not found earnings");
+        let amount = balances.remove(&ft_token_id).expect("Not found earnings");
+
+        // balance is taken before transfer, resolver puts it back on failure
+        if balances.is_empty() {
+            self.earnings_per_account.remove(&account_id);
+        } else {
+            self.earnings_per_account.insert(&account_id, &balances);
+        }
+
+        self.internal_rent_transfer_promise(&ft_token_id, &account_id, &amount).then(
+            ext_self
+                ::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_EARNINGS_CLAIM)
+                .rent_resolve_earnings_claim(account_id, ft_token_id, amount)
+        )
+    }
+
+    // #[private]
+    fn rent_resolve_earnings_claim(
+        &mut self,
+        account_id: AccountId,
+        ft_token_id: AccountId,
+        amount: U128
+    ) -> bool {
+        if !is_promise_success() {
+            self.internal_add_earnings(&account_id, &ft_token_id, amount.0);
+
+            (RentEarningsClaimFail {
+                account_id: &account_id,
+                ft_token_id: &ft_token_id,
+                amount: &amount,
+            }).emit();
+
+            return false;
+        }
+
+        (RentEarningsClaim {
+            account_id: &account_id,
+            ft_token_id: &ft_token_id,
+            amount: &amount,
+        }).emit();
+
+        true
+    }
+}
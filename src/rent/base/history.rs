@@ -0,0 +1,122 @@
+use near_sdk::{ AccountId, env };
+use near_sdk::json_types::U128;
+use crate::rent::base::internal::RENT_MAX_SHARE;
+use crate::rent::{
+    RentFeature,
+    RentFeatureHistory,
+    RentRecord,
+    RentStats,
+    JsonRentStats,
+    TokenId,
+    contract_token_id,
+};
+
+pub const RENT_HISTORY_LIMIT: usize = 20;
+
+const HOUR: u64 = 3_600_000_000_000;
+
+impl RentFeature {
+    pub(crate) fn internal_init_rent_stats(&mut self, id: &TokenId) {
+        if self.rent_stats_by_id.get(&id).is_none() {
+            self.rent_stats_by_id.insert(&id, &RentStats {
+                listed_at: env::block_timestamp(),
+                rents_count: 0,
+                time: 0,
+                revenue: Default::default(),
+            });
+        }
+    }
+
+    /// keeps the record of the current rent, expected to be called before it is removed
+    pub(crate) fn internal_add_rent_record(&mut self, id: &TokenId) {
+        let (rent, renter_id, payment, end_time) = match
+            (
+                self.rents_by_id.get(&id),
+                self.rents_current.get(&id),
+                self.rents_payment_by_id.get(&id),
+                self.rents_end_by_id.get(&id),
+            )
+        {
+            (Some(rent), Some(renter_id), Some(payment), Some(end_time)) => {
+                (rent, renter_id, payment, end_time)
+            }
+            _ => {
+                return;
+            }
+        };
+
+        let record = RentRecord {
+            token_id: rent.token_id,
+            contract_id: rent.contract_id,
+            renter_id,
+            started_at: payment.started_at,
+            ended_at: end_time.min(env::block_timestamp()).max(payment.started_at),
+            ft_token_id: payment.ft_token_id,
+            price: payment.price,
+        };
+
+        let mut stats = self.rent_stats_by_id.get(&id).unwrap_or(RentStats {
+            listed_at: record.started_at,
+            rents_count: 0,
+            time: 0,
+            revenue: Default::default(),
+        });
+        let revenue = stats.revenue.get(&record.ft_token_id).map(|revenue| revenue.0).unwrap_or(0);
+
+        stats.rents_count += 1;
+        stats.time += record.ended_at - record.started_at;
+        stats.revenue.insert(record.ft_token_id.clone(), U128(revenue + record.price.0));
+        self.rent_stats_by_id.insert(&id, &stats);
+
+        let mut history = self.rent_history_by_id.get(&id).unwrap_or_default();
+        push_record(&mut history, &record);
+        self.rent_history_by_id.insert(&id, &history);
+
+        let mut history = self.rent_history_per_account.get(&record.renter_id).unwrap_or_default();
+        push_record(&mut history, &record);
+        self.rent_history_per_account.insert(&record.renter_id, &history);
+    }
+}
+
+fn push_record(history: &mut Vec<RentRecord>, record: &RentRecord) {
+    if history.len() >= RENT_HISTORY_LIMIT {
+        history.remove(0);
+    }
+
+    history.push(record.clone());
+}
+
+impl RentFeatureHistory for RentFeature {
+    fn rent_history(&self, contract_id: AccountId, token_id: TokenId) -> Vec<RentRecord> {
+        let id = contract_token_id(&contract_id, &token_id);
+
+        self.rent_history_by_id.get(&id).unwrap_or_default().into_iter().rev().collect()
+    }
+
+    fn rent_history_for_account(&self, account_id: AccountId) -> Vec<RentRecord> {
+        self.rent_history_per_account.get(&account_id).unwrap_or_default().into_iter().rev().collect()
+    }
+
+    fn rent_stats(&self, contract_id: AccountId, token_id: TokenId) -> Option<JsonRentStats> {
+        let id = contract_token_id(&contract_id, &token_id);
+
+        self.rent_stats_by_id.get(&id).map(|stats| {
+            let listed_time = env::block_timestamp().saturating_sub(stats.listed_at);
+            let utilization = if listed_time > 0 {
+                ((stats.time as u128) * (RENT_MAX_SHARE as u128) / (listed_time as u128)).min(
+                    RENT_MAX_SHARE as u128
+                ) as u16
+            } else {
+                0
+            };
+
+            JsonRentStats {
+                listed_at: stats.listed_at,
+                rents_count: stats.rents_count,
+                hours: stats.time / HOUR,
+                revenue: stats.revenue,
+                utilization,
+            }
+        })
+    }
+}
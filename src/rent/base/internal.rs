@@ -21,16 +21,27 @@ use crate::rent::{
     hash_account_id,
     TokenId,
     RentPay,
+    RentUpdatePriceTiers,
     time_get_minutes,
 };
-use crate::rent::meta::{ SaleConditions };
+use crate::rent::meta::{ SaleConditions, RevenueShare, PriceTiers, PriceTier, RentAsset };
 use crate::utils::near_ft;
 use crate::nft::base::GAS_FOR_NFT_TRANSFER;
 use crate::rent::base::GAS_FOR_RENT_PAY;
 use crate::nft::base::external::ext_nft;
+use crate::mt::base::{ ext_mt, GAS_FOR_MT_TRANSFER };
+use crate::ft::base::external::ext_ft;
+use crate::ft::base::core_impl::GAS_FOR_FT_TRANSFER;
 
 pub const RENT_TIME_MIN: u64 = 3600000000000; // min 15 min (15 * 60 * 1000 * 1_000_000)
 pub const RENT_TIME_NAX: u64 = 8640000000000000; // max 100 days (60 * 60 * 1000 * 100 * 1_000_000)
+pub const RENT_MAX_SHARE: u16 = 10_000;
+pub const RENT_MAX_PRICE_TIERS: usize = 10;
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn rent_resolve_remove(&mut self, contract_id: AccountId, token_id: TokenId, is_kept: bool) -> bool;
+}
 
 #[derive(BorshStorageKey, BorshSerialize)]
 pub enum StorageKey {
@@ -64,6 +75,16 @@ impl RentFeature {
         assert!(time <= &RENT_TIME_NAX, "maximum 100 days.");
     }
 
+    pub(crate) fn assert_valid_price_tiers(&self, tiers: &Vec<PriceTier>) {
+        assert!(tiers.len() <= RENT_MAX_PRICE_TIERS, "Too many price tiers");
+
+        for tier in tiers {
+            self.assert_valid_time(&tier.min_time);
+            assert!(tier.price.is_some() || tier.discount.is_some(), "Invalid price tier");
+            assert!(tier.discount.unwrap_or(0) <= RENT_MAX_SHARE, "Invalid price tier");
+        }
+    }
+
     //
 
     pub(crate) fn internal_rent_add(
@@ -73,7 +94,13 @@ impl RentFeature {
         owner_id: &AccountId,
         sale_conditions: &SaleConditions,
         min_time: &u64,
-        max_time: &u64
+        max_time: &u64,
+        collateral: &Option<SaleConditions>,
+        revenue_share: &Option<RevenueShare>,
+        price_tiers: &PriceTiers,
+        cancel_fee: &Option<u16>,
+        transfer_fee: &Option<u16>,
+        asset: &RentAsset
     ) {
         let id = contract_token_id(&nft_contract_id, &token_id);
         let is_paid = self.rents_current.get(&id).is_some();
@@ -85,6 +112,23 @@ impl RentFeature {
         self.assert_valid_time(&min_time);
         self.assert_valid_time(&max_time);
 
+        if let Some(revenue_share) = revenue_share {
+            assert!(revenue_share.owner_share <= RENT_MAX_SHARE, "Invalid owner share");
+            self.assert_valid_time(&revenue_share.time);
+        }
+
+        assert!(cancel_fee.unwrap_or(0) <= RENT_MAX_SHARE, "Invalid cancel fee");
+        assert!(transfer_fee.unwrap_or(0) <= RENT_MAX_SHARE, "Invalid transfer fee");
+
+        if let RentAsset::Mt { amount, .. } = asset {
+            assert!(amount.0 > 0, "Invalid amount");
+            assert!(collateral.is_none(), "Mt rent can not have collateral");
+        }
+
+        for tiers in price_tiers.values() {
+            self.assert_valid_price_tiers(&tiers);
+        }
+
         let rent = Rent {
             owner_id: owner_id.clone(),
             contract_id: nft_contract_id.clone(),
@@ -93,9 +137,16 @@ impl RentFeature {
             max_time: max_time.clone(),
             created_at: env::block_timestamp(),
             sale_conditions: sale_conditions.clone(),
+            collateral: collateral.clone(),
+            revenue_share: revenue_share.clone(),
+            price_tiers: price_tiers.clone(),
+            cancel_fee: cancel_fee.clone(),
+            transfer_fee: transfer_fee.clone(),
+            asset: asset.clone(),
         };
 
         self.approved_owner_by_id.as_mut().unwrap().insert(&id, &owner_id);
+        self.internal_init_rent_stats(&id);
         self.rents_pending.insert(&id);
         self.rents_by_id.insert(&id, &rent);
         self.internal_add_rent_to_account(&owner_id, &id);
@@ -109,6 +160,12 @@ impl RentFeature {
             min_time: &rent.min_time,
             max_time: &rent.max_time,
             created_at: &rent.created_at,
+            collateral: &rent.collateral,
+            revenue_share: &rent.revenue_share,
+            price_tiers: &rent.price_tiers,
+            cancel_fee: &rent.cancel_fee,
+            transfer_fee: &rent.transfer_fee,
+            asset: &rent.asset,
         }).emit();
     }
 
@@ -162,6 +219,7 @@ impl RentFeature {
         let is_paid = self.rents_current.get(&id).is_some();
 
         assert!(!is_paid, "Token is already in rent");
+        assert!(self.rent_bookings_by_id.get(&id).is_none(), "Token has bookings");
 
         let rent = self.rents_by_id.get(&id).expect("Not found rent");
 
@@ -172,6 +230,21 @@ impl RentFeature {
         self.internal_remove_rent_from_account(&account_id, &nft_contract_id, &token_id);
         self.internal_remove_rent_from_contract(&nft_contract_id, &token_id);
 
+        let is_kept = self.rents_kept.remove(&id);
+
+        // the listing is restored when the asset is not returned
+        if rent.asset != RentAsset::Nft || is_kept {
+            self.rents_removed_by_id.insert(&id, &rent);
+            self.internal_rent_asset_transfer(&rent, &account_id).then(
+                ext_self
+                    ::ext(env::current_account_id())
+                    .with_static_gas(env::prepaid_gas() - GAS_FOR_RENT_PAY)
+                    .rent_resolve_remove(nft_contract_id.clone(), token_id.clone(), is_kept)
+            );
+
+            return;
+        }
+
         (RentRemove {
             token_id: &token_id,
             contract_id: &nft_contract_id,
@@ -223,6 +296,17 @@ impl RentFeature {
 
     //
 
+    pub(crate) fn internal_restore_rent(&mut self, id: &TokenId, rent: &Rent, is_kept: bool) {
+        self.rents_by_id.insert(&id, &rent);
+        self.rents_pending.insert(&id);
+        self.internal_add_rent_to_account(&rent.owner_id, &id);
+        self.internal_add_rent_to_contract(&rent.contract_id, &rent.token_id);
+
+        if is_kept {
+            self.rents_kept.insert(&id);
+        }
+    }
+
     pub(crate) fn internal_add_rent_to_account(
         &mut self,
         account_id: &AccountId,
@@ -259,6 +343,17 @@ impl RentFeature {
         self.rent_tokens_by_contract.insert(contract_id, &rents_set);
     }
 
+    /// booked time keeps the asset in the contract for the next rent
+    pub(crate) fn internal_keep_rent_asset(&mut self, id: &TokenId) -> bool {
+        if self.rent_bookings_by_id.get(&id).is_none() {
+            return false;
+        }
+
+        self.rents_kept.insert(&id);
+
+        true
+    }
+
     pub(crate) fn internal_remove_current_rent(
         &mut self,
         _account_id: &AccountId,
@@ -267,10 +362,214 @@ impl RentFeature {
     ) {
         let id = contract_token_id(&nft_contract_id, &token_id);
 
-        self.rents_by_id.remove(&id);
+        self.internal_remove_termination(&id);
+        self.internal_add_rent_record(&id);
+        self.rent_sublets_by_id.remove(&id);
+
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+
+        // kept asset backs the bookings, otherwise they are refunded with the listing
+        if self.rents_kept.contains(&id) {
+            self.rents_pending.insert(&id);
+        } else {
+            self.internal_refund_bookings(&id, &rent);
+            self.rents_by_id.remove(&id);
+        }
+
         self.rents_current.remove(&id);
 
-        self.rents_end_by_id.remove(&id);
+        self.internal_remove_rent_end(&id);
+        self.rents_payment_by_id.remove(&id);
+        self.rents_earnings_by_id.remove(&id);
+    }
+
+    pub(crate) fn internal_set_rent_end(&mut self, id: &TokenId, end_time: &u64) {
+        self.internal_remove_rent_end(&id);
+
+        self.rents_end_by_id.insert(&id, &end_time);
+        self.rents_by_end_at.insert(&(end_time.clone(), id.clone()), &());
+    }
+
+    pub(crate) fn internal_remove_rent_end(&mut self, id: &TokenId) {
+        if let Some(end_time) = self.rents_end_by_id.remove(&id) {
+            self.rents_by_end_at.remove(&(end_time, id.clone()));
+        }
+    }
+
+    pub(crate) fn internal_remove_paid_rent(
+        &mut self,
+        owner_id: &AccountId,
+        renter_id: &AccountId,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId
+    ) {
+        let id = contract_token_id(&nft_contract_id, &token_id);
+
+        self.internal_remove_current_rent(&owner_id, &nft_contract_id, &token_id);
+        self.internal_remove_token_from_account(&renter_id, &nft_contract_id, &token_id);
+
+        if self.rents_by_id.get(&id).is_none() {
+            self.internal_remove_rent_from_contract(&nft_contract_id, &token_id);
+            self.internal_remove_rent_from_account(&owner_id, &nft_contract_id, &token_id);
+        }
+    }
+
+    pub(crate) fn internal_remove_termination(&mut self, id: &TokenId) {
+        if let Some(termination) = self.rents_termination_by_id.remove(&id) {
+            if termination.deposit.0 > 0 {
+                let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+                self.internal_rent_transfer(
+                    &payment.ft_token_id,
+                    &termination.account_id,
+                    &termination.deposit
+                );
+            }
+        }
+    }
+
+    //
+
+    pub(crate) fn internal_rent_price(&self, rent: &Rent, ft_token_id: &AccountId, time: &u64) -> u128 {
+        if rent.revenue_share.is_some() {
+            return 0;
+        }
+
+        let minutes = time_get_minutes(time.clone()) as u128;
+        let price_per_hour = rent.sale_conditions.get(&ft_token_id).expect("Ft not available");
+
+        let price = ((price_per_hour.0 * minutes) / 60 / 1_000_000) as u128;
+
+        // the cheapest of applicable tiers
+        rent.price_tiers
+            .get(&ft_token_id)
+            .map(|tiers| {
+                tiers
+                    .iter()
+                    .filter(|tier| time >= &tier.min_time)
+                    .flat_map(|tier| {
+                        let tier_price = tier.price.map(
+                            |tier_price| tier_price.0 * (*time as u128) / (tier.min_time as u128)
+                        );
+                        let discount_price = tier.discount.map(
+                            |discount| price * ((RENT_MAX_SHARE - discount) as u128) / (RENT_MAX_SHARE as u128)
+                        );
+
+                        tier_price.into_iter().chain(discount_price)
+                    })
+                    .fold(price, u128::min)
+            })
+            .unwrap_or(price)
+    }
+
+    pub(crate) fn internal_rent_update_price_tiers(
+        &mut self,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        ft_token_id: &AccountId,
+        price_tiers: &Vec<PriceTier>
+    ) {
+        let id = contract_token_id(&nft_contract_id, &token_id);
+        let is_paid = self.rents_current.get(&id).is_some();
+
+        assert!(!is_paid, "Rent already started");
+
+        let mut rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+
+        assert_eq!(account_id, &rent.owner_id, "Unauthorized");
+
+        self.assert_valid_price_tiers(&price_tiers);
+
+        if price_tiers.is_empty() {
+            rent.price_tiers.remove(&ft_token_id);
+        } else {
+            rent.price_tiers.insert(ft_token_id.clone(), price_tiers.clone());
+        }
+
+        self.rents_by_id.insert(&id, &rent);
+
+        (RentUpdatePriceTiers {
+            token_id: &rent.token_id,
+            contract_id: &nft_contract_id,
+            owner_id: &rent.owner_id,
+            ft_token_id: &ft_token_id,
+            price_tiers: &price_tiers,
+        }).emit();
+    }
+
+    pub(crate) fn internal_rent_collateral(&self, rent: &Rent, ft_token_id: &AccountId) -> u128 {
+        rent.collateral
+            .as_ref()
+            .map(|collateral| collateral.get(&ft_token_id).expect("Ft not available for collateral").0)
+            .unwrap_or(0)
+    }
+
+    // pro-rata part of the paid price for the time left
+    pub(crate) fn internal_rent_refund(&self, id: &TokenId) -> u128 {
+        let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+        let end_time = self.rents_end_by_id.get(&id).expect("Not found rent");
+        let now = env::block_timestamp();
+
+        if now >= end_time || end_time <= payment.started_at {
+            return 0;
+        }
+
+        let left = (end_time - now) as u128;
+        let total = (end_time - payment.started_at) as u128;
+
+        payment.price.0 * left / total
+    }
+
+    /// sends the rented nft or the locked mt amount
+    pub(crate) fn internal_rent_asset_transfer(&self, rent: &Rent, receiver_id: &AccountId) -> Promise {
+        match &rent.asset {
+            RentAsset::Nft =>
+                ext_nft
+                    ::ext(rent.contract_id.clone())
+                    .with_static_gas(GAS_FOR_NFT_TRANSFER)
+                    .with_attached_deposit(1)
+                    .nft_transfer(receiver_id.clone(), rent.token_id.clone(), None, None),
+            RentAsset::Mt { token_id, amount } =>
+                ext_mt
+                    ::ext(rent.contract_id.clone())
+                    .with_static_gas(GAS_FOR_MT_TRANSFER)
+                    .with_attached_deposit(1)
+                    .mt_batch_transfer(
+                        receiver_id.clone(),
+                        vec![AccountId::new_unchecked(token_id.clone())],
+                        vec![amount.clone()],
+                        None
+                    ),
+        }
+    }
+
+    pub(crate) fn internal_rent_transfer(
+        &self,
+        ft_token_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: &U128
+    ) {
+        if amount.0 > 0 {
+            self.internal_rent_transfer_promise(&ft_token_id, &receiver_id, &amount);
+        }
+    }
+
+    pub(crate) fn internal_rent_transfer_promise(
+        &self,
+        ft_token_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: &U128
+    ) -> Promise {
+        if ft_token_id == &near_ft() {
+            Promise::new(receiver_id.clone()).transfer(amount.0)
+        } else {
+            ext_ft
+                ::ext(ft_token_id.clone())
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .with_attached_deposit(1)
+                .ft_transfer(receiver_id.clone(), amount.clone(), None)
+        }
     }
 
     pub(crate) fn internal_remove_rent_from_account(
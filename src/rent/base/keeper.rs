@@ -0,0 +1,131 @@
+use near_sdk::{ AccountId, Gas, Promise, env, require };
+use near_sdk::json_types::U128;
+use crate::nft::base::GAS_FOR_NFT_TRANSFER;
+use crate::rent::{
+    RentFeature,
+    RentFeatureKeeper,
+    RentKeeperReward,
+    RentKeeperRewardUpdate,
+    RentKeeperPoolDeposit,
+    TokenId,
+};
+
+pub const MAX_RENT_CLAIM_EXPIRED: u64 = 5;
+
+const GAS_FOR_RESOLVE_CLAIM: Gas = Gas(20_000_000_000_000);
+const GAS_FOR_CLAIM_EXPIRED: Gas = Gas(10_000_000_000_000);
+
+impl RentFeature {
+    /// reward of one claim, limited by the pool
+    pub(crate) fn internal_pay_keeper_reward(&mut self, keeper_id: &AccountId) {
+        let reward = self.keeper_reward.min(self.keeper_pool);
+
+        if reward > 0 {
+            self.keeper_pool -= reward;
+
+            Promise::new(keeper_id.clone()).transfer(reward);
+
+            (RentKeeperReward {
+                account_id: &keeper_id,
+                claimed: &1,
+                amount: &U128(reward),
+            }).emit();
+        }
+    }
+}
+
+impl RentFeatureKeeper for RentFeature {
+    fn rent_claim_expired(&mut self, limit: u64) -> u64 {
+        let keeper_id = env::predecessor_account_id();
+        let now = env::block_timestamp();
+
+        // every claim transfers the asset and resolves it
+        let gas_per_claim = GAS_FOR_NFT_TRANSFER.0 + GAS_FOR_RESOLVE_CLAIM.0;
+        let limit = limit
+            .min(MAX_RENT_CLAIM_EXPIRED)
+            .min(env::prepaid_gas().0.saturating_sub(GAS_FOR_CLAIM_EXPIRED.0) / gas_per_claim);
+
+        require!(limit != 0, "More gas is required");
+
+        let ended: Vec<(u64, TokenId)> = self.rents_by_end_at
+            .iter()
+            .map(|(key, _)| key)
+            .take_while(|(end_time, _)| now > *end_time)
+            .take(limit as usize)
+            .collect();
+
+        let mut claimed = 0;
+
+        for key in ended {
+            // index entry goes first so the same rent is not claimed twice
+            self.rents_by_end_at.remove(&key);
+
+            let id = key.1;
+            let (rent, renter_id) = match (self.rents_by_id.get(&id), self.rents_current.get(&id)) {
+                (Some(rent), Some(renter_id)) => (rent, renter_id),
+                _ => {
+                    continue;
+                }
+            };
+
+            self.internal_rent_claim(
+                &rent.contract_id,
+                &rent.token_id,
+                &rent.owner_id,
+                &renter_id,
+                &Some(keeper_id.clone()),
+                GAS_FOR_RESOLVE_CLAIM
+            );
+
+            claimed += 1;
+        }
+
+        claimed
+    }
+
+    fn rent_resolve_claim_expired(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        renter_id: AccountId,
+        keeper_id: AccountId
+    ) {
+        if self.internal_resolve_claim(&contract_id, &token_id, &owner_id, &renter_id) {
+            self.internal_pay_keeper_reward(&keeper_id);
+        }
+    }
+
+    // #[payable]
+    fn rent_keeper_pool_deposit(&mut self) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let deposit = env::attached_deposit();
+
+        assert!(deposit > 0, "Deposit must be greater than 0");
+
+        self.keeper_pool += deposit;
+
+        (RentKeeperPoolDeposit {
+            account_id: &account_id,
+            amount: &U128(deposit),
+        }).emit();
+
+        U128(self.keeper_pool)
+    }
+
+    fn rent_set_keeper_reward(&mut self, reward: U128) {
+        self.keeper_reward = reward.0;
+
+        (RentKeeperRewardUpdate {
+            reward: &reward,
+        }).emit();
+    }
+
+    fn rent_keeper_reward(&self) -> U128 {
+        U128(self.keeper_reward)
+    }
+
+    fn rent_keeper_pool(&self) -> U128 {
+        U128(self.keeper_pool)
+    }
+}
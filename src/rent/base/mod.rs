@@ -1,14 +1,32 @@
-use near_sdk::{ AccountId, Promise };
+use near_sdk::{ AccountId, Promise, PromiseOrValue };
 use near_sdk::json_types::U128;
 
 mod base_impl;
 
+mod earnings;
+mod booking;
+mod history;
+mod sublet;
 mod internal;
+mod keeper;
 mod receivers;
 mod resolvers;
 
 pub use self::base_impl::*;
-use crate::rent::TokenId;
+pub use self::keeper::MAX_RENT_CLAIM_EXPIRED;
+pub use self::history::RENT_HISTORY_LIMIT;
+pub use self::booking::MAX_RENT_BOOKINGS;
+use std::collections::HashMap;
+use crate::rent::{
+    TokenId,
+    RentEarnings,
+    PriceTier,
+    RentRecord,
+    JsonRentStats,
+    RentBooking,
+    RentSublet,
+    SaleConditions,
+};
 
 pub trait RentFeatureCore {
     //
@@ -23,6 +41,13 @@ pub trait RentFeatureCore {
         min_time: u64,
         max_time: u64
     );
+    fn rent_update_price_tiers(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        ft_token_id: AccountId,
+        price_tiers: Vec<PriceTier>
+    );
     fn rent_remove(&mut self, contract_id: AccountId, token_id: TokenId);
 
     // payable
@@ -31,12 +56,28 @@ pub trait RentFeatureCore {
         contract_id: AccountId,
         token_id: TokenId,
         time: u64,
-        receiver_id: AccountId
-    ) -> Promise;
-    fn rent_claim(&mut self, contract_id: AccountId, token_id: TokenId) -> Promise;
+        receiver_id: AccountId,
+        starts_at: Option<u64>
+    ) -> PromiseOrValue<U128>;
+    fn rent_claim(&mut self, contract_id: AccountId, token_id: TokenId) -> Option<Promise>;
+
+    // payable
+    fn rent_extend(&mut self, contract_id: AccountId, token_id: TokenId, time: u64);
+    // payable
+    fn rent_terminate(&mut self, contract_id: AccountId, token_id: TokenId) -> Option<Promise>;
+    fn rent_terminate_cancel(&mut self, contract_id: AccountId, token_id: TokenId);
+    fn rent_terminate_refund(&self, contract_id: AccountId, token_id: TokenId) -> U128;
 
     fn rent_is_ended(&self, contract_id: AccountId, token_id: TokenId) -> bool;
     fn rent_total_supply(&self) -> u64;
+    /// exact price of rent for `time` paid with `ft_token_id`
+    fn rent_price(
+        &self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        ft_token_id: AccountId,
+        time: u64
+    ) -> U128;
 
     fn rent_is_approved(
         &self,
@@ -65,4 +106,110 @@ pub trait RentFeatureResolve {
         owner_id: AccountId,
         renter_id: AccountId
     );
+    fn rent_resolve_terminate(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        renter_id: AccountId,
+        refund: U128,
+        deposit: U128
+    ) -> U128;
+    /// returns true when the nft should be returned to the renter
+    fn rent_resolve_return(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        renter_id: AccountId
+    ) -> bool;
+    /// restores the removed listing when the asset is not returned to the owner
+    fn rent_resolve_remove(&mut self, contract_id: AccountId, token_id: TokenId, is_kept: bool) -> bool;
+}
+
+pub trait RentFeatureEarnings {
+    fn rent_earnings(&self, contract_id: AccountId, token_id: TokenId) -> Option<RentEarnings>;
+    fn rent_earnings_for_account(&self, account_id: AccountId) -> HashMap<AccountId, U128>;
+
+    fn rent_earnings_claim(&mut self, ft_token_id: AccountId) -> Promise;
+
+    fn rent_resolve_earnings_claim(
+        &mut self,
+        account_id: AccountId,
+        ft_token_id: AccountId,
+        amount: U128
+    ) -> bool;
+}
+
+pub trait RentFeatureKeeper {
+    /// starts claims of up to `limit` ended rents ordered by end time, returns started claims
+    /// caller gets reward from the pool for every returned asset
+    fn rent_claim_expired(&mut self, limit: u64) -> u64;
+    fn rent_resolve_claim_expired(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        renter_id: AccountId,
+        keeper_id: AccountId
+    );
+
+    // payable
+    fn rent_keeper_pool_deposit(&mut self) -> U128;
+    fn rent_set_keeper_reward(&mut self, reward: U128);
+
+    fn rent_keeper_reward(&self) -> U128;
+    fn rent_keeper_pool(&self) -> U128;
+}
+
+pub trait RentFeatureHistory {
+    /// last ended rents of the token, newest first
+    fn rent_history(&self, contract_id: AccountId, token_id: TokenId) -> Vec<RentRecord>;
+    /// last ended rents of the renter, newest first
+    fn rent_history_for_account(&self, account_id: AccountId) -> Vec<RentRecord>;
+
+    fn rent_stats(&self, contract_id: AccountId, token_id: TokenId) -> Option<JsonRentStats>;
+}
+
+pub trait RentFeatureBooking {
+    fn rent_bookings(&self, contract_id: AccountId, token_id: TokenId) -> Vec<RentBooking>;
+
+    /// renter cancels the booking before its start, owner keeps `cancel_fee`
+    fn rent_booking_cancel(&mut self, contract_id: AccountId, token_id: TokenId, starts_at: u64);
+    /// turns the first started booking into the current rent
+    fn rent_booking_start(&mut self, contract_id: AccountId, token_id: TokenId) -> Option<Promise>;
+
+    fn rent_resolve_booking(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        owner_id: AccountId,
+        booking: RentBooking
+    ) -> U128;
+}
+
+pub trait RentFeatureSublet {
+    /// `None` forbids renters to pass the rent
+    fn rent_update_transfer_fee(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        transfer_fee: Option<u16>
+    );
+
+    /// passes the rent to `receiver_id`, owner fee is taken from the remaining rent value
+    // payable
+    fn rent_transfer(&mut self, contract_id: AccountId, token_id: TokenId, receiver_id: AccountId);
+
+    fn rent_sublet_add(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        sale_conditions: SaleConditions
+    );
+    fn rent_sublet_remove(&mut self, contract_id: AccountId, token_id: TokenId);
+    // payable
+    fn rent_sublet_pay(&mut self, contract_id: AccountId, token_id: TokenId, receiver_id: AccountId);
+
+    fn rent_sublet(&self, contract_id: AccountId, token_id: TokenId) -> Option<RentSublet>;
 }
@@ -1,6 +1,15 @@
-use crate::rent::{ RentFeature, RentOnNftApproveArgs, TokenId };
+use crate::rent::{ RentFeature, RentOnNftApproveArgs, TokenId, contract_token_id, mt_listing_id };
 use near_sdk::{ AccountId, Promise, PromiseOrValue };
-use crate::rent::meta::RentOnFtTransferArgs;
+use crate::rent::meta::{
+    RentOnFtTransferArgs,
+    RentOnFtTransferExtendArgs,
+    RentOnFtTransferTerminateArgs,
+    RentOnFtTransferEarningsArgs,
+    RentOnFtTransferRenterArgs,
+    RentOnFtTransferSubletArgs,
+    RentOnMtTransferArgs,
+    RentAsset,
+};
 use near_sdk::json_types::{ U128 };
 use crate::utils::near_ft;
 
@@ -12,7 +21,16 @@ impl RentFeature {
         token_id: &TokenId,
         owner_id: &AccountId
     ) -> PromiseOrValue<String> {
-        let RentOnNftApproveArgs { sale_conditions, max_time, min_time } = args;
+        let RentOnNftApproveArgs {
+            sale_conditions,
+            max_time,
+            min_time,
+            collateral,
+            revenue_share,
+            price_tiers,
+            cancel_fee,
+            transfer_fee,
+        } = args;
 
         self.internal_rent_add(
             &contract_id,
@@ -20,12 +38,64 @@ impl RentFeature {
             &owner_id,
             &sale_conditions,
             &min_time,
-            &max_time
+            &max_time,
+            &collateral,
+            &revenue_share,
+            &price_tiers.clone().unwrap_or_default(),
+            &cancel_fee,
+            &transfer_fee,
+            &RentAsset::Nft
         );
 
         PromiseOrValue::Value("true".to_string())
     }
 
+    /// lists the transferred mt amount, the amount is kept until the rent is removed or claimed
+    pub fn internal_on_mt_transfer(
+        &mut self,
+        args: &RentOnMtTransferArgs,
+        contract_id: &AccountId,
+        token_ids: &Vec<AccountId>,
+        amounts: &Vec<U128>,
+        owner_id: &AccountId
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert!(token_ids.len() == 1 && amounts.len() == 1, "Invalid params");
+
+        // every transferred amount is a new listing, holders can list the same mt token
+        let mt_token_id = token_ids[0].to_string();
+        let id = contract_token_id(&contract_id, &mt_token_id);
+        let nonce = self.rent_mt_nonce_by_id.get(&id).unwrap_or(0) + 1;
+
+        self.rent_mt_nonce_by_id.insert(&id, &nonce);
+
+        let RentOnMtTransferArgs {
+            sale_conditions,
+            max_time,
+            min_time,
+            revenue_share,
+            price_tiers,
+            cancel_fee,
+            transfer_fee,
+        } = args;
+
+        self.internal_rent_add(
+            &contract_id,
+            &mt_listing_id(&mt_token_id, nonce),
+            &owner_id,
+            &sale_conditions,
+            &min_time,
+            &max_time,
+            &None,
+            &revenue_share,
+            &price_tiers.clone().unwrap_or_default(),
+            &cancel_fee,
+            &transfer_fee,
+            &(RentAsset::Mt { token_id: mt_token_id, amount: amounts[0] })
+        );
+
+        PromiseOrValue::Value(vec![U128(0)])
+    }
+
     pub fn internal_on_ft_transfer(
         &mut self,
         args: &RentOnFtTransferArgs,
@@ -33,16 +103,106 @@ impl RentFeature {
         amount: &U128,
         sender_id: &AccountId
     ) -> PromiseOrValue<U128> {
-        let RentOnFtTransferArgs { token_id, contract_id, receiver_id, time } = args;
+        let RentOnFtTransferArgs { token_id, contract_id, receiver_id, time, starts_at } = args;
 
-        self.internal_process_purchase(
+        self.internal_rent_pay(
             &contract_id,
             &token_id,
             &sender_id,
             &receiver_id,
             &time,
             &ft_token_id,
+            &amount,
+            &starts_at
+        )
+    }
+
+    pub fn internal_on_ft_transfer_extend(
+        &mut self,
+        args: &RentOnFtTransferExtendArgs,
+        ft_token_id: &AccountId,
+        amount: &U128,
+        sender_id: &AccountId
+    ) -> PromiseOrValue<U128> {
+        let RentOnFtTransferExtendArgs { token_id, contract_id, time } = args;
+
+        self.internal_rent_extend(&contract_id, &token_id, &sender_id, &time, &ft_token_id, &amount);
+
+        PromiseOrValue::Value(U128(0))
+    }
+
+    pub fn internal_on_ft_transfer_terminate(
+        &mut self,
+        args: &RentOnFtTransferTerminateArgs,
+        ft_token_id: &AccountId,
+        amount: &U128,
+        sender_id: &AccountId
+    ) -> PromiseOrValue<U128> {
+        let RentOnFtTransferTerminateArgs { token_id, contract_id } = args;
+
+        match
+            self.internal_rent_terminate(&contract_id, &token_id, &sender_id, &ft_token_id, &amount)
+        {
+            Some(promise) => promise.into(),
+            None => PromiseOrValue::Value(U128(0)),
+        }
+    }
+
+    // the renter of a collateralized rent returns the nft with nft_transfer_call
+    pub fn internal_on_nft_transfer(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        previous_owner_id: &AccountId
+    ) -> PromiseOrValue<bool> {
+        self.internal_rent_return(&contract_id, &token_id, &previous_owner_id).into()
+    }
+
+    // game contract routes earnings of the rented token
+    pub fn internal_on_ft_transfer_earnings(
+        &mut self,
+        args: &RentOnFtTransferEarningsArgs,
+        ft_token_id: &AccountId,
+        amount: &U128
+    ) -> PromiseOrValue<U128> {
+        let RentOnFtTransferEarningsArgs { token_id, contract_id } = args;
+
+        self.internal_rent_earn(&contract_id, &token_id, &ft_token_id, &amount);
+
+        PromiseOrValue::Value(U128(0))
+    }
+
+    pub fn internal_on_ft_transfer_renter(
+        &mut self,
+        args: &RentOnFtTransferRenterArgs,
+        ft_token_id: &AccountId,
+        amount: &U128,
+        sender_id: &AccountId
+    ) -> PromiseOrValue<U128> {
+        let RentOnFtTransferRenterArgs { token_id, contract_id, receiver_id } = args;
+
+        self.internal_rent_transfer_right(
+            &contract_id,
+            &token_id,
+            &sender_id,
+            &receiver_id,
+            &ft_token_id,
             &amount
-        ).into()
+        );
+
+        PromiseOrValue::Value(U128(0))
+    }
+
+    pub fn internal_on_ft_transfer_sublet(
+        &mut self,
+        args: &RentOnFtTransferSubletArgs,
+        ft_token_id: &AccountId,
+        amount: &U128
+    ) -> PromiseOrValue<U128> {
+        let RentOnFtTransferSubletArgs { token_id, contract_id, receiver_id } = args;
+
+        self.internal_rent_sublet_pay(&contract_id, &token_id, &receiver_id, &ft_token_id, &amount);
+
+        PromiseOrValue::Value(U128(0))
     }
 }
@@ -1,13 +1,26 @@
-use near_sdk::{Promise, AccountId, env, is_promise_success, ext_contract};
-use crate::rent::{contract_token_id, Rent, time_get_minutes, RentFeature, RentPay, RentFeatureResolve, RentClaim};
+use near_sdk::{Promise, PromiseOrValue, AccountId, env, is_promise_success, ext_contract};
+use crate::rent::{
+  contract_token_id,
+  Rent,
+  RentFeature,
+  RentPay,
+  RentFeatureResolve,
+  RentClaim,
+  RentPayment,
+  RentTermination,
+  RentExtend,
+  RentTerminatePropose,
+  RentTerminate,
+  RentReturn,
+  RentAsset,
+  RentRemove,
+};
 use crate::metadata::TokenId;
 use crate::nft::base::GAS_FOR_NFT_TRANSFER;
 use crate::rent::base::GAS_FOR_RENT_PAY;
 use crate::utils::near_ft;
 use near_sdk::json_types::U128;
 use crate::nft::base::external::ext_nft;
-use crate::ft::base::external::ext_ft;
-use crate::ft::base::core_impl::GAS_FOR_FT_TRANSFER;
 
 #[ext_contract(ext_self)]
 trait ExtSelf {
@@ -30,6 +43,23 @@ trait ExtSelf {
     owner_id: AccountId,
     renter_id: AccountId
   );
+  fn rent_resolve_terminate(
+    &mut self,
+    contract_id: AccountId,
+    token_id: TokenId,
+    owner_id: AccountId,
+    renter_id: AccountId,
+    refund: U128,
+    deposit: U128
+  ) -> U128;
+  fn rent_resolve_return(
+    &mut self,
+    contract_id: AccountId,
+    token_id: TokenId,
+    owner_id: AccountId,
+    renter_id: AccountId
+  ) -> bool;
+  fn rent_resolve_remove(&mut self, contract_id: AccountId, token_id: TokenId, is_kept: bool) -> bool;
 }
 
 impl RentFeature {
@@ -42,7 +72,7 @@ impl RentFeature {
     time: &u64,
     ft_token_id: &AccountId,
     deposit: &U128
-  ) -> Promise {
+  ) -> PromiseOrValue<U128> {
     let id = contract_token_id(&contract_id, &token_id);
     let is_paid = self.rents_current.get(&id).is_some();
     let rent: Rent = self.rents_by_id.get(&id).expect("Token is not available for rent");
@@ -50,10 +80,9 @@ impl RentFeature {
     assert!(!is_paid, "Token is already in rent");
     assert_ne!(receiver_id, &rent.owner_id, "Not rent owned token");
 
-    let minutes = time_get_minutes(time.clone()) as u128;
-    let price_per_hour = rent.sale_conditions.get(&ft_token_id).expect("Ft not available");
-
-    let price = ((price_per_hour.0 * minutes) / 60 / 1_000_000) as u128;
+    let price =
+      self.internal_rent_price(&rent, &ft_token_id, &time) +
+      self.internal_rent_collateral(&rent, &ft_token_id);
 
     assert!(
       deposit.0 == price,
@@ -62,20 +91,54 @@ impl RentFeature {
       price.to_string()
     );
 
+    // collateralized rent is transferred to the renter
+    let nft_receiver_id = if rent.collateral.is_some() {
+      receiver_id.clone()
+    } else {
+      env::current_account_id()
+    };
+
     let now = env::block_timestamp();
     let end_time = now + time;
 
-    assert!(time >= &rent.min_time && time <= &rent.max_time, "Invalid rent time");
+    self.assert_no_booking_overlap(&id, now, end_time);
+
+    // revenue share rents have a fixed time
+    let is_valid_time = if let Some(revenue_share) = &rent.revenue_share {
+      time == &revenue_share.time
+    } else {
+      time >= &rent.min_time && time <= &rent.max_time
+    };
+
+    assert!(is_valid_time, "Invalid rent time");
 
     self.rents_pending.remove(&id);
     // self.internal_remove_rent_from_account(&rent.owner_id, &contract_id, &token_id);
     // self.internal_remove_rent_from_contract(&contract_id, &token_id);
 
+    let is_kept = self.rents_kept.remove(&id);
+
+    // mt amount and kept nft are already in the contract
+    if rent.asset != RentAsset::Nft || is_kept {
+      return PromiseOrValue::Value(
+        self.internal_start_rent(
+          &contract_id,
+          &token_id,
+          &rent.owner_id,
+          &receiver_id,
+          &time,
+          &end_time,
+          &ft_token_id,
+          &deposit
+        )
+      );
+    }
+
     ext_nft
     ::ext(contract_id.clone())
       .with_static_gas(GAS_FOR_NFT_TRANSFER)
       .with_attached_deposit(1)
-      .nft_transfer(env::current_account_id(), token_id.clone(), None, None)
+      .nft_transfer(nft_receiver_id, token_id.clone(), None, None)
       .then(
         ext_self
         ::ext(env::current_account_id())
@@ -92,6 +155,7 @@ impl RentFeature {
             deposit.clone()
           )
       )
+      .into()
   }
 
   pub fn internal_resolve_purchase(
@@ -119,19 +183,45 @@ impl RentFeature {
       return price.clone();
     }
 
+    self.internal_start_rent(
+      &contract_id,
+      &token_id,
+      &owner_id,
+      &receiver_id,
+      &time,
+      &end_time,
+      &ft_token_id,
+      &price
+    )
+  }
+
+  pub(crate) fn internal_start_rent(
+    &mut self,
+    contract_id: &AccountId,
+    token_id: &TokenId,
+    owner_id: &AccountId,
+    receiver_id: &AccountId,
+    time: &u64,
+    end_time: &u64,
+    ft_token_id: &AccountId,
+    price: &U128
+  ) -> U128 {
+    let id = contract_token_id(&contract_id, &token_id);
+    let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+    let collateral = U128(self.internal_rent_collateral(&rent, &ft_token_id));
+    let price = U128(price.0 - collateral.0);
+
     self.rents_current.insert(&id, &receiver_id);
-    self.rents_end_by_id.insert(&id, &end_time);
+    self.internal_set_rent_end(&id, &end_time);
+    self.rents_payment_by_id.insert(&id, &RentPayment {
+      ft_token_id: ft_token_id.clone(),
+      price: price.clone(),
+      collateral,
+      started_at: end_time - time,
+    });
     self.internal_add_token_to_account(&receiver_id, &contract_id, &token_id);
 
-    if ft_token_id == &near_ft() {
-      Promise::new(owner_id.clone()).transfer(u128::from(price.clone()));
-    } else {
-      ext_ft
-      ::ext(ft_token_id.clone())
-        .with_static_gas(GAS_FOR_FT_TRANSFER)
-        .with_attached_deposit(1)
-        .ft_transfer(owner_id.clone(), price.clone(), None);
-    }
+    self.internal_rent_transfer(&ft_token_id, &owner_id, &price);
 
     (RentPay {
       token_id: &token_id,
@@ -145,6 +235,258 @@ impl RentFeature {
 
     U128(0)
   }
+
+  pub fn internal_rent_extend(
+    &mut self,
+    contract_id: &AccountId,
+    token_id: &TokenId,
+    account_id: &AccountId,
+    time: &u64,
+    ft_token_id: &AccountId,
+    deposit: &U128
+  ) {
+    let id = contract_token_id(&contract_id, &token_id);
+    let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+    let renter_id = self.rents_current.get(&id).expect("Not found renter");
+
+    assert_eq!(&renter_id, account_id, "Not authorized");
+    assert!(!self.internal_rent_is_ended(&id), "Rent is expired");
+    assert!(rent.revenue_share.is_none(), "Rent with revenue share can not be extended");
+
+    let mut payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+    assert_eq!(&payment.ft_token_id, ft_token_id, "Invalid ft token");
+
+    let current_end_time = self.rents_end_by_id.get(&id).unwrap();
+    let end_time = current_end_time + time;
+
+    assert!(
+      time > &0 && end_time - payment.started_at <= rent.max_time,
+      "Invalid rent time"
+    );
+
+    self.assert_no_booking_overlap(&id, current_end_time, end_time);
+
+    let price = self.internal_rent_price(&rent, &ft_token_id, &time);
+
+    assert!(
+      deposit.0 == price,
+      "Invalid attached deposit {}, price {}",
+      deposit.0.to_string(),
+      price.to_string()
+    );
+
+    payment.price = U128(payment.price.0 + price);
+
+    self.internal_set_rent_end(&id, &end_time);
+    self.rents_payment_by_id.insert(&id, &payment);
+
+    self.internal_rent_transfer(&ft_token_id, &rent.owner_id, &deposit);
+
+    (RentExtend {
+      token_id: &token_id,
+      contract_id: &contract_id,
+      renter_id: &renter_id,
+      ft_token_id: &ft_token_id,
+      time: &time,
+      end_time: &end_time,
+      price: &deposit,
+    }).emit();
+  }
+
+  // the owner funds the refund with deposit, the termination is done once both sides agreed
+  pub fn internal_rent_terminate(
+    &mut self,
+    contract_id: &AccountId,
+    token_id: &TokenId,
+    account_id: &AccountId,
+    ft_token_id: &AccountId,
+    deposit: &U128
+  ) -> Option<Promise> {
+    let id = contract_token_id(&contract_id, &token_id);
+    let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+    let renter_id = self.rents_current.get(&id).expect("Not found renter");
+    let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+    assert!(!self.internal_rent_is_ended(&id), "Rent is expired");
+    assert_eq!(payment.collateral.0, 0, "Rent with collateral can not be terminated");
+
+    let is_owner = account_id == &rent.owner_id;
+
+    assert!(is_owner || account_id == &renter_id, "Not authorized");
+
+    if is_owner {
+      assert_eq!(&payment.ft_token_id, ft_token_id, "Invalid ft token");
+    } else {
+      assert_eq!(deposit.0, 0, "Deposit is not required");
+    }
+
+    let termination = if let Some(termination) = self.rents_termination_by_id.get(&id) {
+      termination
+    } else {
+      self.rents_termination_by_id.insert(&id, &RentTermination {
+        account_id: account_id.clone(),
+        deposit: deposit.clone(),
+      });
+
+      (RentTerminatePropose {
+        token_id: &token_id,
+        contract_id: &contract_id,
+        account_id: &account_id,
+        deposit: &deposit,
+      }).emit();
+
+      return None;
+    };
+
+    assert_ne!(&termination.account_id, account_id, "Termination already proposed");
+
+    let deposit = if is_owner { deposit.clone() } else { termination.deposit };
+    let refund = self.internal_rent_refund(&id);
+
+    assert!(
+      deposit.0 >= refund,
+      "Invalid deposit {}, refund {}",
+      deposit.0.to_string(),
+      refund.to_string()
+    );
+
+    self.rents_termination_by_id.remove(&id);
+
+    if self.internal_keep_rent_asset(&id) {
+      self.internal_terminate_rent(
+        &contract_id,
+        &token_id,
+        &rent.owner_id,
+        &renter_id,
+        &U128(refund),
+        &deposit
+      );
+
+      return None;
+    }
+
+    Some(
+      self
+        .internal_rent_asset_transfer(&rent, &rent.owner_id)
+        .then(
+          ext_self
+          ::ext(env::current_account_id())
+            .with_static_gas(env::prepaid_gas() - GAS_FOR_RENT_PAY)
+            .rent_resolve_terminate(
+              contract_id.clone(),
+              token_id.clone(),
+              rent.owner_id.clone(),
+              renter_id.clone(),
+              U128(refund),
+              deposit
+            )
+        )
+    )
+  }
+}
+
+impl RentFeature {
+  /// failed transfer keeps the rent claimable, returns true when the rent is claimed
+  pub(crate) fn internal_resolve_claim(
+    &mut self,
+    contract_id: &AccountId,
+    token_id: &TokenId,
+    owner_id: &AccountId,
+    renter_id: &AccountId
+  ) -> bool {
+    let id = contract_token_id(&contract_id, &token_id);
+
+    if !is_promise_success() {
+      // keeper removes the rent from the index before the claim
+      if let Some(end_time) = self.rents_end_by_id.get(&id) {
+        self.rents_by_end_at.insert(&(end_time, id), &());
+      }
+
+      return false;
+    }
+
+    self.internal_claim_rent(&contract_id, &token_id, &owner_id, &renter_id);
+
+    true
+  }
+
+  pub(crate) fn internal_claim_rent(
+    &mut self,
+    contract_id: &AccountId,
+    token_id: &TokenId,
+    owner_id: &AccountId,
+    renter_id: &AccountId
+  ) {
+    self.internal_remove_paid_rent(&owner_id, &renter_id, &contract_id, &token_id);
+
+    (RentClaim {
+      token_id: &token_id,
+      contract_id: &contract_id,
+      owner_id: &owner_id,
+      renter_id: &renter_id,
+    }).emit();
+  }
+
+  pub(crate) fn internal_terminate_rent(
+    &mut self,
+    contract_id: &AccountId,
+    token_id: &TokenId,
+    owner_id: &AccountId,
+    renter_id: &AccountId,
+    refund: &U128,
+    deposit: &U128
+  ) {
+    let id = contract_token_id(&contract_id, &token_id);
+    let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+    // history keeps the price without refund
+    let mut paid = payment.clone();
+    paid.price = U128(payment.price.0 - refund.0);
+    self.rents_payment_by_id.insert(&id, &paid);
+
+    self.internal_remove_paid_rent(&owner_id, &renter_id, &contract_id, &token_id);
+
+    self.internal_rent_transfer(&payment.ft_token_id, &renter_id, &refund);
+    self.internal_rent_transfer(&payment.ft_token_id, &owner_id, &U128(deposit.0 - refund.0));
+
+    (RentTerminate {
+      token_id: &token_id,
+      contract_id: &contract_id,
+      owner_id: &owner_id,
+      renter_id: &renter_id,
+      ft_token_id: &payment.ft_token_id,
+      refund: &refund,
+    }).emit();
+  }
+
+  pub fn internal_rent_return(
+    &mut self,
+    contract_id: &AccountId,
+    token_id: &TokenId,
+    previous_owner_id: &AccountId
+  ) -> Promise {
+    let id = contract_token_id(&contract_id, &token_id);
+    let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+    let renter_id = self.rents_current.get(&id).expect("Not found renter");
+    let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+    assert!(payment.collateral.0 > 0, "Rent without collateral");
+    assert_eq!(&renter_id, previous_owner_id, "Not authorized");
+    assert!(!self.internal_rent_is_ended(&id), "Rent is expired");
+
+    ext_nft
+    ::ext(contract_id.clone())
+      .with_static_gas(GAS_FOR_NFT_TRANSFER)
+      .with_attached_deposit(1)
+      .nft_transfer(rent.owner_id.clone(), token_id.clone(), None, None)
+      .then(
+        ext_self
+        ::ext(env::current_account_id())
+          .with_static_gas(env::prepaid_gas() - GAS_FOR_RENT_PAY)
+          .rent_resolve_return(contract_id.clone(), token_id.clone(), rent.owner_id, renter_id)
+      )
+  }
 }
 
 impl RentFeatureResolve for RentFeature {
@@ -180,22 +522,78 @@ impl RentFeatureResolve for RentFeature {
     owner_id: AccountId,
     renter_id: AccountId
   ) {
-    let is_success = is_promise_success();
+    self.internal_resolve_claim(&contract_id, &token_id, &owner_id, &renter_id);
+  }
 
-    if !is_success {
-      env::panic_str("Error during transfer nft");
+  fn rent_resolve_terminate(
+    &mut self,
+    contract_id: AccountId,
+    token_id: TokenId,
+    owner_id: AccountId,
+    renter_id: AccountId,
+    refund: U128,
+    deposit: U128
+  ) -> U128 {
+    let id = contract_token_id(&contract_id, &token_id);
+    let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+    if !is_promise_success() {
+      self.internal_rent_transfer(&payment.ft_token_id, &owner_id, &deposit);
+
+      return U128(0);
     }
 
-    self.internal_remove_current_rent(&owner_id, &contract_id, &token_id);
-    self.internal_remove_token_from_account(&renter_id, &contract_id, &token_id);
-    self.internal_remove_rent_from_contract(&contract_id, &token_id);
-    self.internal_remove_rent_from_account(&owner_id, &contract_id, &token_id);
+    self.internal_terminate_rent(&contract_id, &token_id, &owner_id, &renter_id, &refund, &deposit);
 
-    (RentClaim {
+    U128(0)
+  }
+
+  fn rent_resolve_remove(&mut self, contract_id: AccountId, token_id: TokenId, is_kept: bool) -> bool {
+    let id = contract_token_id(&contract_id, &token_id);
+    let rent = self.rents_removed_by_id.remove(&id).expect("Not found rent");
+
+    if !is_promise_success() {
+      self.internal_restore_rent(&id, &rent, is_kept);
+
+      return false;
+    }
+
+    (RentRemove {
+      token_id: &token_id,
+      contract_id: &contract_id,
+      account_id: &rent.owner_id,
+    }).emit();
+
+    true
+  }
+
+  fn rent_resolve_return(
+    &mut self,
+    contract_id: AccountId,
+    token_id: TokenId,
+    owner_id: AccountId,
+    renter_id: AccountId
+  ) -> bool {
+    // rent is kept, the nft goes back to the renter to return it again
+    if !is_promise_success() {
+      return true;
+    }
+
+    let id = contract_token_id(&contract_id, &token_id);
+    let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+    self.internal_remove_paid_rent(&owner_id, &renter_id, &contract_id, &token_id);
+    self.internal_rent_transfer(&payment.ft_token_id, &renter_id, &payment.collateral);
+
+    (RentReturn {
       token_id: &token_id,
       contract_id: &contract_id,
       owner_id: &owner_id,
       renter_id: &renter_id,
+      ft_token_id: &payment.ft_token_id,
+      collateral: &payment.collateral,
     }).emit();
+
+    false
   }
 }
@@ -0,0 +1,280 @@
+use near_sdk::{ AccountId, env };
+use near_sdk::json_types::U128;
+use crate::rent::base::internal::RENT_MAX_SHARE;
+use crate::rent::{
+    RentFeature,
+    RentFeatureCore,
+    RentFeatureSublet,
+    RentSublet,
+    RentTransfer,
+    RentTransferFeeUpdate,
+    RentSubletAdd,
+    RentSubletRemove,
+    Rent,
+    SaleConditions,
+    TokenId,
+    contract_token_id,
+};
+use crate::utils::near_ft;
+
+impl RentFeature {
+    fn assert_transferable(&self, id: &TokenId, rent: &Rent) -> u16 {
+        let transfer_fee = rent.transfer_fee.expect("Rent transfer is not allowed");
+        let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+        // the rent is passed on behalf of the owner, so the token must be still approved
+        let is_approved = self.rent_is_approved(
+            rent.contract_id.clone(),
+            rent.token_id.clone(),
+            rent.owner_id.clone()
+        );
+
+        assert!(is_approved, "Not approved for owner");
+
+        assert_eq!(payment.collateral.0, 0, "Rent with collateral can not be transferred");
+        assert!(!self.internal_rent_is_ended(&id), "Rent is expired");
+
+        transfer_fee
+    }
+
+    fn internal_move_renter(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        old_renter_id: &AccountId,
+        renter_id: &AccountId
+    ) {
+        let id = contract_token_id(&contract_id, &token_id);
+        let refund = self.internal_rent_refund(&id);
+        let mut payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+        self.internal_remove_termination(&id);
+        self.rent_sublets_by_id.remove(&id);
+
+        // the outgoing renter gets the used time in the history, the remaining value moves on
+        payment.price = U128(payment.price.0 - refund);
+        self.rents_payment_by_id.insert(&id, &payment);
+        self.internal_add_rent_record(&id);
+
+        payment.price = U128(refund);
+        payment.started_at = env::block_timestamp();
+        self.rents_payment_by_id.insert(&id, &payment);
+
+        self.rents_current.insert(&id, &renter_id);
+        self.internal_remove_token_from_account(&old_renter_id, &contract_id, &token_id);
+        self.internal_add_token_to_account(&renter_id, &contract_id, &token_id);
+    }
+
+    /// the renter passes the rent, owner fee is taken from the remaining rent value
+    pub fn internal_rent_transfer_right(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        receiver_id: &AccountId,
+        ft_token_id: &AccountId,
+        deposit: &U128
+    ) {
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+        let renter_id = self.rents_current.get(&id).expect("Not found renter");
+
+        assert_eq!(account_id, &renter_id, "Not authorized");
+        assert_ne!(receiver_id, &renter_id, "Receiver is already a renter");
+        assert_ne!(receiver_id, &rent.owner_id, "Not rent owned token");
+
+        let transfer_fee = self.assert_transferable(&id, &rent);
+        let payment = self.rents_payment_by_id.get(&id).expect("Not found payment");
+
+        assert_eq!(&payment.ft_token_id, ft_token_id, "Invalid ft token");
+
+        let fee =
+            (self.internal_rent_refund(&id) * (transfer_fee as u128)) / (RENT_MAX_SHARE as u128);
+
+        assert!(
+            deposit.0 == fee,
+            "Invalid attached deposit {}, fee {}",
+            deposit.0.to_string(),
+            fee.to_string()
+        );
+
+        self.internal_rent_transfer(&ft_token_id, &rent.owner_id, &U128(fee));
+        self.internal_move_renter(&contract_id, &token_id, &renter_id, &receiver_id);
+
+        (RentTransfer {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            owner_id: &rent.owner_id,
+            old_renter_id: &renter_id,
+            renter_id: &receiver_id,
+            ft_token_id: &ft_token_id,
+            price: &U128(0),
+            fee: &U128(fee),
+        }).emit();
+    }
+
+    /// buys the remaining rent time listed by the renter
+    pub fn internal_rent_sublet_pay(
+        &mut self,
+        contract_id: &AccountId,
+        token_id: &TokenId,
+        receiver_id: &AccountId,
+        ft_token_id: &AccountId,
+        deposit: &U128
+    ) {
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+        let sublet = self.rent_sublets_by_id.get(&id).expect("Not found sublet");
+        let renter_id = self.rents_current.get(&id).expect("Not found renter");
+
+        assert_eq!(sublet.renter_id, renter_id, "Not found sublet");
+        assert_ne!(receiver_id, &renter_id, "Receiver is already a renter");
+        assert_ne!(receiver_id, &rent.owner_id, "Not rent owned token");
+
+        let transfer_fee = self.assert_transferable(&id, &rent);
+        let price = sublet.sale_conditions.get(&ft_token_id).expect("Not available ft").0;
+
+        assert!(
+            deposit.0 == price,
+            "Invalid attached deposit {}, price {}",
+            deposit.0.to_string(),
+            price.to_string()
+        );
+
+        let fee = (price * (transfer_fee as u128)) / (RENT_MAX_SHARE as u128);
+
+        self.internal_rent_transfer(&ft_token_id, &rent.owner_id, &U128(fee));
+        self.internal_rent_transfer(&ft_token_id, &renter_id, &U128(price - fee));
+        self.internal_move_renter(&contract_id, &token_id, &renter_id, &receiver_id);
+
+        (RentTransfer {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            owner_id: &rent.owner_id,
+            old_renter_id: &renter_id,
+            renter_id: &receiver_id,
+            ft_token_id: &ft_token_id,
+            price: &U128(price),
+            fee: &U128(fee),
+        }).emit();
+    }
+}
+
+impl RentFeatureSublet for RentFeature {
+    fn rent_update_transfer_fee(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        transfer_fee: Option<u16>
+    ) {
+        let account_id = env::predecessor_account_id();
+
+        self.assert_approved(&contract_id, &token_id);
+
+        let id = contract_token_id(&contract_id, &token_id);
+        let mut rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+
+        assert_eq!(account_id, rent.owner_id, "Unauthorized");
+        assert!(transfer_fee.unwrap_or(0) <= RENT_MAX_SHARE, "Invalid transfer fee");
+
+        rent.transfer_fee = transfer_fee;
+        self.rents_by_id.insert(&id, &rent);
+
+        // forbidden transfers close the listing of the renter
+        if transfer_fee.is_none() {
+            if let Some(sublet) = self.rent_sublets_by_id.remove(&id) {
+                (RentSubletRemove {
+                    token_id: &token_id,
+                    contract_id: &contract_id,
+                    renter_id: &sublet.renter_id,
+                }).emit();
+            }
+        }
+
+        (RentTransferFeeUpdate {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            owner_id: &rent.owner_id,
+            transfer_fee: &rent.transfer_fee,
+        }).emit();
+    }
+
+    fn rent_transfer(&mut self, contract_id: AccountId, token_id: TokenId, receiver_id: AccountId) {
+        let deposit = env::attached_deposit();
+
+        self.internal_rent_transfer_right(
+            &contract_id,
+            &token_id,
+            &env::predecessor_account_id(),
+            &receiver_id,
+            &near_ft(),
+            &U128::from(deposit)
+        )
+    }
+
+    fn rent_sublet_add(
+        &mut self,
+        contract_id: AccountId,
+        token_id: TokenId,
+        sale_conditions: SaleConditions
+    ) {
+        let account_id = env::predecessor_account_id();
+        let id = contract_token_id(&contract_id, &token_id);
+        let rent: Rent = self.rents_by_id.get(&id).expect("Not found rent");
+        let renter_id = self.rents_current.get(&id).expect("Not found renter");
+
+        assert_eq!(account_id, renter_id, "Not authorized");
+        assert!(!sale_conditions.is_empty(), "Invalid sale conditions");
+
+        self.assert_transferable(&id, &rent);
+
+        let end_time = self.rents_end_by_id.get(&id).expect("Not found rent");
+
+        self.rent_sublets_by_id.insert(&id, &(RentSublet {
+            renter_id: renter_id.clone(),
+            sale_conditions: sale_conditions.clone(),
+        }));
+
+        (RentSubletAdd {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            renter_id: &renter_id,
+            sale_conditions: &sale_conditions,
+            end_time: &end_time,
+        }).emit();
+    }
+
+    fn rent_sublet_remove(&mut self, contract_id: AccountId, token_id: TokenId) {
+        let account_id = env::predecessor_account_id();
+        let id = contract_token_id(&contract_id, &token_id);
+        let sublet = self.rent_sublets_by_id.get(&id).expect("Not found sublet");
+
+        assert_eq!(account_id, sublet.renter_id, "Not authorized");
+
+        self.rent_sublets_by_id.remove(&id);
+
+        (RentSubletRemove {
+            token_id: &token_id,
+            contract_id: &contract_id,
+            renter_id: &sublet.renter_id,
+        }).emit();
+    }
+
+    fn rent_sublet_pay(&mut self, contract_id: AccountId, token_id: TokenId, receiver_id: AccountId) {
+        let deposit = env::attached_deposit();
+
+        self.internal_rent_sublet_pay(
+            &contract_id,
+            &token_id,
+            &receiver_id,
+            &near_ft(),
+            &U128::from(deposit)
+        )
+    }
+
+    fn rent_sublet(&self, contract_id: AccountId, token_id: TokenId) -> Option<RentSublet> {
+        let id = contract_token_id(&contract_id, &token_id);
+
+        self.rent_sublets_by_id.get(&id)
+    }
+}
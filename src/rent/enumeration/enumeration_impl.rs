@@ -1,6 +1,14 @@
 use near_sdk::json_types::U128;
 use near_sdk::AccountId;
-use crate::rent::{ RentFeatureEnumeration, RentFeature, JsonRent, contract_token_id, TokenId };
+use crate::rent::{
+    RentFeatureEnumeration,
+    RentFeature,
+    JsonRent,
+    contract_token_id,
+    TokenId,
+    RentFilter,
+    RentsPage,
+};
 
 impl RentFeatureEnumeration for RentFeature {
     fn rents(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonRent> {
@@ -98,4 +106,30 @@ impl RentFeatureEnumeration for RentFeature {
 
         U128::from(0)
     }
+
+    fn rents_available(
+        &self,
+        filter: Option<RentFilter>,
+        cursor: Option<TokenId>,
+        limit: u64
+    ) -> RentsPage {
+        let filter = filter.unwrap_or_default();
+
+        self.enum_rents_page(cursor, limit, |rent| {
+            rent.renter_id.is_none() &&
+                self.rents_pending.contains(&contract_token_id(&rent.contract_id, &rent.token_id)) &&
+                filter.matches(rent)
+        })
+    }
+
+    fn rents_ending_before(
+        &self,
+        timestamp: u64,
+        cursor: Option<TokenId>,
+        limit: u64
+    ) -> RentsPage {
+        self.enum_rents_page(cursor, limit, |rent| {
+            rent.renter_id.is_some() && rent.ended_at.map(|ended_at| ended_at < timestamp).unwrap_or(false)
+        })
+    }
 }
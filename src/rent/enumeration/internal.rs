@@ -1,7 +1,48 @@
-use crate::rent::{RentFeature, TokenId, JsonRent, contract_token_id};
+use crate::rent::{RentFeature, TokenId, JsonRent, Rent, RentsPage, contract_token_id};
 use near_sdk::{AccountId, env};
 
+pub const MAX_RENTS_SCAN: u64 = 500;
+
 impl RentFeature {
+  /// scans at most `MAX_RENTS_SCAN` rents after `cursor`
+  pub(crate) fn enum_rents_page<F>(&self, cursor: Option<TokenId>, limit: u64, is_match: F) -> RentsPage
+  where
+    F: Fn(&JsonRent) -> bool
+  {
+    let ids: Box<dyn Iterator<Item = (TokenId, Rent)>> = if let Some(cursor) = cursor {
+      Box::new(self.rents_by_id.iter_from(cursor))
+    } else {
+      Box::new(self.rents_by_id.iter())
+    };
+    let mut ids = ids.map(|(id, _)| id).peekable();
+
+    let mut rents = vec![];
+    let mut next_cursor = None;
+    let mut scanned = 0;
+
+    while (rents.len() as u64) < limit && scanned < MAX_RENTS_SCAN {
+      let id = if let Some(id) = ids.next() {
+        id
+      } else {
+        break;
+      };
+      scanned += 1;
+
+      if let Some(rent) = self.enum_rent(&id) {
+        if is_match(&rent) {
+          rents.push(rent);
+        }
+      }
+
+      next_cursor = Some(id);
+    }
+
+    RentsPage {
+      rents,
+      next_cursor: if ids.peek().is_some() { next_cursor } else { None },
+    }
+  }
+
   pub(crate) fn internal_rent_is_ended(&self, id: &String) -> bool {
     let rent_end_at = self.rents_end_by_id.get(&id).expect(&format!("Not found {}", id.to_string()));
     let now = env::block_timestamp();
@@ -21,6 +62,12 @@ impl RentFeature {
         ended_at: self.rents_end_by_id.get(&contract_token_id),
         renter_id: self.rents_current.get(&contract_token_id),
         created_at: rent.created_at,
+        collateral: rent.collateral,
+        revenue_share: rent.revenue_share,
+        price_tiers: rent.price_tiers,
+        cancel_fee: rent.cancel_fee,
+        transfer_fee: rent.transfer_fee,
+        asset: rent.asset,
       })
     } else {
       None
@@ -32,7 +79,14 @@ impl RentFeature {
     nft_contract_id: &AccountId,
     token_id: &TokenId
   ) -> bool {
-    self.rents_current.get(&contract_token_id(&nft_contract_id, &token_id)).is_some()
+    let id = contract_token_id(&nft_contract_id, &token_id);
+    let now = env::block_timestamp();
+
+    self.rents_current.get(&id).is_some() ||
+      self.rent_bookings_by_id
+        .get(&id)
+        .map(|bookings| bookings.iter().any(|booking| booking.starts_at <= now && now < booking.ends_at))
+        .unwrap_or(false)
   }
 
   pub(crate) fn internal_rent_token_ids_for_account(
@@ -1,9 +1,11 @@
 mod enumeration_impl;
 mod internal;
 
+pub use self::internal::MAX_RENTS_SCAN;
+
 use near_sdk::json_types::U128;
 use near_sdk::AccountId;
-use crate::rent::{ JsonRent, TokenId };
+use crate::rent::{ JsonRent, TokenId, RentFilter, RentsPage };
 
 pub trait RentFeatureEnumeration {
     fn rents(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonRent>;
@@ -33,4 +35,19 @@ pub trait RentFeatureEnumeration {
     fn rents_supply_by_contract(&self, contract_id: AccountId) -> U128;
 
     fn rent(&self, contract_id: AccountId, token_id: TokenId) -> Option<JsonRent>;
+
+    /// rents available for pay now
+    fn rents_available(
+        &self,
+        filter: Option<RentFilter>,
+        cursor: Option<TokenId>,
+        limit: u64
+    ) -> RentsPage;
+    /// paid rents with end time before `timestamp`, including already ended
+    fn rents_ending_before(
+        &self,
+        timestamp: u64,
+        cursor: Option<TokenId>,
+        limit: u64
+    ) -> RentsPage;
 }
@@ -22,7 +22,7 @@ use near_sdk::AccountId;
 use serde::Serialize;
 use near_sdk::json_types::U128;
 use crate::rent::TokenId;
-use crate::rent::meta::SaleConditions;
+use crate::rent::meta::{ SaleConditions, RevenueShare, PriceTiers, PriceTier, RentAsset };
 
 /// Data to log for an NFT mint event. To log this event, call [`.emit()`](NftMint::emit).
 #[must_use]
@@ -35,6 +35,12 @@ pub struct RentAdd<'a> {
     pub min_time: &'a u64,
     pub max_time: &'a u64,
     pub created_at: &'a u64,
+    pub collateral: &'a Option<SaleConditions>,
+    pub revenue_share: &'a Option<RevenueShare>,
+    pub price_tiers: &'a PriceTiers,
+    pub cancel_fee: &'a Option<u16>,
+    pub transfer_fee: &'a Option<u16>,
+    pub asset: &'a RentAsset,
 }
 
 impl RentAdd<'_> {
@@ -159,6 +165,494 @@ impl RentClaim<'_> {
 
 //
 
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentExtend<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub time: &'a u64,
+    pub end_time: &'a u64,
+    pub price: &'a U128,
+}
+
+impl RentExtend<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent extend event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentExtend`] represents the data of each extend.
+    pub fn emit_many(data: &[RentExtend<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentExtend(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentTerminatePropose<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub account_id: &'a AccountId,
+    pub deposit: &'a U128,
+}
+
+impl RentTerminatePropose<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent terminate propose event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentTerminatePropose`] represents the data of each propose.
+    pub fn emit_many(data: &[RentTerminatePropose<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentTerminatePropose(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentTerminateCancel<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub account_id: &'a AccountId,
+}
+
+impl RentTerminateCancel<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent terminate cancel event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentTerminateCancel`] represents the data of each cancel.
+    pub fn emit_many(data: &[RentTerminateCancel<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentTerminateCancel(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentTerminate<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub refund: &'a U128,
+}
+
+impl RentTerminate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent terminate event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentTerminate`] represents the data of each terminate.
+    pub fn emit_many(data: &[RentTerminate<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentTerminate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentReturn<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub collateral: &'a U128,
+}
+
+impl RentReturn<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent return event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentReturn`] represents the data of each return.
+    pub fn emit_many(data: &[RentReturn<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentReturn(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentCollateralClaim<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub collateral: &'a U128,
+}
+
+impl RentCollateralClaim<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent collateral claim event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentCollateralClaim`] represents the data of each claim.
+    pub fn emit_many(data: &[RentCollateralClaim<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentCollateralClaim(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentEarn<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub owner_amount: &'a U128,
+    pub renter_amount: &'a U128,
+}
+
+impl RentEarn<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent earn event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentEarn`] represents the data of each earn.
+    pub fn emit_many(data: &[RentEarn<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentEarn(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentEarningsClaim<'a> {
+    pub account_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl RentEarningsClaim<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent earnings claim event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentEarningsClaim`] represents the data of each claim.
+    pub fn emit_many(data: &[RentEarningsClaim<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentEarningsClaim(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentEarningsClaimFail<'a> {
+    pub account_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl RentEarningsClaimFail<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent earnings claim fail event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentEarningsClaimFail`] represents the data of each fail.
+    pub fn emit_many(data: &[RentEarningsClaimFail<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentEarningsClaimFail(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentUpdatePriceTiers<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub price_tiers: &'a Vec<PriceTier>,
+}
+
+impl RentUpdatePriceTiers<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent price tiers update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentUpdatePriceTiers`] represents the data of each update.
+    pub fn emit_many(data: &[RentUpdatePriceTiers<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentUpdatePriceTiers(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentKeeperReward<'a> {
+    pub account_id: &'a AccountId,
+    pub claimed: &'a u64,
+    pub amount: &'a U128,
+}
+
+impl RentKeeperReward<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent keeper reward event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentKeeperReward`] represents the data of each reward.
+    pub fn emit_many(data: &[RentKeeperReward<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentKeeperReward(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentKeeperPoolDeposit<'a> {
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl RentKeeperPoolDeposit<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent keeper pool deposit event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentKeeperPoolDeposit`] represents the data of each deposit.
+    pub fn emit_many(data: &[RentKeeperPoolDeposit<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentKeeperPoolDeposit(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentKeeperRewardUpdate<'a> {
+    pub reward: &'a U128,
+}
+
+impl RentKeeperRewardUpdate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent keeper reward update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentKeeperRewardUpdate`] represents the data of each update.
+    pub fn emit_many(data: &[RentKeeperRewardUpdate<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentKeeperRewardUpdate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentBook<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub starts_at: &'a u64,
+    pub ends_at: &'a u64,
+    pub ft_token_id: &'a AccountId,
+    pub price: &'a U128,
+}
+
+impl RentBook<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent book event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentBook`] represents the data of each book.
+    pub fn emit_many(data: &[RentBook<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentBook(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentBookingCancel<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub starts_at: &'a u64,
+    pub refund: &'a U128,
+    pub fee: &'a U128,
+}
+
+impl RentBookingCancel<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent booking cancel event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentBookingCancel`] represents the data of each cancel.
+    pub fn emit_many(data: &[RentBookingCancel<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentBookingCancel(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentTransferFeeUpdate<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub transfer_fee: &'a Option<u16>,
+}
+
+impl RentTransferFeeUpdate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a transfer fee update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentTransferFeeUpdate`] represents the data of each update.
+    pub fn emit_many(data: &[RentTransferFeeUpdate<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentTransferFeeUpdate(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentTransfer<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub old_renter_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub ft_token_id: &'a AccountId,
+    pub price: &'a U128,
+    pub fee: &'a U128,
+}
+
+impl RentTransfer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rent transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentTransfer`] represents the data of each transfer.
+    pub fn emit_many(data: &[RentTransfer<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentTransfer(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentSubletAdd<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+    pub sale_conditions: &'a SaleConditions,
+    pub end_time: &'a u64,
+}
+
+impl RentSubletAdd<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a sublet listing event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentSubletAdd`] represents the data of each listing.
+    pub fn emit_many(data: &[RentSubletAdd<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentSubletAdd(data)).emit()
+    }
+}
+
+//
+
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RentSubletRemove<'a> {
+    pub token_id: &'a TokenId,
+    pub contract_id: &'a AccountId,
+    pub renter_id: &'a AccountId,
+}
+
+impl RentSubletRemove<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a sublet removal event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RentSubletRemove`] represents the data of each removal.
+    pub fn emit_many(data: &[RentSubletRemove<'_>]) {
+        new_mfight_rent_v1(MfRentEventKind::RentSubletRemove(data)).emit()
+    }
+}
+
+//
+
 #[derive(Serialize, Debug)]
 pub(crate) struct MfRentEvent<'a> {
     version: &'static str,
@@ -176,6 +670,25 @@ enum MfRentEventKind<'a> {
     RentRemove(&'a [RentRemove<'a>]),
     RentPay(&'a [RentPay<'a>]),
     RentClaim(&'a [RentClaim<'a>]),
+    RentExtend(&'a [RentExtend<'a>]),
+    RentTerminatePropose(&'a [RentTerminatePropose<'a>]),
+    RentTerminateCancel(&'a [RentTerminateCancel<'a>]),
+    RentTerminate(&'a [RentTerminate<'a>]),
+    RentReturn(&'a [RentReturn<'a>]),
+    RentCollateralClaim(&'a [RentCollateralClaim<'a>]),
+    RentEarn(&'a [RentEarn<'a>]),
+    RentEarningsClaim(&'a [RentEarningsClaim<'a>]),
+    RentEarningsClaimFail(&'a [RentEarningsClaimFail<'a>]),
+    RentUpdatePriceTiers(&'a [RentUpdatePriceTiers<'a>]),
+    RentKeeperReward(&'a [RentKeeperReward<'a>]),
+    RentKeeperPoolDeposit(&'a [RentKeeperPoolDeposit<'a>]),
+    RentKeeperRewardUpdate(&'a [RentKeeperRewardUpdate<'a>]),
+    RentBook(&'a [RentBook<'a>]),
+    RentBookingCancel(&'a [RentBookingCancel<'a>]),
+    RentTransferFeeUpdate(&'a [RentTransferFeeUpdate<'a>]),
+    RentTransfer(&'a [RentTransfer<'a>]),
+    RentSubletAdd(&'a [RentSubletAdd<'a>]),
+    RentSubletRemove(&'a [RentSubletRemove<'a>]),
 }
 
 fn new_mfight_rent<'a>(version: &'static str, event_kind: MfRentEventKind<'a>) -> NearEvent<'a> {
@@ -15,26 +15,51 @@ macro_rules! impl_rent_core {
             $(self.$assert_action();)?
             self.$tokens.rent_update(contract_id, token_id, ft_token_id, price_per_hour, min_time, max_time)
           }
+          fn rent_update_price_tiers(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, ft_token_id: AccountId, price_tiers: Vec<$crate::rent::PriceTier>) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_update_price_tiers(contract_id, token_id, ft_token_id, price_tiers)
+          }
           fn rent_remove(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId) {
             $(self.$assert_action();)?
             self.$tokens.rent_remove(contract_id, token_id)
           }
 
           #[payable]
-          fn rent_pay(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, time: u64, receiver_id: AccountId) -> near_sdk::Promise {
+          fn rent_pay(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, time: u64, receiver_id: AccountId, starts_at: Option<u64>) -> near_sdk::PromiseOrValue<near_sdk::json_types::U128> {
             $(self.$assert_action();)?
-            self.$tokens.rent_pay(contract_id, token_id, time, receiver_id)
+            self.$tokens.rent_pay(contract_id, token_id, time, receiver_id, starts_at)
           }
-          fn rent_claim(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> near_sdk::Promise {
+          fn rent_claim(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Option<near_sdk::Promise> {
             $(self.$assert_action();)?
             self.$tokens.rent_claim(contract_id, token_id)
           }
+
+          #[payable]
+          fn rent_extend(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, time: u64) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_extend(contract_id, token_id, time)
+          }
+          #[payable]
+          fn rent_terminate(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Option<near_sdk::Promise> {
+            $(self.$assert_action();)?
+            self.$tokens.rent_terminate(contract_id, token_id)
+          }
+          fn rent_terminate_cancel(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_terminate_cancel(contract_id, token_id)
+          }
+          fn rent_terminate_refund(&self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> near_sdk::json_types::U128 {
+            self.$tokens.rent_terminate_refund(contract_id, token_id)
+          }
           fn rent_is_ended(&self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> bool {
             self.$tokens.rent_is_ended(contract_id, token_id)
           }
           fn rent_total_supply(&self) -> u64 {
             self.$tokens.rent_total_supply()
           }
+          fn rent_price(&self, contract_id: AccountId, token_id: $crate::rent::TokenId, ft_token_id: AccountId, time: u64) -> near_sdk::json_types::U128 {
+            self.$tokens.rent_price(contract_id, token_id, ft_token_id, time)
+          }
           fn rent_is_approved(&self, contract_id: AccountId, token_id: $crate::rent::TokenId, account_id: AccountId) -> bool {
             self.$tokens.rent_is_approved(contract_id, token_id, account_id)
           }
@@ -51,6 +76,18 @@ macro_rules! impl_rent_core {
           fn rent_resolve_claim(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, owner_id: AccountId, renter_id: AccountId) {
             self.$tokens.rent_resolve_claim(contract_id, token_id, owner_id, renter_id)
           }
+          #[private]
+          fn rent_resolve_terminate(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, owner_id: AccountId, renter_id: AccountId, refund: near_sdk::json_types::U128, deposit: near_sdk::json_types::U128) -> near_sdk::json_types::U128 {
+            self.$tokens.rent_resolve_terminate(contract_id, token_id, owner_id, renter_id, refund, deposit)
+          }
+          #[private]
+          fn rent_resolve_return(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, owner_id: AccountId, renter_id: AccountId) -> bool {
+            self.$tokens.rent_resolve_return(contract_id, token_id, owner_id, renter_id)
+          }
+          #[private]
+          fn rent_resolve_remove(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, is_kept: bool) -> bool {
+            self.$tokens.rent_resolve_remove(contract_id, token_id, is_kept)
+          }
         }
     };
 }
@@ -90,6 +127,152 @@ macro_rules! impl_rent_enumeration {
           fn rents_supply_by_contract(&self, contract_id: AccountId) -> near_sdk::json_types::U128 {
             self.$tokens.rents_supply_by_contract(contract_id)
           }
+
+          fn rents_available(&self, filter: Option<$crate::rent::RentFilter>, cursor: Option<$crate::rent::TokenId>, limit: u64) -> $crate::rent::RentsPage {
+            self.$tokens.rents_available(filter, cursor, limit)
+          }
+          fn rents_ending_before(&self, timestamp: u64, cursor: Option<$crate::rent::TokenId>, limit: u64) -> $crate::rent::RentsPage {
+            self.$tokens.rents_ending_before(timestamp, cursor, limit)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_rent_earnings {
+    ($contract:ident, $tokens:ident $(, $assert_action: ident)?) => {
+        use $crate::rent::RentFeatureEarnings;
+
+        #[near_bindgen]
+        impl RentFeatureEarnings for $contract {
+          fn rent_earnings(&self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Option<$crate::rent::RentEarnings> {
+            self.$tokens.rent_earnings(contract_id, token_id)
+          }
+          fn rent_earnings_for_account(&self, account_id: AccountId) -> std::collections::HashMap<AccountId, near_sdk::json_types::U128> {
+            self.$tokens.rent_earnings_for_account(account_id)
+          }
+          fn rent_earnings_claim(&mut self, ft_token_id: AccountId) -> near_sdk::Promise {
+            $(self.$assert_action();)?
+            self.$tokens.rent_earnings_claim(ft_token_id)
+          }
+          #[private]
+          fn rent_resolve_earnings_claim(&mut self, account_id: AccountId, ft_token_id: AccountId, amount: near_sdk::json_types::U128) -> bool {
+            self.$tokens.rent_resolve_earnings_claim(account_id, ft_token_id, amount)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_rent_keeper {
+    ($contract:ident, $tokens:ident, $assert_owner:ident) => {
+        use $crate::rent::RentFeatureKeeper;
+
+        #[near_bindgen]
+        impl RentFeatureKeeper for $contract {
+          fn rent_claim_expired(&mut self, limit: u64) -> u64 {
+            self.$tokens.rent_claim_expired(limit)
+          }
+          #[private]
+          fn rent_resolve_claim_expired(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, owner_id: AccountId, renter_id: AccountId, keeper_id: AccountId) {
+            self.$tokens.rent_resolve_claim_expired(contract_id, token_id, owner_id, renter_id, keeper_id)
+          }
+          #[payable]
+          fn rent_keeper_pool_deposit(&mut self) -> near_sdk::json_types::U128 {
+            self.$tokens.rent_keeper_pool_deposit()
+          }
+          fn rent_set_keeper_reward(&mut self, reward: near_sdk::json_types::U128) {
+            self.$assert_owner();
+            self.$tokens.rent_set_keeper_reward(reward)
+          }
+          fn rent_keeper_reward(&self) -> near_sdk::json_types::U128 {
+            self.$tokens.rent_keeper_reward()
+          }
+          fn rent_keeper_pool(&self) -> near_sdk::json_types::U128 {
+            self.$tokens.rent_keeper_pool()
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_rent_history {
+    ($contract:ident, $tokens:ident) => {
+        use $crate::rent::{RentFeatureHistory, RentRecord, JsonRentStats};
+
+        #[near_bindgen]
+        impl RentFeatureHistory for $contract {
+          fn rent_history(&self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Vec<RentRecord> {
+            self.$tokens.rent_history(contract_id, token_id)
+          }
+          fn rent_history_for_account(&self, account_id: AccountId) -> Vec<RentRecord> {
+            self.$tokens.rent_history_for_account(account_id)
+          }
+          fn rent_stats(&self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Option<JsonRentStats> {
+            self.$tokens.rent_stats(contract_id, token_id)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_rent_booking {
+    ($contract:ident, $tokens:ident $(, $assert_action: ident)?) => {
+        use $crate::rent::{RentFeatureBooking, RentBooking};
+
+        #[near_bindgen]
+        impl RentFeatureBooking for $contract {
+          fn rent_bookings(&self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Vec<RentBooking> {
+            self.$tokens.rent_bookings(contract_id, token_id)
+          }
+          fn rent_booking_cancel(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, starts_at: u64) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_booking_cancel(contract_id, token_id, starts_at)
+          }
+          fn rent_booking_start(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Option<near_sdk::Promise> {
+            $(self.$assert_action();)?
+            self.$tokens.rent_booking_start(contract_id, token_id)
+          }
+          #[private]
+          fn rent_resolve_booking(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, owner_id: AccountId, booking: RentBooking) -> near_sdk::json_types::U128 {
+            self.$tokens.rent_resolve_booking(contract_id, token_id, owner_id, booking)
+          }
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! impl_rent_sublet {
+    ($contract:ident, $tokens:ident $(, $assert_action: ident)?) => {
+        use $crate::rent::{RentFeatureSublet, RentSublet};
+
+        #[near_bindgen]
+        impl RentFeatureSublet for $contract {
+          fn rent_update_transfer_fee(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, transfer_fee: Option<u16>) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_update_transfer_fee(contract_id, token_id, transfer_fee)
+          }
+          #[payable]
+          fn rent_transfer(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, receiver_id: AccountId) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_transfer(contract_id, token_id, receiver_id)
+          }
+          fn rent_sublet_add(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, sale_conditions: $crate::rent::SaleConditions) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_sublet_add(contract_id, token_id, sale_conditions)
+          }
+          fn rent_sublet_remove(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_sublet_remove(contract_id, token_id)
+          }
+          #[payable]
+          fn rent_sublet_pay(&mut self, contract_id: AccountId, token_id: $crate::rent::TokenId, receiver_id: AccountId) {
+            $(self.$assert_action();)?
+            self.$tokens.rent_sublet_pay(contract_id, token_id, receiver_id)
+          }
+          fn rent_sublet(&self, contract_id: AccountId, token_id: $crate::rent::TokenId) -> Option<RentSublet> {
+            self.$tokens.rent_sublet(contract_id, token_id)
+          }
         }
     };
 }
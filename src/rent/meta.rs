@@ -7,6 +7,30 @@ use std::collections::HashMap;
 
 pub type TokenId = String;
 pub type SaleConditions = HashMap<AccountId, U128>;
+pub type PriceTiers = HashMap<AccountId, Vec<PriceTier>>;
+
+/// Applies to rents of at least `min_time`, either as a rate per `min_time`
+/// (e.g. daily or weekly price) or as a discount of the hourly price.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceTier {
+    pub min_time: u64,
+    pub price: Option<U128>,
+    // discount in basis points
+    pub discount: Option<u16>,
+}
+
+/// Rented token, mt amount is kept by the contract from listing until claim
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RentAsset {
+    Nft,
+    // mt rents are listed by the listing id, the mt token id is kept here
+    Mt {
+        token_id: TokenId,
+        amount: U128,
+    },
+}
 
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct Rent {
@@ -17,6 +41,46 @@ pub struct Rent {
     pub min_time: u64,
     pub max_time: u64,
     pub created_at: u64,
+    // renter receives the nft and locks collateral instead
+    pub collateral: Option<SaleConditions>,
+    // renter pays nothing upfront and shares earnings instead
+    pub revenue_share: Option<RevenueShare>,
+    pub price_tiers: PriceTiers,
+    // part of booking price kept by owner on cancel, in basis points
+    pub cancel_fee: Option<u16>,
+    // renter can pass the rent to other account when set, owner fee in basis points
+    pub transfer_fee: Option<u16>,
+    pub asset: RentAsset,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RevenueShare {
+    // owner part of earnings in basis points
+    pub owner_share: u16,
+    pub time: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentEarnings {
+    pub owner: HashMap<AccountId, U128>,
+    pub renter: HashMap<AccountId, U128>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentPayment {
+    pub ft_token_id: AccountId,
+    pub price: U128,
+    pub collateral: U128,
+    pub started_at: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RentTermination {
+    pub account_id: AccountId,
+    pub deposit: U128,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -31,6 +95,12 @@ pub struct JsonRent {
     pub created_at: u64,
     pub ended_at: Option<u64>,
     pub renter_id: Option<AccountId>,
+    pub collateral: Option<SaleConditions>,
+    pub revenue_share: Option<RevenueShare>,
+    pub price_tiers: PriceTiers,
+    pub cancel_fee: Option<u16>,
+    pub transfer_fee: Option<u16>,
+    pub asset: RentAsset,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -39,6 +109,11 @@ pub struct RentOnNftApproveArgs {
     pub sale_conditions: SaleConditions,
     pub min_time: u64,
     pub max_time: u64,
+    pub collateral: Option<SaleConditions>,
+    pub revenue_share: Option<RevenueShare>,
+    pub price_tiers: Option<PriceTiers>,
+    pub cancel_fee: Option<u16>,
+    pub transfer_fee: Option<u16>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -48,4 +123,161 @@ pub struct RentOnFtTransferArgs {
     pub contract_id: AccountId,
     pub receiver_id: AccountId,
     pub time: u64,
+    // books the token for a future time instead of renting now
+    #[serde(default)]
+    pub starts_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentOnFtTransferExtendArgs {
+    pub token_id: TokenId,
+    pub contract_id: AccountId,
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentOnFtTransferTerminateArgs {
+    pub token_id: TokenId,
+    pub contract_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentOnFtTransferEarningsArgs {
+    pub token_id: TokenId,
+    pub contract_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentFilter {
+    #[serde(default)]
+    pub contract_id: Option<AccountId>,
+    #[serde(default)]
+    pub ft_token_id: Option<AccountId>,
+    #[serde(default)]
+    pub max_price_per_hour: Option<U128>,
+    // rent time that should be between `min_time` and `max_time`
+    #[serde(default)]
+    pub time: Option<u64>,
+}
+
+impl RentFilter {
+    pub fn matches(&self, rent: &JsonRent) -> bool {
+        if let Some(contract_id) = &self.contract_id {
+            if &rent.contract_id != contract_id {
+                return false;
+            }
+        }
+
+        if let Some(time) = self.time {
+            let is_time_match = if let Some(revenue_share) = &rent.revenue_share {
+                time == revenue_share.time
+            } else {
+                time >= rent.min_time && time <= rent.max_time
+            };
+            if !is_time_match {
+                return false;
+            }
+        }
+
+        let max_price = self.max_price_per_hour.map(|price| price.0).unwrap_or(u128::MAX);
+        let is_price_match = |price: &U128| price.0 <= max_price;
+
+        if let Some(ft_token_id) = &self.ft_token_id {
+            rent.sale_conditions.get(ft_token_id).map(is_price_match).unwrap_or(false)
+        } else {
+            self.max_price_per_hour.is_none() || rent.sale_conditions.values().any(is_price_match)
+        }
+    }
+}
+
+/// `next_cursor` is the last scanned rent id, pass it to get the next page
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentsPage {
+    pub rents: Vec<JsonRent>,
+    pub next_cursor: Option<TokenId>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentRecord {
+    pub token_id: TokenId,
+    pub contract_id: AccountId,
+    pub renter_id: AccountId,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub ft_token_id: AccountId,
+    pub price: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RentStats {
+    // first listing time, utilization is counted from it
+    pub listed_at: u64,
+    pub rents_count: u64,
+    pub time: u64,
+    pub revenue: HashMap<AccountId, U128>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonRentStats {
+    pub listed_at: u64,
+    pub rents_count: u64,
+    pub hours: u64,
+    pub revenue: HashMap<AccountId, U128>,
+    // rented time share since listing in basis points
+    pub utilization: u16,
+}
+
+/// Paid future rent, the price is kept by contract until the start
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentBooking {
+    pub renter_id: AccountId,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub ft_token_id: AccountId,
+    pub price: U128,
+}
+
+/// Listing of the remaining rent time by the renter
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentSublet {
+    pub renter_id: AccountId,
+    pub sale_conditions: SaleConditions,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentOnFtTransferRenterArgs {
+    pub token_id: TokenId,
+    pub contract_id: AccountId,
+    pub receiver_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentOnFtTransferSubletArgs {
+    pub token_id: TokenId,
+    pub contract_id: AccountId,
+    pub receiver_id: AccountId,
+}
+
+/// Listing of the amount sent with `mt_batch_transfer_call`
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentOnMtTransferArgs {
+    pub sale_conditions: SaleConditions,
+    pub min_time: u64,
+    pub max_time: u64,
+    pub revenue_share: Option<RevenueShare>,
+    pub price_tiers: Option<PriceTiers>,
+    pub cancel_fee: Option<u16>,
+    pub transfer_fee: Option<u16>,
 }
@@ -1,6 +1,15 @@
 pub use macros::*;
 
-pub use self::base::{ RentFeatureCore, RentFeatureResolve, RentFeature };
+pub use self::base::{
+    RentFeatureCore,
+    RentFeatureResolve,
+    RentFeatureEarnings,
+    RentFeatureKeeper,
+    RentFeatureHistory,
+    RentFeatureBooking,
+    RentFeatureSublet,
+    RentFeature,
+};
 pub use self::enumeration::RentFeatureEnumeration;
 pub use self::meta::*;
 
@@ -1,20 +1,27 @@
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use crate::rent::{RentFeature};
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::{AccountId, BorshStorageKey, Balance, testing_env, env};
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::mock::VmAction;
+    use near_sdk::{AccountId, BorshStorageKey, Balance, Gas, testing_env, env};
     use near_sdk::json_types::U128;
     use near_sdk::borsh::{self, BorshSerialize};
     use crate::rent::base::RentFeatureCore;
     use crate::rent::RentFeatureEnumeration;
-    use crate::rent::utils::{contract_token_id};
+    use crate::rent::{RentOnFtTransferEarningsArgs, PriceTier, RentFilter};
+    use crate::rent::{RentPayment, TokenId, SaleConditions, RentFeatureResolve, RevenueShare, RentFeatureEarnings, RentFeatureKeeper, RentFeatureHistory, RentFeatureBooking, RentBooking, RentFeatureSublet, RentAsset, RentOnMtTransferArgs};
+    use near_sdk::{PromiseOrValue, PromiseResult, RuntimeFeesConfig, VMConfig};
+    use crate::rent::utils::{contract_token_id, mt_listing_id};
     use std::collections::HashMap;
+    use crate::utils::near_ft;
 
     const PRICE_PER_HOUR: U128 = U128(10_000_000_000_000_000_000_000);
 
     const ATTACHED_SUPPLY: Balance = 100_000_000_000_000_000_000_000;
     const MIN_TIME: u64 = 3700000000000;
     const MAX_TIME: u64 = 8540000000000000;
+    const HOUR: u64 = 3600000000000;
+    const COLLATERAL: U128 = U128(50_000_000_000_000_000_000_000);
 
     /// Helper structure for keys of the persistent collections.
     #[derive(BorshSerialize, BorshStorageKey)]
@@ -26,7 +33,20 @@ mod tests {
         RentsPerAccount,
         RentsAt,
         RentContractTokens,
-        ApprovedOwners
+        ApprovedOwners,
+        RentsPayment,
+        RentsTermination,
+        RentsEarnings,
+        EarningsPerAccount,
+        RentsByEndAt,
+        RentHistory,
+        RentHistoryPerAccount,
+        RentStats,
+        RentBookings,
+        RentSublets,
+        RentsKept,
+        RentMtNonce,
+        RentsRemoved,
     }
 
     fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
@@ -48,9 +68,68 @@ mod tests {
           StorageKey::RentsPerAccount,
           StorageKey::RentsAt,
           StorageKey::RentContractTokens,
+          StorageKey::RentsPayment,
+          StorageKey::RentsTermination,
+          StorageKey::RentsEarnings,
+          StorageKey::EarningsPerAccount,
+          StorageKey::RentsByEndAt,
+          StorageKey::RentHistory,
+          StorageKey::RentHistoryPerAccount,
+          StorageKey::RentStats,
+          StorageKey::RentBookings,
+          StorageKey::RentSublets,
+          StorageKey::RentsKept,
+          StorageKey::RentMtNonce,
+          StorageKey::RentsRemoved,
         )
     }
 
+    fn add_paid_rent(
+        instance: &mut RentFeature,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        renter_id: &AccountId,
+        ft_token_id: &AccountId,
+        time: u64,
+        collateral: &Option<SaleConditions>
+    ) -> TokenId {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+
+        instance.internal_rent_add(
+            nft_contract_id,
+            token_id,
+            owner_id,
+            &sale_conditions,
+            &MIN_TIME,
+            &MAX_TIME,
+            collateral,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
+        );
+
+        let id = contract_token_id(nft_contract_id, token_id);
+        let rent = instance.rents_by_id.get(&id).unwrap();
+        let now = env::block_timestamp();
+
+        instance.rents_pending.remove(&id);
+        instance.rents_current.insert(&id, renter_id);
+        instance.internal_set_rent_end(&id, &(now + time));
+        instance.rents_payment_by_id.insert(&id, &RentPayment {
+            ft_token_id: ft_token_id.clone(),
+            price: U128(instance.internal_rent_price(&rent, ft_token_id, &time)),
+            collateral: U128(instance.internal_rent_collateral(&rent, ft_token_id)),
+            started_at: now,
+        });
+        instance.internal_add_token_to_account(renter_id, nft_contract_id, token_id);
+
+        id
+    }
+
     #[test]
     fn test_rent_add_internal() {
         let owner_id = accounts(1);
@@ -79,6 +158,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let id = contract_token_id(&nft_contract_id , &token_id);
@@ -139,6 +224,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -204,6 +295,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -262,6 +359,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -305,6 +408,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
 
@@ -355,6 +464,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -395,6 +510,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -440,6 +561,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let token_id2 = "Token2".to_string();
@@ -455,6 +582,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -514,6 +647,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
@@ -531,6 +670,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -590,6 +735,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let id1 = contract_token_id(&nft_contract_id , &token_id);
@@ -607,6 +758,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let id2 = contract_token_id(&nft_contract_id , &token_id2);
@@ -669,6 +826,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let token_id2 = "Token2".to_string();
@@ -683,6 +846,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -724,6 +893,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         if let Some(json_rent)= instance.rent(
@@ -771,6 +946,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let receiver_id = accounts(2);
@@ -793,6 +974,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let id2 = contract_token_id(&nft_contract_id , &token_id2);
@@ -857,6 +1044,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let id = contract_token_id(&nft_contract_id , &token_id);
@@ -876,6 +1069,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let id2 = contract_token_id(&nft_contract_id , &token_id2);
@@ -921,6 +1120,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let token_id2 = "Token2".to_string();
@@ -936,6 +1141,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -994,6 +1205,12 @@ mod tests {
             &sale_conditions,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let token_id2 = "Token2".to_string();
@@ -1008,6 +1225,12 @@ mod tests {
             &sale_conditions2,
             &MIN_TIME,
             &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
         );
 
         let mut context = get_context(owner_id.clone());
@@ -1020,4 +1243,1316 @@ mod tests {
 
         assert_eq!(supply, U128(2));
     }
+
+    #[test]
+    fn test_rent_extend() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+
+        instance.internal_rent_extend(
+            &nft_contract_id,
+            &token_id,
+            &renter_id,
+            &HOUR,
+            &ft_token_id,
+            &PRICE_PER_HOUR,
+        );
+
+        let payment = instance.rents_payment_by_id.get(&id).unwrap();
+
+        assert_eq!(instance.rents_end_by_id.get(&id), Some(env::block_timestamp() + 2 * HOUR));
+        assert_eq!(payment.price, U128(PRICE_PER_HOUR.0 * 2));
+        assert_eq!(instance.rents_current.get(&id), Some(renter_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid rent time")]
+    fn test_rent_extend_max_time() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+
+        instance.internal_rent_extend(
+            &nft_contract_id,
+            &token_id,
+            &renter_id,
+            &MAX_TIME,
+            &ft_token_id,
+            &PRICE_PER_HOUR,
+        );
+    }
+
+    #[test]
+    fn test_rent_terminate() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, 2 * HOUR, &None);
+
+        let promise = instance.internal_rent_terminate(&nft_contract_id, &token_id, &renter_id, &ft_token_id, &U128(0));
+
+        assert!(promise.is_none());
+        assert_eq!(instance.rents_termination_by_id.get(&id).unwrap().account_id, renter_id);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context
+            .block_timestamp(HOUR)
+            .build()
+        );
+
+        let refund = instance.rent_terminate_refund(nft_contract_id.clone(), token_id.clone());
+
+        assert_eq!(refund, PRICE_PER_HOUR);
+
+        let promise = instance.internal_rent_terminate(&nft_contract_id, &token_id, &owner_id, &ft_token_id, &refund);
+
+        assert!(promise.is_some());
+        assert!(instance.rents_termination_by_id.get(&id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid deposit")]
+    fn test_rent_terminate_low_deposit() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, 2 * HOUR, &None);
+
+        instance.internal_rent_terminate(&nft_contract_id, &token_id, &owner_id, &ft_token_id, &U128(1));
+        instance.internal_rent_terminate(&nft_contract_id, &token_id, &renter_id, &ft_token_id, &U128(0));
+    }
+
+    #[test]
+    fn test_rent_collateral_return() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let mut collateral = HashMap::new();
+        collateral.insert(ft_token_id.clone(), COLLATERAL);
+
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &Some(collateral));
+
+        assert_eq!(instance.rents_payment_by_id.get(&id).unwrap().collateral, COLLATERAL);
+
+        let mut context = get_context(nft_contract_id.clone());
+        testing_env!(context.build());
+
+        let result = instance.internal_on_nft_transfer(&nft_contract_id, &token_id, &renter_id);
+
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+
+        assert!(!instance.rent_resolve_return(nft_contract_id.clone(), token_id.clone(), owner_id.clone(), renter_id.clone()));
+
+        assert!(instance.rents_current.get(&id).is_none());
+        assert!(instance.rents_by_id.get(&id).is_none());
+        assert!(instance.rents_payment_by_id.get(&id).is_none());
+        assert!(instance.rent_tokens_per_account.get(&renter_id).is_none());
+    }
+
+    #[test]
+    fn test_rent_collateral_return_failed() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let mut collateral = HashMap::new();
+        collateral.insert(ft_token_id.clone(), COLLATERAL);
+
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &Some(collateral));
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+
+        // the nft goes back to the renter, the rent can be returned again
+        assert!(instance.rent_resolve_return(nft_contract_id.clone(), token_id.clone(), owner_id.clone(), renter_id.clone()));
+
+        assert_eq!(instance.rents_current.get(&id), Some(renter_id.clone()));
+        assert_eq!(instance.rents_payment_by_id.get(&id).unwrap().collateral, COLLATERAL);
+        assert!(instance.rent_tokens_per_account.get(&renter_id).unwrap().contains(&id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Rent is expired")]
+    fn test_rent_collateral_return_expired() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let mut collateral = HashMap::new();
+        collateral.insert(ft_token_id.clone(), COLLATERAL);
+
+        add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &Some(collateral));
+
+        let mut context = get_context(nft_contract_id.clone());
+        testing_env!(context
+            .block_timestamp(2 * HOUR)
+            .build()
+        );
+
+        instance.internal_on_nft_transfer(&nft_contract_id, &token_id, &renter_id);
+    }
+
+    #[test]
+    fn test_rent_collateral_claim() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let mut collateral = HashMap::new();
+        collateral.insert(ft_token_id.clone(), COLLATERAL);
+
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &Some(collateral));
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context
+            .block_timestamp(2 * HOUR)
+            .build()
+        );
+
+        instance.rent_claim(nft_contract_id.clone(), token_id.clone());
+
+        assert!(instance.rents_current.get(&id).is_none());
+        assert!(instance.rents_by_id.get(&id).is_none());
+        assert!(instance.rents_per_account.get(&owner_id).is_none());
+    }
+
+    #[test]
+    fn test_rent_revenue_share_earn() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+        let game_id = accounts(3);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        instance.internal_rent_add(
+            &nft_contract_id,
+            &token_id,
+            &owner_id,
+            &HashMap::new(),
+            &MIN_TIME,
+            &MAX_TIME,
+            &None,
+            &Some(RevenueShare { owner_share: 3_000, time: HOUR }),
+            &HashMap::new(),
+            &None,
+            &None,
+            &RentAsset::Nft,
+        );
+
+        let id = contract_token_id(&nft_contract_id, &token_id);
+        let rent = instance.rents_by_id.get(&id).unwrap();
+
+        assert_eq!(instance.internal_rent_price(&rent, &ft_token_id, &HOUR), 0);
+
+        instance.rents_current.insert(&id, &renter_id);
+
+        let mut context = get_context(ft_token_id.clone());
+        testing_env!(context
+            .signer_account_id(game_id.clone())
+            .build()
+        );
+
+        instance.internal_on_ft_transfer_earnings(
+            &RentOnFtTransferEarningsArgs {
+                token_id: token_id.clone(),
+                contract_id: nft_contract_id.clone(),
+            },
+            &ft_token_id,
+            &U128(1_000),
+        );
+        instance.internal_on_ft_transfer_earnings(
+            &RentOnFtTransferEarningsArgs {
+                token_id: token_id.clone(),
+                contract_id: nft_contract_id.clone(),
+            },
+            &ft_token_id,
+            &U128(1_000),
+        );
+
+        let earnings = instance.rent_earnings(nft_contract_id.clone(), token_id.clone()).unwrap();
+
+        assert_eq!(earnings.owner.get(&ft_token_id), Some(&U128(600)));
+        assert_eq!(earnings.renter.get(&ft_token_id), Some(&U128(1_400)));
+        assert_eq!(instance.rent_earnings_for_account(owner_id.clone()).get(&ft_token_id), Some(&U128(600)));
+        assert_eq!(instance.rent_earnings_for_account(renter_id.clone()).get(&ft_token_id), Some(&U128(1_400)));
+    }
+
+    #[test]
+    fn test_rent_earnings_claim_fail() {
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        instance.internal_add_earnings(&renter_id, &ft_token_id, 1_400);
+        instance.rent_earnings_claim(ft_token_id.clone());
+
+        assert!(instance.rent_earnings_for_account(renter_id.clone()).is_empty());
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+
+        let claimed = instance.rent_resolve_earnings_claim(renter_id.clone(), ft_token_id.clone(), U128(1_400));
+
+        assert!(!claimed);
+        assert_eq!(instance.rent_earnings_for_account(renter_id.clone()).get(&ft_token_id), Some(&U128(1_400)));
+    }
+
+    #[test]
+    fn test_rent_price_tiers() {
+        let owner_id = accounts(1);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+
+        let mut price_tiers = HashMap::new();
+        price_tiers.insert(ft_token_id.clone(), vec![
+            // 10% off from 5 hours
+            PriceTier { min_time: 5 * HOUR, price: None, discount: Some(1_000) },
+            // daily rate of 20 hours price
+            PriceTier { min_time: 24 * HOUR, price: Some(U128(PRICE_PER_HOUR.0 * 20)), discount: None },
+        ]);
+
+        instance.internal_rent_add(
+            &nft_contract_id,
+            &token_id,
+            &owner_id,
+            &sale_conditions,
+            &MIN_TIME,
+            &MAX_TIME,
+            &None,
+            &None,
+            &price_tiers,
+            &None,
+            &None,
+            &RentAsset::Nft,
+        );
+
+        let quote = |time: u64| instance.rent_price(nft_contract_id.clone(), token_id.clone(), ft_token_id.clone(), time).0;
+
+        assert_eq!(quote(2 * HOUR), PRICE_PER_HOUR.0 * 2);
+        assert_eq!(quote(10 * HOUR), PRICE_PER_HOUR.0 * 9);
+        assert_eq!(quote(48 * HOUR), PRICE_PER_HOUR.0 * 40);
+    }
+
+    #[test]
+    fn test_enum_rents_available() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+        let mut sale_conditions_expensive = HashMap::new();
+        sale_conditions_expensive.insert(ft_token_id.clone(), U128(PRICE_PER_HOUR.0 * 2));
+
+        for (token_id, sale_conditions) in [("Token1", &sale_conditions), ("Token2", &sale_conditions_expensive), ("Token3", &sale_conditions)] {
+            instance.internal_rent_add(
+                &nft_contract_id,
+                &token_id.to_string(),
+                &owner_id,
+                sale_conditions,
+                &MIN_TIME,
+                &MAX_TIME,
+                &None,
+                &None,
+                &HashMap::new(),
+                &None,
+                &None,
+                &RentAsset::Nft,
+            );
+        }
+
+        add_paid_rent(&mut instance, &nft_contract_id, &"Token4".to_string(), &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+
+        let filter = RentFilter {
+            ft_token_id: Some(ft_token_id.clone()),
+            max_price_per_hour: Some(PRICE_PER_HOUR),
+            time: Some(2 * HOUR),
+            ..Default::default()
+        };
+
+        let page = instance.rents_available(Some(filter.clone()), None, 1);
+
+        assert_eq!(page.rents.len(), 1);
+        assert_eq!(page.rents[0].token_id, "Token1".to_string());
+
+        let page = instance.rents_available(Some(filter.clone()), page.next_cursor, 1);
+
+        assert_eq!(page.rents.len(), 1);
+        assert_eq!(page.rents[0].token_id, "Token3".to_string());
+
+        let page = instance.rents_available(Some(filter), page.next_cursor, 1);
+
+        assert_eq!(page.rents.len(), 0);
+        assert_eq!(page.next_cursor, None);
+
+        let page = instance.rents_ending_before(2 * HOUR, None, 10);
+
+        assert_eq!(page.rents.len(), 1);
+        assert_eq!(page.rents[0].token_id, "Token4".to_string());
+        assert_eq!(instance.rents_ending_before(HOUR, None, 10).rents.len(), 0);
+    }
+
+    #[test]
+    fn test_rent_claim_expired() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+        let keeper_id = accounts(3);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context
+            .attached_deposit(ATTACHED_SUPPLY)
+            .build()
+        );
+
+        let mut instance = get_instance();
+
+        instance.rent_set_keeper_reward(U128(1_000));
+        instance.rent_keeper_pool_deposit();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let mut collateral = HashMap::new();
+        collateral.insert(ft_token_id.clone(), COLLATERAL);
+
+        let id1 = add_paid_rent(&mut instance, &nft_contract_id, &"Token1".to_string(), &owner_id, &renter_id, &ft_token_id, 2 * HOUR, &Some(collateral));
+        let id2 = add_paid_rent(&mut instance, &nft_contract_id, &"Token2".to_string(), &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+        let id3 = add_paid_rent(&mut instance, &nft_contract_id, &"Token3".to_string(), &owner_id, &renter_id, &ft_token_id, 4 * HOUR, &None);
+
+        let mut context = get_context(keeper_id.clone());
+        testing_env!(context
+            .block_timestamp(3 * HOUR)
+            .build()
+        );
+
+        let claimed = instance.rent_claim_expired(10);
+
+        assert_eq!(claimed, 2);
+        // collateral is taken at once, nft transfer is resolved later
+        assert_eq!(instance.rent_keeper_pool(), U128(ATTACHED_SUPPLY - 1_000));
+        assert!(instance.rents_current.get(&id1).is_none());
+        assert!(instance.rents_current.get(&id2).is_some());
+        assert!(instance.rents_current.get(&id3).is_some());
+        assert_eq!(instance.rents_by_end_at.len(), 1);
+
+        assert_eq!(instance.rent_claim_expired(10), 0);
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.block_timestamp(3 * HOUR).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+
+        instance.rent_resolve_claim_expired(nft_contract_id.clone(), "Token2".to_string(), owner_id.clone(), renter_id.clone(), keeper_id.clone());
+
+        // failed transfer is claimable again and is not rewarded
+        assert_eq!(instance.rent_keeper_pool(), U128(ATTACHED_SUPPLY - 1_000));
+        assert!(instance.rents_current.get(&id2).is_some());
+        assert!(instance.rents_by_end_at.get(&(HOUR, id2.clone())).is_some());
+
+        testing_env!(
+            context.block_timestamp(3 * HOUR).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+
+        instance.rent_resolve_claim_expired(nft_contract_id.clone(), "Token2".to_string(), owner_id.clone(), renter_id.clone(), keeper_id.clone());
+
+        assert_eq!(instance.rent_keeper_pool(), U128(ATTACHED_SUPPLY - 2_000));
+        assert!(instance.rents_current.get(&id2).is_none());
+        assert_eq!(instance.rents_by_end_at.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "More gas is required")]
+    fn test_rent_claim_expired_low_gas() {
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .prepaid_gas(Gas(50_000_000_000_000))
+            .build()
+        );
+
+        let mut instance = get_instance();
+
+        instance.rent_claim_expired(10);
+    }
+
+    #[test]
+    fn test_rent_history() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.block_timestamp(2 * HOUR).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+
+        instance.rent_resolve_claim(nft_contract_id.clone(), token_id.clone(), owner_id.clone(), renter_id.clone());
+
+        let history = instance.rent_history(nft_contract_id.clone(), token_id.clone());
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].renter_id, renter_id);
+        assert_eq!(history[0].started_at, 0);
+        assert_eq!(history[0].ended_at, HOUR);
+        assert_eq!(history[0].price, PRICE_PER_HOUR);
+        assert_eq!(instance.rent_history_for_account(renter_id.clone()).len(), 1);
+
+        let stats = instance.rent_stats(nft_contract_id.clone(), token_id.clone()).unwrap();
+
+        assert_eq!(stats.rents_count, 1);
+        assert_eq!(stats.hours, 1);
+        assert_eq!(stats.revenue.get(&ft_token_id), Some(&PRICE_PER_HOUR));
+        assert_eq!(stats.utilization, 5_000);
+    }
+
+    fn add_booking_rent(instance: &mut RentFeature, nft_contract_id: &AccountId, token_id: &TokenId, owner_id: &AccountId, ft_token_id: &AccountId) {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+
+        instance.internal_rent_add(
+            nft_contract_id,
+            token_id,
+            owner_id,
+            &sale_conditions,
+            &MIN_TIME,
+            &MAX_TIME,
+            &None,
+            &None,
+            &HashMap::new(),
+            &Some(1_000),
+            &None,
+            &RentAsset::Nft,
+        );
+    }
+
+    #[test]
+    fn test_rent_book() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        add_booking_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &ft_token_id);
+
+        let result = instance.internal_rent_pay(&nft_contract_id, &token_id, &renter_id, &renter_id, &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(2 * HOUR));
+
+        assert!(matches!(result, PromiseOrValue::Value(U128(0))));
+
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &renter_id, &accounts(3), &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(5 * HOUR));
+
+        let bookings = instance.rent_bookings(nft_contract_id.clone(), token_id.clone());
+
+        assert_eq!(bookings.len(), 2);
+        assert_eq!(bookings[0].renter_id, renter_id);
+        assert_eq!(bookings[0].ends_at, 4 * HOUR);
+        assert_eq!(bookings[1].renter_id, accounts(3));
+        assert!(!instance.rent_token_is_locked(nft_contract_id.clone(), token_id.clone()));
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context
+            .block_timestamp(2 * HOUR + 1)
+            .build()
+        );
+
+        assert!(instance.rent_token_is_locked(nft_contract_id.clone(), token_id.clone()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Time is already booked")]
+    fn test_rent_book_overlap() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        add_booking_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &ft_token_id);
+
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &renter_id, &renter_id, &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(2 * HOUR));
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &renter_id, &renter_id, &(3 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 3), &None);
+    }
+
+    #[test]
+    fn test_rent_booking_cancel() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        add_booking_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &ft_token_id);
+
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &renter_id, &renter_id, &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(2 * HOUR));
+        instance.rent_booking_cancel(nft_contract_id.clone(), token_id.clone(), 2 * HOUR);
+
+        assert!(instance.rent_bookings(nft_contract_id.clone(), token_id.clone()).is_empty());
+        assert!(instance.rent_bookings_by_id.get(&contract_token_id(&nft_contract_id, &token_id)).is_none());
+    }
+
+    #[test]
+    fn test_rent_booking_start() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let id = contract_token_id(&nft_contract_id, &token_id);
+
+        add_booking_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &ft_token_id);
+
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &renter_id, &renter_id, &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(2 * HOUR));
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context
+            .block_timestamp(2 * HOUR)
+            .build()
+        );
+
+        assert!(instance.rent_booking_start(nft_contract_id.clone(), token_id.clone()).is_some());
+
+        let booking = RentBooking {
+            renter_id: renter_id.clone(),
+            starts_at: 2 * HOUR,
+            ends_at: 4 * HOUR,
+            ft_token_id: ft_token_id.clone(),
+            price: U128(PRICE_PER_HOUR.0 * 2),
+        };
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.block_timestamp(2 * HOUR).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+
+        instance.rent_resolve_booking(nft_contract_id.clone(), token_id.clone(), owner_id.clone(), booking);
+
+        assert_eq!(instance.rents_current.get(&id), Some(renter_id));
+        assert_eq!(instance.rents_end_by_id.get(&id), Some(4 * HOUR));
+        assert_eq!(instance.rents_payment_by_id.get(&id).unwrap().started_at, 2 * HOUR);
+        assert!(!instance.rents_pending.contains(&id));
+    }
+
+    #[test]
+    fn test_rent_claim_keeps_bookings() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &accounts(3), &accounts(3), &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(2 * HOUR));
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &accounts(4), &accounts(4), &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(5 * HOUR));
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.block_timestamp(HOUR + 1).build());
+
+        assert!(instance.rent_claim(nft_contract_id.clone(), token_id.clone()).is_none());
+
+        assert!(instance.rents_by_id.get(&id).is_some());
+        assert!(instance.rents_current.get(&id).is_none());
+        assert!(instance.rents_pending.contains(&id));
+        assert!(instance.rents_kept.contains(&id));
+        assert_eq!(instance.rent_bookings(nft_contract_id.clone(), token_id.clone()).len(), 2);
+
+        let mut context = get_context(accounts(4));
+        testing_env!(context.block_timestamp(HOUR + 1).build());
+
+        instance.rent_booking_cancel(nft_contract_id.clone(), token_id.clone(), 5 * HOUR);
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(2 * HOUR).build());
+
+        assert!(instance.rent_booking_start(nft_contract_id.clone(), token_id.clone()).is_none());
+        assert!(instance.rent_bookings_by_id.get(&id).is_none());
+        assert!(!instance.rents_kept.contains(&id));
+        assert_eq!(instance.rents_current.get(&id), Some(accounts(3)));
+        assert_eq!(instance.rents_end_by_id.get(&id), Some(4 * HOUR));
+    }
+
+    #[test]
+    fn test_rent_claim_late_booking_refunded() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.block_timestamp(HOUR + 1).build());
+
+        assert!(instance.rent_claim(nft_contract_id.clone(), token_id.clone()).is_some());
+
+        // booked while the nft is returned to the owner
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &accounts(3), &accounts(3), &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(2 * HOUR));
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.block_timestamp(HOUR + 1).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+
+        instance.rent_resolve_claim(nft_contract_id.clone(), token_id.clone(), owner_id.clone(), renter_id.clone());
+
+        assert!(instance.rents_by_id.get(&id).is_none());
+        assert!(!instance.rents_pending.contains(&id));
+        assert!(instance.rent_bookings_by_id.get(&id).is_none());
+    }
+
+    #[test]
+    fn test_rent_remove_kept() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let id = add_paid_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, HOUR, &None);
+
+        instance.internal_rent_pay(&nft_contract_id, &token_id, &accounts(3), &accounts(3), &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(2 * HOUR));
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.block_timestamp(HOUR + 1).build());
+
+        instance.rent_claim(nft_contract_id.clone(), token_id.clone());
+
+        let mut context = get_context(accounts(3));
+        testing_env!(context.block_timestamp(HOUR + 1).build());
+
+        instance.rent_booking_cancel(nft_contract_id.clone(), token_id.clone(), 2 * HOUR);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context
+            .attached_deposit(1)
+            .block_timestamp(HOUR + 1)
+            .build()
+        );
+
+        instance.rent_remove(nft_contract_id.clone(), token_id.clone());
+
+        assert!(instance.rents_by_id.get(&id).is_none());
+        assert!(!instance.rents_kept.contains(&id));
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == nft_contract_id)
+            .expect("Not found nft transfer");
+
+        assert!(
+            matches!(&receipt.actions[0], VmAction::FunctionCall { function_name, .. } if function_name == "nft_transfer")
+        );
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.block_timestamp(HOUR + 1).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+
+        instance.rent_resolve_remove(nft_contract_id.clone(), token_id.clone(), true);
+
+        assert!(instance.rents_by_id.get(&id).is_some());
+        assert!(instance.rents_kept.contains(&id));
+    }
+
+    #[test]
+    fn test_rent_mt_claim_keeps_bookings() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let mt_contract_id = AccountId::new_unchecked("mt_token_contract".to_string());
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let token_id = add_mt_rent(&mut instance, &mt_contract_id, &"potion".to_string(), &owner_id, &ft_token_id);
+        let id = contract_token_id(&mt_contract_id, &token_id);
+
+        instance.internal_rent_pay(&mt_contract_id, &token_id, &renter_id, &renter_id, &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &None);
+        instance.internal_rent_pay(&mt_contract_id, &token_id, &accounts(3), &accounts(3), &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &Some(3 * HOUR));
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.block_timestamp(2 * HOUR + 1).build());
+
+        assert!(instance.rent_claim(mt_contract_id.clone(), token_id.clone()).is_none());
+
+        assert!(instance.rents_by_id.get(&id).is_some());
+        assert!(instance.rents_pending.contains(&id));
+        assert_eq!(instance.rent_bookings(mt_contract_id.clone(), token_id.clone()).len(), 1);
+    }
+
+    fn add_transferable_rent(
+        instance: &mut RentFeature,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        renter_id: &AccountId,
+        ft_token_id: &AccountId,
+        transfer_fee: Option<u16>
+    ) -> TokenId {
+        let id = add_paid_rent(instance, nft_contract_id, token_id, owner_id, renter_id, ft_token_id, 10 * HOUR, &None);
+
+        let mut rent = instance.rents_by_id.get(&id).unwrap();
+        rent.transfer_fee = transfer_fee;
+        instance.rents_by_id.insert(&id, &rent);
+
+        id
+    }
+
+    #[test]
+    fn test_rent_transfer() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+        let receiver_id = accounts(3);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let id = add_transferable_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &near_ft(), Some(1000));
+
+        let fee = instance.internal_rent_refund(&id) / 10;
+
+        testing_env!(context
+            .attached_deposit(fee)
+            .build()
+        );
+
+        instance.rent_transfer(nft_contract_id.clone(), token_id.clone(), receiver_id.clone());
+
+        assert_eq!(instance.rents_current.get(&id), Some(receiver_id.clone()));
+        assert!(instance.rent_tokens_per_account.get(&renter_id).is_none());
+        assert!(instance.rent_tokens_per_account.get(&receiver_id).unwrap().contains(&id));
+        assert!(instance.rent_is_approved(nft_contract_id, token_id, owner_id));
+    }
+
+    #[test]
+    fn test_rent_transfer_history() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+        let receiver_id = accounts(3);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let id = add_transferable_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &near_ft(), Some(1000));
+        let price = instance.rents_payment_by_id.get(&id).unwrap().price.0;
+
+        testing_env!(context.block_timestamp(4 * HOUR).build());
+
+        let refund = instance.internal_rent_refund(&id);
+
+        testing_env!(context
+            .attached_deposit(refund / 10)
+            .block_timestamp(4 * HOUR)
+            .build()
+        );
+
+        instance.rent_transfer(nft_contract_id.clone(), token_id.clone(), receiver_id.clone());
+
+        let history = instance.rent_history_for_account(renter_id.clone());
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].started_at, 0);
+        assert_eq!(history[0].ended_at, 4 * HOUR);
+        assert_eq!(history[0].price.0, price - refund);
+
+        let payment = instance.rents_payment_by_id.get(&id).unwrap();
+
+        assert_eq!(payment.started_at, 4 * HOUR);
+        assert_eq!(payment.price.0, refund);
+        assert_eq!(instance.internal_rent_refund(&id), refund);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rent transfer is not allowed")]
+    fn test_rent_transfer_not_allowed() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        add_transferable_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &near_ft(), None);
+
+        instance.rent_transfer(nft_contract_id, token_id, accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not approved for owner")]
+    fn test_rent_transfer_not_approved() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let id = add_transferable_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &near_ft(), Some(1000));
+
+        instance.approved_owner_by_id.as_mut().unwrap().remove(&id);
+
+        instance.rent_transfer(nft_contract_id, token_id, accounts(3));
+    }
+
+    #[test]
+    fn test_rent_sublet_pay() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+        let buyer_id = accounts(3);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let id = add_transferable_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, Some(500));
+
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+
+        instance.rent_sublet_add(nft_contract_id.clone(), token_id.clone(), sale_conditions);
+
+        assert_eq!(instance.rent_sublet(nft_contract_id.clone(), token_id.clone()).unwrap().renter_id, renter_id);
+
+        testing_env!(context
+            .predecessor_account_id(buyer_id.clone())
+            .block_timestamp(HOUR)
+            .build()
+        );
+
+        instance.internal_rent_sublet_pay(&nft_contract_id, &token_id, &buyer_id, &ft_token_id, &PRICE_PER_HOUR);
+
+        assert_eq!(instance.rents_current.get(&id), Some(buyer_id.clone()));
+        assert_eq!(instance.rents_end_by_id.get(&id), Some(10 * HOUR));
+        assert!(instance.rent_sublet(nft_contract_id, token_id).is_none());
+        assert!(instance.rent_tokens_per_account.get(&buyer_id).unwrap().contains(&id));
+    }
+
+    #[test]
+    fn test_rent_update_transfer_fee_removes_sublet() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let nft_contract_id = AccountId::new_unchecked("nft_token_contract".to_string());
+        let token_id = "Token1".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let id = add_transferable_rent(&mut instance, &nft_contract_id, &token_id, &owner_id, &renter_id, &ft_token_id, Some(500));
+
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+
+        instance.rent_sublet_add(nft_contract_id.clone(), token_id.clone(), sale_conditions);
+
+        testing_env!(context
+            .predecessor_account_id(owner_id.clone())
+            .build()
+        );
+
+        instance.rent_update_transfer_fee(nft_contract_id.clone(), token_id.clone(), None);
+
+        assert!(instance.rent_sublet(nft_contract_id, token_id).is_none());
+        assert_eq!(instance.rents_by_id.get(&id).unwrap().transfer_fee, None);
+    }
+
+    fn add_mt_rent(
+        instance: &mut RentFeature,
+        mt_contract_id: &AccountId,
+        token_id: &TokenId,
+        owner_id: &AccountId,
+        ft_token_id: &AccountId
+    ) -> TokenId {
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+
+        let args = RentOnMtTransferArgs {
+            sale_conditions,
+            min_time: MIN_TIME,
+            max_time: MAX_TIME,
+            revenue_share: None,
+            price_tiers: None,
+            cancel_fee: None,
+            transfer_fee: None,
+        };
+
+        instance.internal_on_mt_transfer(
+            &args,
+            mt_contract_id,
+            &vec![AccountId::new_unchecked(token_id.clone())],
+            &vec![U128(5)],
+            owner_id
+        );
+
+        let nonce = instance.rent_mt_nonce_by_id.get(&contract_token_id(mt_contract_id, token_id)).unwrap();
+
+        mt_listing_id(token_id, nonce)
+    }
+
+    #[test]
+    fn test_rent_mt_add() {
+        let owner_id = accounts(1);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let mt_contract_id = AccountId::new_unchecked("mt_token_contract".to_string());
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let token_id = add_mt_rent(&mut instance, &mt_contract_id, &"potion".to_string(), &owner_id, &ft_token_id);
+        let id = contract_token_id(&mt_contract_id, &token_id);
+
+        let rent = instance.rents_by_id.get(&id).unwrap();
+
+        assert_eq!(rent.owner_id, owner_id);
+        assert_eq!(rent.asset, RentAsset::Mt { token_id: "potion".to_string(), amount: U128(5) });
+        assert!(instance.rents_pending.contains(&id));
+        assert!(instance.rent_is_approved(mt_contract_id, token_id, owner_id));
+    }
+
+    #[test]
+    fn test_rent_mt_pay() {
+        let owner_id = accounts(1);
+        let renter_id = accounts(2);
+
+        let mut context = get_context(renter_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let mt_contract_id = AccountId::new_unchecked("mt_token_contract".to_string());
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let token_id = add_mt_rent(&mut instance, &mt_contract_id, &"potion".to_string(), &owner_id, &ft_token_id);
+        let id = contract_token_id(&mt_contract_id, &token_id);
+
+        let result = instance.internal_rent_pay(&mt_contract_id, &token_id, &renter_id, &renter_id, &(2 * HOUR), &ft_token_id, &U128(PRICE_PER_HOUR.0 * 2), &None);
+
+        assert!(matches!(result, PromiseOrValue::Value(U128(0))));
+        assert_eq!(instance.rents_current.get(&id), Some(renter_id.clone()));
+        assert_eq!(instance.rents_end_by_id.get(&id), Some(2 * HOUR));
+        assert!(!instance.rents_pending.contains(&id));
+        assert!(instance.rent_tokens_per_account.get(&renter_id).unwrap().contains(&id));
+    }
+
+    #[test]
+    fn test_rent_mt_add_listings() {
+        let owner_id = accounts(1);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let mt_contract_id = AccountId::new_unchecked("mt_token_contract".to_string());
+        let mt_token_id = "potion".to_string();
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        let token_id = add_mt_rent(&mut instance, &mt_contract_id, &mt_token_id, &owner_id, &ft_token_id);
+        let second_token_id = add_mt_rent(&mut instance, &mt_contract_id, &mt_token_id, &owner_id, &ft_token_id);
+        let holder_token_id = add_mt_rent(&mut instance, &mt_contract_id, &mt_token_id, &accounts(2), &ft_token_id);
+
+        assert_ne!(token_id, second_token_id);
+        assert_ne!(second_token_id, holder_token_id);
+        assert_eq!(instance.rents_pending.len(), 3);
+
+        testing_env!(context.attached_deposit(1).build());
+
+        instance.rent_remove(mt_contract_id.clone(), second_token_id.clone());
+
+        assert!(instance.rents_by_id.get(&contract_token_id(&mt_contract_id, &second_token_id)).is_none());
+        assert!(instance.rents_by_id.get(&contract_token_id(&mt_contract_id, &token_id)).is_some());
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == mt_contract_id)
+            .expect("Not found mt transfer");
+
+        assert!(
+            matches!(&receipt.actions[0], VmAction::FunctionCall { function_name, args, .. } if
+                function_name == "mt_batch_transfer" &&
+                String::from_utf8(args.clone()).unwrap().contains("\"potion\""))
+        );
+    }
+
+    #[test]
+    fn test_rent_mt_remove_failed() {
+        let owner_id = accounts(1);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let mt_contract_id = AccountId::new_unchecked("mt_token_contract".to_string());
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+        let token_id = add_mt_rent(&mut instance, &mt_contract_id, &"potion".to_string(), &owner_id, &ft_token_id);
+        let id = contract_token_id(&mt_contract_id, &token_id);
+
+        testing_env!(context.attached_deposit(1).build());
+
+        instance.rent_remove(mt_contract_id.clone(), token_id.clone());
+
+        assert!(instance.rents_by_id.get(&id).is_none());
+        assert!(instance.rents_removed_by_id.get(&id).is_some());
+
+        let mut context = get_context(accounts(0));
+        testing_env!(
+            context.build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+
+        assert!(!instance.rent_resolve_remove(mt_contract_id.clone(), token_id.clone(), false));
+
+        assert!(instance.rents_removed_by_id.get(&id).is_none());
+        assert_eq!(instance.rents_by_id.get(&id).unwrap().owner_id, owner_id);
+        assert!(instance.rents_pending.contains(&id));
+        assert!(instance.rents_per_account.get(&owner_id).unwrap().contains(&id));
+        assert!(instance.rent_is_approved(mt_contract_id, token_id, owner_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Mt rent can not have collateral")]
+    fn test_rent_mt_collateral() {
+        let owner_id = accounts(1);
+
+        let mut context = get_context(owner_id.clone());
+        testing_env!(context.build());
+
+        let mut instance = get_instance();
+
+        let mt_contract_id = AccountId::new_unchecked("mt_token_contract".to_string());
+        let ft_token_id = AccountId::new_unchecked("ft_token".to_string());
+
+        let mut sale_conditions = HashMap::new();
+        sale_conditions.insert(ft_token_id.clone(), PRICE_PER_HOUR);
+
+        instance.internal_rent_add(
+            &mt_contract_id,
+            &"potion".to_string(),
+            &owner_id,
+            &sale_conditions.clone(),
+            &MIN_TIME,
+            &MAX_TIME,
+            &Some(sale_conditions),
+            &None,
+            &HashMap::new(),
+            &None,
+            &None,
+            &(RentAsset::Mt { token_id: "potion".to_string(), amount: U128(5) }),
+        );
+    }
 }
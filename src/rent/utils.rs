@@ -17,3 +17,7 @@ pub(crate) fn time_get_minutes(time: u64) -> u64 {
 pub(crate) fn contract_token_id(contract_id: &AccountId, token_id: &TokenId) -> TokenId {
     format!("{}{}{}", contract_id, DELIMETER, token_id)
 }
+
+pub(crate) fn mt_listing_id(token_id: &TokenId, nonce: u64) -> TokenId {
+    format!("{}{}{}", token_id, DELIMETER, nonce)
+}
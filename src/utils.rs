@@ -47,3 +47,54 @@ pub fn random_use() -> StdRng {
 pub fn refund_deposit_to_account(refund: u128) {
   Promise::new(env::signer_account_id()).transfer(refund);
 }
+
+/// `a * b / c` rounded down, the product is not limited by u128
+pub fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    require!(c > 0, "Division by zero");
+
+    let mask = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & mask);
+    let (b1, b0) = (b >> 64, b & mask);
+
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let mid = (p00 >> 64) + (p01 & mask) + (p10 & mask);
+
+    let lo = (p00 & mask) | (mid << 64);
+    let hi = a1 * b1 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+
+    require!(hi < c, "Amount overflow");
+
+    let mut rem = hi;
+    let mut quot = 0u128;
+
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quot <<= 1;
+
+        if carry == 1 || rem >= c {
+            rem = rem.wrapping_sub(c);
+            quot |= 1;
+        }
+    }
+
+    quot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mul_div;
+
+    #[test]
+    fn test_mul_div() {
+        assert_eq!(mul_div(10, 3, 4), 7);
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(
+            mul_div(3_000_000_000_000_000_000_000_000_000, 2_000_000_000_000_000_000_000_000, 6_000_000_000_000_000_000_000_000),
+            1_000_000_000_000_000_000_000_000_000
+        );
+    }
+}
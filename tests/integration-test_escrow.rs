@@ -256,6 +256,9 @@ async fn test_ft_to_ft() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -290,6 +293,9 @@ async fn test_ft_to_ft() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: None,
                 offer_id: Some(offer_id),
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -388,6 +394,8 @@ async fn test_nft_to_nft() -> anyhow::Result<()> {
                 nft_token_id_out: Some(token_id2.to_string()),
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -422,6 +430,8 @@ async fn test_nft_to_nft() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: None,
                 offer_id: Some(offer_id),
+                expires_at: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -547,6 +557,8 @@ async fn test_nft_to_ft() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -581,6 +593,9 @@ async fn test_nft_to_ft() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: None,
                 offer_id: Some(offer_id),
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -705,6 +720,9 @@ async fn test_ft_to_nft() -> anyhow::Result<()> {
                 nft_token_id_out: Some(token_id.to_string()),
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -739,6 +757,8 @@ async fn test_ft_to_nft() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: None,
                 offer_id: Some(offer_id),
+                expires_at: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -859,6 +879,9 @@ async fn test_remove_offer() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -893,6 +916,9 @@ async fn test_remove_offer() -> anyhow::Result<()> {
                 nft_token_id_out: Some(token_id2.to_string()),
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -947,6 +973,8 @@ async fn test_remove_offer() -> anyhow::Result<()> {
                 nft_token_id_out: Some(token_id2.to_string()),
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -969,8 +997,10 @@ async fn test_remove_offer() -> anyhow::Result<()> {
     println!("Nft_transfer NFT1 for NFT to NFT offer to escrow outcome: {:#?}", res);
     assert!(res.is_success());
 
+    let offer_id3 = format!("{}-{}", alice.id().clone(), 3);
+
     //Remove NFT to NFT offer from escrow contract
-    let res = remove_offer(&worker, &escrow_contract, &alice, &offer_id).await?;
+    let res = remove_offer(&worker, &escrow_contract, &alice, &offer_id3).await?;
     println!("Escrow remove offer nft for nft outcome: {:#?}", res);
 
     //Make arg string to offer NFT to escrow to make NFT to FT offer
@@ -983,6 +1013,9 @@ async fn test_remove_offer() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -1005,8 +1038,10 @@ async fn test_remove_offer() -> anyhow::Result<()> {
     println!("Nft_transfer NFT1 offer NFT to FT to escrow outcome: {:#?}", res);
     assert!(res.is_success());
 
+    let offer_id4 = format!("{}-{}", alice.id().clone(), 4);
+
     //Remove NFT to FT offer from escrow contract
-    let res = remove_offer(&worker, &escrow_contract, &alice, &offer_id).await?;
+    let res = remove_offer(&worker, &escrow_contract, &alice, &offer_id4).await?;
     println!("Escrow remove offer nft for ft outcome: {:#?}", res);
 
     Ok(())
@@ -1080,6 +1115,9 @@ async fn test_enumeration_for_offers() -> anyhow::Result<()> {
                 nft_token_id_out: None,
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -1114,6 +1152,9 @@ async fn test_enumeration_for_offers() -> anyhow::Result<()> {
                 nft_token_id_out: Some(token_id2.to_string()),
                 receiver_id: Some(AccountId::try_from(bob.id().clone()).unwrap()),
                 offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
             })
         )
         .ok()
@@ -1208,3 +1249,361 @@ async fn test_enumeration_for_offers() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+async fn transfer_ft_to_offer(
+    worker: &Worker<Sandbox>,
+    escrow_contract: &Contract,
+    ft_contract: &Contract,
+    user: &Account,
+    offer_id: &String,
+    amount: U128
+) -> anyhow::Result<CallExecutionDetails> {
+    let escrow_ft_offer_id_msg = serde_json
+        ::to_string(
+            &ArgsFt::Escrow(EscrowOnFtTransferArgs {
+                ft_contract_id_out: None,
+                ft_amount_out: None,
+                nft_contract_id_out: None,
+                nft_token_id_out: None,
+                receiver_id: None,
+                offer_id: Some(offer_id.clone()),
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
+            })
+        )
+        .ok()
+        .expect("Wrong struct to stringify");
+
+    let res = user
+        .call(&worker, ft_contract.id(), "ft_transfer_call")
+        .args_json(
+            json!({
+            "receiver_id": escrow_contract.id(),
+            "amount": amount,
+            "msg": escrow_ft_offer_id_msg
+        })
+        )?
+        .deposit(ONE_YOCTO)
+        .gas(near_units::parse_gas!("300 T") as u64)
+        .transact().await?;
+
+    assert!(res.is_success());
+
+    Ok(res)
+}
+
+async fn ft_balance_of(
+    worker: &Worker<Sandbox>,
+    ft_contract: &Contract,
+    user: &Account
+) -> anyhow::Result<U128> {
+    let res: U128 = user
+        .call(&worker, ft_contract.id(), "ft_balance_of")
+        .args_json(json!({
+            "account_id": user.id(),
+        }))?
+        .view().await?
+        .json()?;
+
+    Ok(res)
+}
+
+async fn create_ft_basket(
+    worker: &Worker<Sandbox>,
+    escrow_contract: &Contract,
+    ft_contract1: &Contract,
+    ft_contract2: &Contract,
+    alice: &Account,
+    bob: &Account,
+    amount: U128
+) -> anyhow::Result<JsonEscrow> {
+    let res: JsonEscrow = alice
+        .call(&worker, escrow_contract.id(), "escrow_basket_offer")
+        .args_json(
+            json!({
+            "receiver_id": bob.id(),
+            "assets_in": [
+                { "Ft": { "ft_contract_id": ft_contract1.id(), "amount": amount } },
+            ],
+            "assets_out": [
+                { "Ft": { "ft_contract_id": ft_contract2.id(), "amount": amount } },
+            ],
+        })
+        )?
+        .gas(near_units::parse_gas!("300 T") as u64)
+        .transact().await?
+        .json()?;
+
+    Ok(res)
+}
+
+async fn prepare_ft_pair(
+    worker: &Worker<Sandbox>,
+    escrow_contract: &Contract,
+    ft_contract: &Contract,
+    owner: &Account,
+    users: &[&Account],
+    holder: &Account,
+    amount: U128
+) -> anyhow::Result<()> {
+    create_ft_contract(&worker, &ft_contract, &owner).await?;
+    storage_deposit_for_user(&worker, &ft_contract, &escrow_contract.as_account()).await?;
+
+    for user in users {
+        storage_deposit_for_user(&worker, &ft_contract, &user).await?;
+    }
+
+    let res = owner
+        .call(&worker, ft_contract.id(), "ft_transfer")
+        .args_json(
+            json!({
+            "receiver_id": holder.id(),
+            "amount": amount
+        })
+        )?
+        .deposit(ATTACHED_SUPPLY)
+        .gas(near_units::parse_gas!("300 T") as u64)
+        .transact().await?;
+
+    assert!(res.is_success());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_basket_several_deposits() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+
+    let escrow_wasm = std::fs::read(ESCROW_WASM_FILEPATH)?;
+    let escrow_contract = worker.dev_deploy(&escrow_wasm).await?;
+
+    let ft_wasm = std::fs::read(FT_WASM_FILEPATH)?;
+    let ft_contract1 = worker.dev_deploy(&ft_wasm).await?;
+    let ft_contract2 = worker.dev_deploy(&ft_wasm).await?;
+
+    let owner = worker.dev_create_account().await?;
+
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+    let keeper = worker.dev_create_account().await?;
+
+    let amount = U128(1_000_000_000_000_000_000);
+    let part = U128(amount.0 / 2);
+
+    create_escrow_contract(&worker, &escrow_contract, &owner).await?;
+
+    //FT1 for Alice, FT2 for Bob
+    prepare_ft_pair(&worker, &escrow_contract, &ft_contract1, &owner, &[&alice, &bob], &alice, amount).await?;
+    prepare_ft_pair(&worker, &escrow_contract, &ft_contract2, &owner, &[&alice, &bob], &bob, amount).await?;
+
+    let offer = create_ft_basket(
+        &worker,
+        &escrow_contract,
+        &ft_contract1,
+        &ft_contract2,
+        &alice,
+        &bob,
+        amount
+    ).await?;
+    println!("Escrow basket offer outcome: {:#?}", offer);
+
+    //Both sides fund the basket in two deposits
+    transfer_ft_to_offer(&worker, &escrow_contract, &ft_contract1, &alice, &offer.offer_id, part).await?;
+    transfer_ft_to_offer(&worker, &escrow_contract, &ft_contract2, &bob, &offer.offer_id, part).await?;
+    transfer_ft_to_offer(&worker, &escrow_contract, &ft_contract1, &alice, &offer.offer_id, part).await?;
+    transfer_ft_to_offer(&worker, &escrow_contract, &ft_contract2, &bob, &offer.offer_id, part).await?;
+
+    //Funded basket is swapped by anyone
+    let res = keeper
+        .call(&worker, escrow_contract.id(), "escrow_basket_settle")
+        .args_json(json!({
+            "offer_id": offer.offer_id,
+        }))?
+        .gas(near_units::parse_gas!("300 T") as u64)
+        .transact().await?;
+
+    println!("Escrow basket settle outcome: {:#?}", res);
+    assert!(res.is_success());
+
+    assert_eq!(ft_balance_of(&worker, &ft_contract2, &alice).await?, amount);
+    assert_eq!(ft_balance_of(&worker, &ft_contract1, &bob).await?, amount);
+
+    let res: Vec<serde_json::Value> = alice
+        .call(&worker, escrow_contract.id(), "escrow_claims")
+        .args_json(json!({
+            "account_id": alice.id(),
+        }))?
+        .view().await?
+        .json()?;
+
+    assert!(res.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_basket_refund_on_cancel() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+
+    let escrow_wasm = std::fs::read(ESCROW_WASM_FILEPATH)?;
+    let escrow_contract = worker.dev_deploy(&escrow_wasm).await?;
+
+    let ft_wasm = std::fs::read(FT_WASM_FILEPATH)?;
+    let ft_contract1 = worker.dev_deploy(&ft_wasm).await?;
+    let ft_contract2 = worker.dev_deploy(&ft_wasm).await?;
+
+    let owner = worker.dev_create_account().await?;
+
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let amount = U128(1_000_000_000_000_000_000);
+    let part = U128(amount.0 / 2);
+
+    create_escrow_contract(&worker, &escrow_contract, &owner).await?;
+
+    prepare_ft_pair(&worker, &escrow_contract, &ft_contract1, &owner, &[&alice, &bob], &alice, amount).await?;
+    prepare_ft_pair(&worker, &escrow_contract, &ft_contract2, &owner, &[&alice, &bob], &bob, amount).await?;
+
+    let offer = create_ft_basket(
+        &worker,
+        &escrow_contract,
+        &ft_contract1,
+        &ft_contract2,
+        &alice,
+        &bob,
+        amount
+    ).await?;
+
+    //Basket is funded partially by both sides
+    transfer_ft_to_offer(&worker, &escrow_contract, &ft_contract1, &alice, &offer.offer_id, amount).await?;
+    transfer_ft_to_offer(&worker, &escrow_contract, &ft_contract2, &bob, &offer.offer_id, part).await?;
+
+    //Cancel refunds the funded amounts of both sides
+    let res = remove_offer(&worker, &escrow_contract, &bob, &offer.offer_id).await?;
+    println!("Escrow remove basket outcome: {:#?}", res);
+
+    assert_eq!(ft_balance_of(&worker, &ft_contract1, &alice).await?, amount);
+    assert_eq!(ft_balance_of(&worker, &ft_contract2, &bob).await?, amount);
+
+    let res: Option<JsonEscrow> = alice
+        .call(&worker, escrow_contract.id(), "escrow_offer")
+        .args_json(json!({
+            "offer_id": offer.offer_id,
+        }))?
+        .view().await?
+        .json()?;
+
+    assert!(res.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_open_offer_accepted_by_third_account() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+
+    let escrow_wasm = std::fs::read(ESCROW_WASM_FILEPATH)?;
+    let escrow_contract = worker.dev_deploy(&escrow_wasm).await?;
+
+    let ft_wasm = std::fs::read(FT_WASM_FILEPATH)?;
+    let ft_contract1 = worker.dev_deploy(&ft_wasm).await?;
+    let ft_contract2 = worker.dev_deploy(&ft_wasm).await?;
+
+    let owner = worker.dev_create_account().await?;
+
+    let alice = worker.dev_create_account().await?;
+    let carol = worker.dev_create_account().await?;
+
+    let amount = U128(1_000_000_000_000_000_000);
+
+    create_escrow_contract(&worker, &escrow_contract, &owner).await?;
+
+    //FT1 for Alice, FT2 for Carol
+    prepare_ft_pair(
+        &worker,
+        &escrow_contract,
+        &ft_contract1,
+        &owner,
+        &[&alice, &carol],
+        &alice,
+        amount
+    ).await?;
+    prepare_ft_pair(
+        &worker,
+        &escrow_contract,
+        &ft_contract2,
+        &owner,
+        &[&alice, &carol],
+        &carol,
+        amount
+    ).await?;
+
+    //Make arg string to make an open offer of FT1 for FT2
+    let escrow_open_offer_msg = serde_json
+        ::to_string(
+            &ArgsFt::Escrow(EscrowOnFtTransferArgs {
+                ft_contract_id_out: Some(AccountId::try_from(ft_contract2.id().clone()).unwrap()),
+                ft_amount_out: Some(amount),
+                nft_contract_id_out: None,
+                nft_token_id_out: None,
+                receiver_id: None,
+                offer_id: None,
+                expires_at: None,
+                partial: None,
+                counter_offer_id: None,
+            })
+        )
+        .ok()
+        .expect("Wrong struct to stringify");
+
+    let res = alice
+        .call(&worker, ft_contract1.id(), "ft_transfer_call")
+        .args_json(
+            json!({
+            "receiver_id": escrow_contract.id(),
+            "amount": amount,
+            "msg": escrow_open_offer_msg
+        })
+        )?
+        .deposit(ONE_YOCTO)
+        .gas(near_units::parse_gas!("300 T") as u64)
+        .transact().await?;
+
+    println!("FT_transfer open offer to escrow outcome: {:#?}", res);
+    assert!(res.is_success());
+
+    //Open offer is found by the requested asset
+    let res: Vec<JsonEscrow> = carol
+        .call(&worker, escrow_contract.id(), "escrow_open_offers_by_asset")
+        .args_json(json!({
+            "contract_id": ft_contract2.id(),
+        }))?
+        .view().await?
+        .json()?;
+
+    println!("Escrow open offers by asset outcome: {:#?}", res);
+    assert_eq!(res.len(), 1);
+    assert!(res[0].receiver_id.is_none());
+
+    let offer_id = res[0].offer_id.clone();
+
+    //Carol, not known to the offer, takes it
+    transfer_ft_to_offer(&worker, &escrow_contract, &ft_contract2, &carol, &offer_id, amount).await?;
+
+    assert_eq!(ft_balance_of(&worker, &ft_contract2, &alice).await?, amount);
+    assert_eq!(ft_balance_of(&worker, &ft_contract1, &carol).await?, amount);
+
+    let res: Vec<JsonEscrow> = carol
+        .call(&worker, escrow_contract.id(), "escrow_open_offers_by_asset")
+        .args_json(json!({
+            "contract_id": ft_contract2.id(),
+        }))?
+        .view().await?
+        .json()?;
+
+    assert!(res.is_empty());
+
+    Ok(())
+}
@@ -163,6 +163,11 @@ async fn initialize_rent (
                 sale_conditions: sale_conditions.clone(),
                 min_time: MIN_TIME,
                 max_time: MAX_TIME,
+                collateral: None,
+                revenue_share: None,
+                price_tiers: None,
+                cancel_fee: None,
+                transfer_fee: None,
             })
         )
         .ok()
@@ -338,6 +343,7 @@ async fn test_nft_rent_for_ft() -> anyhow::Result<()> {
                 contract_id: AccountId::try_from(nft_contract.id().clone()).unwrap(),
                 receiver_id: AccountId::try_from(bob.id().clone()).unwrap(),
                 time: time,
+                starts_at: None,
             })
         )
         .ok()
@@ -665,6 +671,11 @@ async fn test_nft_rent_enumeration() -> anyhow::Result<()> {
                 sale_conditions: sale_conditions.clone(),
                 min_time: MIN_TIME,
                 max_time: MAX_TIME,
+                collateral: None,
+                revenue_share: None,
+                price_tiers: None,
+                cancel_fee: None,
+                transfer_fee: None,
             })
         )
         .ok()